@@ -0,0 +1,340 @@
+//! Renovação automática do token e aviso único de sessão expirada.
+//!
+//! Quando uma chamada da ponte recebe 401, [`SessionManager::refresh`] tenta
+//! renovar o token uma única vez no endpoint de refresh. Chamadas simultâneas
+//! que recebem 401 esperam a mesma renovação em vez de disparar várias. Se a
+//! renovação falhar, o evento `session-expired` é emitido uma vez para o
+//! frontend redirecionar ao login (`useSessionExpired`). A API atual não tem
+//! endpoint de refresh, então a renovação fica desligada de propósito: nenhum
+//! endpoint é configurado e o 401 expira a sessão direto. Quando a API ganhar
+//! um, basta informá-lo em `set_refresh_config`.
+//!
+//! O login também passa pelo core ([`login`]): o papel e o usuário da sessão
+//! são lidos da resposta da API e valem só para o token que veio com ela.
+
+use super::{dispatch, read_json_response, send_once, ApiRequest};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+pub const EVENT_SESSION_EXPIRED: &str = "session-expired";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshConfig {
+    /// Endpoint que devolve um novo token (POST, autenticado com o token
+    /// atual). Sem ele, um 401 emite `session-expired` sem tentar renovar.
+    pub endpoint: Option<String>,
+    /// Refresh token opcional enviado no corpo como `{"refresh_token": ...}`
+    pub refresh_token: Option<String>,
+}
+
+/// Usuário da sessão segundo a API. O papel usado pela política de endpoints
+/// vem daqui, nunca de um valor informado pelo WebView.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionIdentity {
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub role: String,
+}
+
+impl SessionIdentity {
+    /// Lê a identidade da resposta de `/auth/login`
+    fn from_login_response(body: &Value) -> Self {
+        let is_admin = body
+            .get("is_admin")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        Self {
+            user_id: body.get("user_id").and_then(|v| v.as_i64()),
+            username: body
+                .get("username")
+                .and_then(|v| v.as_str())
+                .map(|u| u.to_string()),
+            role: if is_admin { "admin" } else { "user" }.to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SessionManager {
+    app: Mutex<Option<AppHandle>>,
+    config: Mutex<RefreshConfig>,
+    identity: Mutex<Option<SessionIdentity>>,
+    /// Serializa as renovações: só uma vai à API por vez
+    refresh_lock: tokio::sync::Mutex<()>,
+    expired_notified: AtomicBool,
+}
+
+/// Extrai o novo token da resposta, aceitando os nomes usados pela API Python
+fn token_from_response(body: &Value) -> Option<String> {
+    ["session_token", "access_token", "token"]
+        .iter()
+        .find_map(|field| body.get(*field).and_then(|v| v.as_str()))
+        .map(|t| t.to_string())
+}
+
+impl SessionManager {
+    pub fn attach(&self, app: AppHandle) {
+        if let Ok(mut slot) = self.app.lock() {
+            *slot = Some(app);
+        }
+    }
+
+    pub fn set_config(&self, config: RefreshConfig) -> AppResult<()> {
+        *self
+            .config
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na configuração de refresh"))? = config;
+        Ok(())
+    }
+
+    pub fn identity(&self) -> Option<SessionIdentity> {
+        self.identity.lock().ok().and_then(|i| i.clone())
+    }
+
+    /// Papel da sessão atual; `None` se o token não veio de um login feito pelo core
+    pub fn role(&self) -> Option<String> {
+        self.identity().map(|i| i.role)
+    }
+
+    fn set_identity(&self, identity: Option<SessionIdentity>) {
+        if let Ok(mut slot) = self.identity.lock() {
+            *slot = identity;
+        }
+    }
+
+    /// Chamado quando um token novo chega por fora (login, cofre): libera um
+    /// novo aviso de sessão expirada no futuro
+    pub fn reset(&self) {
+        self.expired_notified.store(false, Ordering::SeqCst);
+    }
+
+    fn notify_expired(&self, error: &AppError) {
+        if self.expired_notified.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        warn!("[Session] Sessão expirada: {}", error);
+        if let Ok(app) = self.app.lock() {
+            if let Some(ref app) = *app {
+                let _ = app.emit(EVENT_SESSION_EXPIRED, error.clone());
+            }
+        }
+    }
+
+    /// Tenta renovar o token após um 401. `stale_token` é o token usado na
+    /// chamada que falhou: se outro refresh já o substituiu, não há o que fazer.
+    /// Retorna `true` se a requisição original deve ser repetida.
+    pub async fn refresh(
+        &self,
+        state: &AppState,
+        stale_token: Option<String>,
+        original: &AppError,
+    ) -> bool {
+        let _guard = self.refresh_lock.lock().await;
+
+        let current = state.auth_token.lock().ok().and_then(|t| t.clone());
+        if current.is_none() {
+            self.notify_expired(original);
+            return false;
+        }
+        if current != stale_token {
+            return true;
+        }
+
+        let config = match self.config.lock() {
+            Ok(c) => c.clone(),
+            Err(_) => return false,
+        };
+        let Some(endpoint) = config.endpoint else {
+            self.notify_expired(original);
+            return false;
+        };
+        let body = config
+            .refresh_token
+            .as_ref()
+            .map(|rt| json!({ "refresh_token": rt }));
+        let request = ApiRequest {
+            method: reqwest::Method::POST,
+            endpoint,
+            params: None,
+            body,
+            idempotency_key: None,
+        };
+
+        let result = match dispatch(state, &request, &[]).await {
+            Ok(response) => read_json_response(response).await,
+            Err(e) => Err(e),
+        };
+
+        match result.map(|body| (token_from_response(&body), body)) {
+            Ok((Some(token), body)) => {
+                info!("[Session] Token renovado");
+                if let Ok(mut slot) = state.auth_token.lock() {
+                    *slot = Some(token.clone());
+                }
+                if let Some(rt) = body.get("refresh_token").and_then(|v| v.as_str()) {
+                    if let Ok(mut c) = self.config.lock() {
+                        c.refresh_token = Some(rt.to_string());
+                    }
+                }
+                // Mantém o cofre em sincronia se as credenciais estiverem salvas
+                if let Ok(Some(mut credentials)) = state.vault.load() {
+                    credentials.token = Some(token);
+                    credentials.saved_at = chrono::Utc::now();
+                    if let Err(e) = state.vault.store(&credentials) {
+                        warn!("[Session] Falha ao gravar token renovado no cofre: {}", e);
+                    }
+                }
+                true
+            }
+            Ok((None, _)) => {
+                warn!("[Session] Resposta de refresh sem token");
+                self.notify_expired(original);
+                false
+            }
+            Err(e) if e.is_unreachable() => false,
+            Err(_) => {
+                self.notify_expired(original);
+                false
+            }
+        }
+    }
+}
+
+/// Quem está usando a ponte: base URL + usuário do login feito pelo core ou,
+/// sem ele, um resumo do token. Separa cache e fila offline por sessão.
+pub(crate) fn session_scope(state: &AppState) -> String {
+    let base_url = state
+        .api_base_url
+        .lock()
+        .map(|b| b.trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    let who = match state.session.identity() {
+        Some(SessionIdentity {
+            user_id: Some(id), ..
+        }) => format!("user:{}", id),
+        Some(SessionIdentity {
+            username: Some(name),
+            ..
+        }) => format!("user:{}", name),
+        _ => match state.auth_token.lock().ok().and_then(|t| t.clone()) {
+            Some(token) => {
+                let digest = Sha256::digest(token.as_bytes());
+                let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+                format!("token:{}", hex)
+            }
+            None => "anon".to_string(),
+        },
+    };
+    format!("{}#{}", base_url, who)
+}
+
+/// Aplica base URL e token à ponte. Se algum dos dois mudou, a identidade da
+/// sessão anterior é descartada junto e substituída por `identity`, que só o
+/// próprio core informa (login). Retorna `true` se houve mudança.
+pub(crate) fn apply_connection(
+    state: &AppState,
+    base_url: String,
+    token: Option<String>,
+    identity: Option<SessionIdentity>,
+) -> AppResult<bool> {
+    let base_changed = {
+        let mut current = state
+            .api_base_url
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no base_url"))?;
+        let changed = *current != base_url;
+        *current = base_url;
+        changed
+    };
+    let token_changed = {
+        let mut current = state
+            .auth_token
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no auth_token"))?;
+        let changed = *current != token;
+        *current = token;
+        changed
+    };
+
+    let changed = base_changed || token_changed;
+    if changed || identity.is_some() {
+        state.session.set_identity(identity);
+    }
+    state.session.reset();
+    if changed {
+        state.realtime.reconnect();
+    }
+    Ok(changed)
+}
+
+/// Faz o login na API pelo core e adota o token devolvido, com o papel e o
+/// usuário lidos da mesma resposta. Devolve a resposta da API sem alterações.
+pub async fn login(state: &AppState, username: String, password: String) -> AppResult<Value> {
+    let request = ApiRequest {
+        method: reqwest::Method::POST,
+        endpoint: "/auth/login".to_string(),
+        params: None,
+        body: Some(json!({ "username": username, "password": password })),
+        idempotency_key: None,
+    };
+    let body = send_once(state, &request).await?;
+
+    if let Some(token) = token_from_response(&body) {
+        let identity = SessionIdentity::from_login_response(&body);
+        info!(
+            "[Session] Login de {} ({})",
+            identity.username.as_deref().unwrap_or("?"),
+            identity.role
+        );
+        let base_url = state
+            .api_base_url
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no base_url"))?
+            .clone();
+        apply_connection(state, base_url, Some(token), Some(identity))?;
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_from_response() {
+        assert_eq!(
+            token_from_response(&json!({ "session_token": "abc" })),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            token_from_response(&json!({ "access_token": "xyz", "token_type": "bearer" })),
+            Some("xyz".to_string())
+        );
+        assert_eq!(token_from_response(&json!({ "detail": "ok" })), None);
+    }
+
+    #[test]
+    fn test_identity_from_login_response() {
+        let identity = SessionIdentity::from_login_response(&json!({
+            "success": true,
+            "user_id": 4,
+            "username": "ana",
+            "is_admin": true,
+            "session_token": "abc"
+        }));
+        assert_eq!(identity.user_id, Some(4));
+        assert_eq!(identity.username.as_deref(), Some("ana"));
+        assert_eq!(identity.role, "admin");
+
+        let identity = SessionIdentity::from_login_response(&json!({ "user_id": 5 }));
+        assert_eq!(identity.role, "user");
+    }
+}
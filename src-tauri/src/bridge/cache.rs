@@ -0,0 +1,470 @@
+//! Cache de leitura das respostas GET da ponte.
+//!
+//! Cada resposta é guardada em memória e em `<app_data>/http_cache/`, junto com
+//! os validadores (`ETag`/`Last-Modified`) e o `Cache-Control` devolvidos pela
+//! API. Enquanto a entrada está dentro do TTL ela é servida sem rede; depois
+//! disso é revalidada com `If-None-Match`/`If-Modified-Since`. Sem conexão, a
+//! última cópia conhecida é servida marcada como `stale`.
+//!
+//! As entradas são separadas por base URL e usuário (`auth::session_scope`):
+//! trocar de servidor ou de conta nunca devolve dados da sessão anterior.
+
+use super::{auth, dispatch, read_json_response, with_retry, ApiRequest};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use crate::storage;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderName, CACHE_CONTROL, ETAG, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::{debug, warn};
+
+/// TTL aplicado a endpoints com prefixo `prefix`, sobrepondo o `max-age` da API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtlRule {
+    pub prefix: String,
+    pub ttl_secs: u64,
+}
+
+fn default_ttl_rules() -> Vec<TtlRule> {
+    vec![
+        TtlRule {
+            prefix: "/pedidos/".to_string(),
+            ttl_secs: 10,
+        },
+        TtlRule {
+            prefix: "/maquinas/ativos".to_string(),
+            ttl_secs: 30,
+        },
+        TtlRule {
+            prefix: "/materiais/stats".to_string(),
+            ttl_secs: 60,
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    endpoint: String,
+    body: Value,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    max_age_secs: Option<u64>,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Resposta de `rust_api_get_cached`
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedResponse {
+    pub data: Value,
+    /// Veio do cache (sem rede ou revalidada com 304)
+    pub from_cache: bool,
+    /// A API está inacessível e `data` pode estar desatualizado
+    pub stale: bool,
+    pub fetched_at: DateTime<Utc>,
+}
+
+pub struct ResponseCache {
+    dir: Mutex<Option<PathBuf>>,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    ttl_rules: Mutex<Vec<TtlRule>>,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self {
+            dir: Mutex::new(None),
+            entries: Mutex::new(HashMap::new()),
+            ttl_rules: Mutex::new(default_ttl_rules()),
+        }
+    }
+}
+
+/// Diretivas de `Cache-Control` relevantes para o cache
+struct CacheControl {
+    no_store: bool,
+    max_age_secs: Option<u64>,
+}
+
+fn parse_cache_control(value: &str) -> CacheControl {
+    let mut cc = CacheControl {
+        no_store: false,
+        max_age_secs: None,
+    };
+    for directive in value.split(',').map(|d| d.trim().to_ascii_lowercase()) {
+        if directive == "no-store" {
+            cc.no_store = true;
+        } else if directive == "no-cache" {
+            cc.max_age_secs = Some(0);
+        } else if let Some(age) = directive.strip_prefix("max-age=") {
+            cc.max_age_secs = age.parse().ok();
+        }
+    }
+    cc
+}
+
+/// Chave do cache: endpoint + parâmetros ordenados
+fn cache_key(request: &ApiRequest) -> String {
+    let mut key = request.endpoint.clone();
+    if let Some(ref params) = request.params {
+        let mut pairs: Vec<_> = params.iter().collect();
+        pairs.sort();
+        for (i, (k, v)) in pairs.into_iter().enumerate() {
+            key.push(if i == 0 { '?' } else { '&' });
+            key.push_str(&format!("{}={}", k, v));
+        }
+    }
+    key
+}
+
+/// Chave de armazenamento: sessão (base URL + usuário) + `cache_key`
+pub(super) fn scoped_key(state: &AppState, request: &ApiRequest) -> String {
+    format!("{}|{}", auth::session_scope(state), cache_key(request))
+}
+
+/// Nome do arquivo em disco. SHA-256 porque o nome precisa ser o mesmo entre
+/// versões do app, o que o `DefaultHasher` da std não garante.
+fn file_name_for(key: &str) -> String {
+    let digest: String = Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}.json", digest)
+}
+
+/// Recurso de um endpoint: o primeiro segmento do path (`/pedidos/7` → `pedidos`)
+pub(super) fn resource_of(endpoint: &str) -> &str {
+    endpoint
+        .trim_start_matches('/')
+        .split(['/', '?'])
+        .next()
+        .unwrap_or_default()
+}
+
+impl ResponseCache {
+    pub fn set_dir(&self, dir: PathBuf) -> AppResult<()> {
+        *self
+            .dir
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no diretório de cache"))? = Some(dir);
+        Ok(())
+    }
+
+    pub fn set_ttl_rules(&self, rules: Vec<TtlRule>) -> AppResult<()> {
+        *self
+            .ttl_rules
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex nas regras de TTL"))? = rules;
+        Ok(())
+    }
+
+    pub fn ttl_rules(&self) -> AppResult<Vec<TtlRule>> {
+        Ok(self
+            .ttl_rules
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex nas regras de TTL"))?
+            .clone())
+    }
+
+    fn disk_path(&self, key: &str) -> Option<PathBuf> {
+        self.dir
+            .lock()
+            .ok()
+            .and_then(|dir| dir.as_ref().map(|d| d.join(file_name_for(key))))
+    }
+
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        if let Some(entry) = self.entries.lock().ok()?.get(key) {
+            return Some(entry.clone());
+        }
+
+        // Não está em memória: tenta a cópia em disco de uma sessão anterior
+        let entry: CacheEntry = storage::read_json(&self.disk_path(key)?).ok()??;
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key.to_string(), entry.clone());
+        }
+        Some(entry)
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        if let Some(path) = self.disk_path(key) {
+            if let Err(e) = storage::write_json(&path, &entry) {
+                warn!("[Cache] Falha ao gravar {}: {}", key, e);
+            }
+        }
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key.to_string(), entry);
+        }
+    }
+
+    /// Tempo de vida da entrada. Com `use_rules`, a regra por endpoint tem
+    /// prioridade sobre o `max-age`; sem, vale só o que a API mandou.
+    fn ttl_for(&self, entry: &CacheEntry, use_rules: bool) -> u64 {
+        if !use_rules {
+            return entry.max_age_secs.unwrap_or(0);
+        }
+        let rule = self.ttl_rules.lock().ok().and_then(|rules| {
+            rules
+                .iter()
+                .filter(|r| entry.endpoint.starts_with(&r.prefix))
+                .max_by_key(|r| r.prefix.len())
+                .map(|r| r.ttl_secs)
+        });
+        rule.or(entry.max_age_secs).unwrap_or(0)
+    }
+
+    /// Remove as entradas do mesmo recurso (primeiro segmento do path) após uma mutação
+    pub fn invalidate_resource(&self, endpoint: &str) {
+        let resource = resource_of(endpoint);
+        if resource.is_empty() {
+            return;
+        }
+
+        let keys: Vec<String> = match self.entries.lock() {
+            Ok(mut entries) => {
+                let keys = entries
+                    .iter()
+                    .filter(|(_, entry)| resource_of(&entry.endpoint) == resource)
+                    .map(|(key, _)| key.clone())
+                    .collect::<Vec<_>>();
+                for key in &keys {
+                    entries.remove(key);
+                }
+                keys
+            }
+            Err(_) => return,
+        };
+
+        for key in keys {
+            if let Some(path) = self.disk_path(&key) {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    pub fn clear(&self) -> AppResult<()> {
+        self.entries
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no cache"))?
+            .clear();
+        if let Ok(dir) = self.dir.lock() {
+            if let Some(ref d) = *dir {
+                if d.exists() {
+                    std::fs::remove_dir_all(d)
+                        .map_err(|e| AppError::io(format!("Erro ao limpar cache: {}", e)))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// GET com cache: serve do cache dentro do TTL e revalida com validadores
+/// condicionais. Com `allow_stale` (`rust_api_get_cached`) valem as regras de
+/// TTL e, sem conexão, a última cópia volta marcada como `stale`. Sem ele
+/// (`rust_api_get`) só o `Cache-Control` da API decide o que pode ser reusado.
+pub async fn get(
+    state: &AppState,
+    request: &ApiRequest,
+    allow_stale: bool,
+) -> AppResult<CachedResponse> {
+    let cache = &state.response_cache;
+    let key = scoped_key(state, request);
+    let cached = cache.get(&key);
+
+    if let Some(ref entry) = cached {
+        let age = (Utc::now() - entry.fetched_at).num_seconds().max(0) as u64;
+        if age < cache.ttl_for(entry, allow_stale) {
+            debug!("[Cache] HIT {} (idade {}s)", key, age);
+            return Ok(CachedResponse {
+                data: entry.body.clone(),
+                from_cache: true,
+                stale: false,
+                fetched_at: entry.fetched_at,
+            });
+        }
+    }
+
+    let mut validators = Vec::new();
+    if let Some(ref entry) = cached {
+        if let Some(ref etag) = entry.etag {
+            validators.push(("If-None-Match", etag.clone()));
+        }
+        if let Some(ref last_modified) = entry.last_modified {
+            validators.push(("If-Modified-Since", last_modified.clone()));
+        }
+    }
+
+    let validators = &validators;
+    let result = with_retry(state, request, move || async move {
+        let response = dispatch(state, request, validators).await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        let header = |name: HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let cache_control = header(CACHE_CONTROL).map(|v| parse_cache_control(&v));
+
+        let body = read_json_response(response).await?;
+        Ok(Some((body, etag, last_modified, cache_control)))
+    })
+    .await;
+
+    match (result, cached) {
+        (Ok(None), Some(mut entry)) => {
+            debug!("[Cache] 304 {}", key);
+            entry.fetched_at = Utc::now();
+            cache.put(&key, entry.clone());
+            Ok(CachedResponse {
+                data: entry.body,
+                from_cache: true,
+                stale: false,
+                fetched_at: entry.fetched_at,
+            })
+        }
+        (Ok(None), None) => Err(AppError::parse(
+            "API respondeu 304 sem cópia local em cache",
+        )),
+        (Ok(Some((body, etag, last_modified, cache_control))), _) => {
+            let fetched_at = Utc::now();
+            let no_store = cache_control.as_ref().is_some_and(|cc| cc.no_store);
+            if !no_store {
+                cache.put(
+                    &key,
+                    CacheEntry {
+                        endpoint: request.endpoint.clone(),
+                        body: body.clone(),
+                        etag,
+                        last_modified,
+                        max_age_secs: cache_control.and_then(|cc| cc.max_age_secs),
+                        fetched_at,
+                    },
+                );
+            }
+            Ok(CachedResponse {
+                data: body,
+                from_cache: false,
+                stale: false,
+                fetched_at,
+            })
+        }
+        (Err(e), Some(entry)) if allow_stale && e.is_unreachable() => {
+            warn!("[Cache] API inacessível, servindo cópia antiga de {}", key);
+            Ok(CachedResponse {
+                data: entry.body,
+                from_cache: true,
+                stale: true,
+                fetched_at: entry.fetched_at,
+            })
+        }
+        (Err(e), _) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_is_order_independent() {
+        let mut a = HashMap::new();
+        a.insert("status".to_string(), "pendente".to_string());
+        a.insert("limit".to_string(), "50".to_string());
+        let mut b = HashMap::new();
+        b.insert("limit".to_string(), "50".to_string());
+        b.insert("status".to_string(), "pendente".to_string());
+
+        let key_a = cache_key(&ApiRequest::get("/pedidos/".to_string(), Some(a)));
+        let key_b = cache_key(&ApiRequest::get("/pedidos/".to_string(), Some(b)));
+        assert_eq!(key_a, key_b);
+        assert_eq!(key_a, "/pedidos/?limit=50&status=pendente");
+    }
+
+    #[test]
+    fn test_parse_cache_control() {
+        let cc = parse_cache_control("public, max-age=120");
+        assert_eq!(cc.max_age_secs, Some(120));
+        assert!(!cc.no_store);
+
+        let cc = parse_cache_control("no-store");
+        assert!(cc.no_store);
+    }
+
+    #[test]
+    fn test_ttl_rules_only_apply_to_cached_reads() {
+        let cache = ResponseCache::default();
+        let entry = CacheEntry {
+            endpoint: "/pedidos/".to_string(),
+            body: Value::Null,
+            etag: None,
+            last_modified: None,
+            max_age_secs: Some(0),
+            fetched_at: Utc::now(),
+        };
+        assert_eq!(cache.ttl_for(&entry, true), 10);
+        assert_eq!(cache.ttl_for(&entry, false), 0);
+    }
+
+    #[test]
+    fn test_cache_is_scoped_by_session() {
+        let state = AppState::new();
+        let request = ApiRequest::get("/pedidos/".to_string(), None);
+        *state.api_base_url.lock().unwrap() = "http://a:8000".to_string();
+        *state.auth_token.lock().unwrap() = Some("token-1".to_string());
+        let first = scoped_key(&state, &request);
+
+        *state.auth_token.lock().unwrap() = Some("token-2".to_string());
+        let other_token = scoped_key(&state, &request);
+        *state.api_base_url.lock().unwrap() = "http://b:8000".to_string();
+        let other_server = scoped_key(&state, &request);
+
+        assert_ne!(first, other_token);
+        assert_ne!(other_token, other_server);
+        assert!(first.ends_with("|/pedidos/"));
+    }
+
+    #[test]
+    fn test_invalidate_resource_matches_whole_segment() {
+        let cache = ResponseCache::default();
+        let entry = |endpoint: &str| CacheEntry {
+            endpoint: endpoint.to_string(),
+            body: Value::Null,
+            etag: None,
+            last_modified: None,
+            max_age_secs: None,
+            fetched_at: Utc::now(),
+        };
+        cache.put("a", entry("/pedidos/"));
+        cache.put("b", entry("/pedidos/7"));
+        cache.put("c", entry("/pedidos-itens/"));
+        cache.put("d", entry("/clientes/"));
+
+        cache.invalidate_resource("/pedidos/7/status");
+
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+        assert!(cache.get("d").is_some());
+    }
+
+    #[test]
+    fn test_file_name_is_stable() {
+        assert_eq!(
+            file_name_for("x"),
+            "2d711642b726b04401627ca9fbac32f5c8530fb1903cc4db02258717921a4881.json"
+        );
+    }
+}
@@ -0,0 +1,548 @@
+//! Diagnóstico de conectividade com a API configurada.
+//!
+//! Automatiza os passos de `docs/TROUBLESHOOTING_REDE.md`: resolve o DNS,
+//! abre TCP, faz o handshake TLS, mede a latência de `/health`, confere o
+//! token e compara o relógio local com o header `Date` do servidor. Cada
+//! etapa vira um [`DiagnosticCheck`]; etapas que dependem de uma anterior que
+//! falhou são puladas. O relatório tem versão em texto para colar em chamados.
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use reqwest::header::{AUTHORIZATION, DATE};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+const STEP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Diferença de relógio que já merece atenção (logs fora de ordem)
+const SKEW_WARN_SECS: i64 = 30;
+/// Diferença que costuma invalidar tokens por `exp`/`iat`
+const SKEW_FAIL_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsOptions {
+    pub health_endpoint: String,
+    /// GET autenticado e barato, usado para conferir o token
+    pub auth_endpoint: String,
+    /// Quantas vezes medir o round-trip de `health_endpoint`
+    pub latency_samples: u32,
+}
+
+impl Default for DiagnosticsOptions {
+    fn default() -> Self {
+        Self {
+            health_endpoint: "/health".to_string(),
+            auth_endpoint: "/pedidos/?limit=1".to_string(),
+            latency_samples: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckOutcome {
+    Ok,
+    Warning,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticCheck {
+    pub name: &'static str,
+    pub outcome: CheckOutcome,
+    pub duration_ms: Option<u64>,
+    pub detail: String,
+}
+
+impl DiagnosticCheck {
+    fn new(name: &'static str, outcome: CheckOutcome, started: Instant, detail: String) -> Self {
+        Self {
+            name,
+            outcome,
+            duration_ms: Some(started.elapsed().as_millis() as u64),
+            detail,
+        }
+    }
+
+    fn skipped(name: &'static str, reason: &str) -> Self {
+        Self {
+            name,
+            outcome: CheckOutcome::Skipped,
+            duration_ms: None,
+            detail: reason.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyStats {
+    pub samples: u32,
+    pub min_ms: u64,
+    pub avg_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    pub base_url: String,
+    pub proxy: Option<String>,
+    pub app_version: &'static str,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub latency: Option<LatencyStats>,
+    /// Relógio do servidor menos o local, em segundos
+    pub clock_skew_secs: Option<i64>,
+    pub checks: Vec<DiagnosticCheck>,
+    /// `true` se nenhuma etapa falhou
+    pub healthy: bool,
+}
+
+impl DiagnosticsReport {
+    /// Versão em texto puro, para a área de transferência
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Diagnóstico de rede - SGP v{}", self.app_version);
+        let _ = writeln!(out, "API: {}", self.base_url);
+        if let Some(ref proxy) = self.proxy {
+            let _ = writeln!(out, "Proxy: {}", proxy);
+        }
+        let _ = writeln!(out, "Início: {}", self.started_at.to_rfc3339());
+        let _ = writeln!(out);
+        for check in &self.checks {
+            let mark = match check.outcome {
+                CheckOutcome::Ok => "OK",
+                CheckOutcome::Warning => "AVISO",
+                CheckOutcome::Failed => "FALHA",
+                CheckOutcome::Skipped => "PULADO",
+            };
+            let duration = check
+                .duration_ms
+                .map(|ms| format!(" ({} ms)", ms))
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "[{}] {}{}: {}",
+                mark, check.name, duration, check.detail
+            );
+        }
+        if let Some(ref l) = self.latency {
+            let _ = writeln!(
+                out,
+                "\nLatência ({} amostras): min {} ms / média {} ms / máx {} ms",
+                l.samples, l.min_ms, l.avg_ms, l.max_ms
+            );
+        }
+        if let Some(skew) = self.clock_skew_secs {
+            let _ = writeln!(out, "Diferença de relógio: {:+} s", skew);
+        }
+        let _ = writeln!(
+            out,
+            "\nResultado: {}",
+            if self.healthy {
+                "sem falhas"
+            } else {
+                "com falhas"
+            }
+        );
+        out
+    }
+}
+
+fn skew_outcome(skew_secs: i64) -> CheckOutcome {
+    match skew_secs.abs() {
+        s if s >= SKEW_FAIL_SECS => CheckOutcome::Failed,
+        s if s >= SKEW_WARN_SECS => CheckOutcome::Warning,
+        _ => CheckOutcome::Ok,
+    }
+}
+
+/// Diferença entre o `Date` do servidor e o instante local no meio da requisição
+fn clock_skew(
+    date_header: &str,
+    sent_at: DateTime<Utc>,
+    received_at: DateTime<Utc>,
+) -> Option<i64> {
+    let server = DateTime::parse_from_rfc2822(date_header).ok()?;
+    let midpoint = sent_at + (received_at - sent_at) / 2;
+    Some((server.with_timezone(&Utc) - midpoint).num_seconds())
+}
+
+fn join_url(base_url: &str, endpoint: &str) -> String {
+    format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        endpoint.trim_start_matches('/')
+    )
+}
+
+pub async fn run(state: &AppState, options: DiagnosticsOptions) -> AppResult<DiagnosticsReport> {
+    let base_url = state
+        .api_base_url
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex na URL base"))?
+        .clone();
+    if base_url.is_empty() {
+        return Err(AppError::config("URL da API não configurada"));
+    }
+    let url = Url::parse(&base_url)
+        .map_err(|e| AppError::config(format!("URL da API inválida: {}", e)))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| AppError::config("URL da API sem host"))?
+        .trim_matches(|c| c == '[' || c == ']')
+        .to_string();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let token = state
+        .auth_token
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex no token"))?
+        .clone();
+    let proxy = state.http.config()?.proxy_url;
+
+    let started_at = Utc::now();
+    let mut checks = Vec::new();
+
+    // 1. DNS
+    let started = Instant::now();
+    let addrs: Vec<SocketAddr> =
+        match tokio::time::timeout(STEP_TIMEOUT, tokio::net::lookup_host((host.as_str(), port)))
+            .await
+        {
+            Ok(Ok(addrs)) => addrs.collect(),
+            Ok(Err(e)) => {
+                checks.push(DiagnosticCheck::new(
+                    "DNS",
+                    CheckOutcome::Failed,
+                    started,
+                    format!("Não resolveu {}: {}", host, e),
+                ));
+                Vec::new()
+            }
+            Err(_) => {
+                checks.push(DiagnosticCheck::new(
+                    "DNS",
+                    CheckOutcome::Failed,
+                    started,
+                    format!("Tempo esgotado resolvendo {}", host),
+                ));
+                Vec::new()
+            }
+        };
+    if !addrs.is_empty() {
+        let list: Vec<String> = addrs.iter().map(|a| a.ip().to_string()).collect();
+        checks.push(DiagnosticCheck::new(
+            "DNS",
+            CheckOutcome::Ok,
+            started,
+            format!("{} -> {}", host, list.join(", ")),
+        ));
+    }
+
+    // 2. TCP
+    let mut reachable: Option<SocketAddr> = None;
+    if addrs.is_empty() {
+        checks.push(DiagnosticCheck::skipped("TCP", "DNS não resolveu"));
+    } else {
+        let started = Instant::now();
+        let mut errors = Vec::new();
+        for addr in &addrs {
+            match tokio::time::timeout(STEP_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+                Ok(Ok(_)) => {
+                    reachable = Some(*addr);
+                    break;
+                }
+                Ok(Err(e)) => errors.push(format!("{}: {}", addr, e)),
+                Err(_) => errors.push(format!("{}: tempo esgotado", addr)),
+            }
+        }
+        checks.push(match reachable {
+            Some(addr) => DiagnosticCheck::new(
+                "TCP",
+                CheckOutcome::Ok,
+                started,
+                format!("Conectou em {}", addr),
+            ),
+            // Atrás de proxy a conexão direta pode ser bloqueada sem que a API esteja fora
+            None if proxy.is_some() => DiagnosticCheck::new(
+                "TCP",
+                CheckOutcome::Warning,
+                started,
+                format!(
+                    "Sem conexão direta (há proxy configurado): {}",
+                    errors.join("; ")
+                ),
+            ),
+            None => DiagnosticCheck::new(
+                "TCP",
+                CheckOutcome::Failed,
+                started,
+                format!(
+                    "Porta {} inacessível (firewall ou API fora do ar?): {}",
+                    port,
+                    errors.join("; ")
+                ),
+            ),
+        });
+    }
+
+    // 3. TLS
+    if url.scheme() != "https" {
+        checks.push(DiagnosticCheck::skipped("TLS", "URL sem HTTPS"));
+    } else if let Some(addr) = reachable {
+        let started = Instant::now();
+        let connector = state.http.native_tls_connector()?;
+        let tls_host = host.clone();
+        let handshake = tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+            let stream = std::net::TcpStream::connect_timeout(&addr, STEP_TIMEOUT)
+                .map_err(|e| e.to_string())?;
+            let _ = stream.set_read_timeout(Some(STEP_TIMEOUT));
+            let _ = stream.set_write_timeout(Some(STEP_TIMEOUT));
+            let tls = connector
+                .connect(&tls_host, stream)
+                .map_err(|e| e.to_string())?;
+            let fingerprint = tls
+                .peer_certificate()
+                .ok()
+                .flatten()
+                .and_then(|cert| cert.to_der().ok())
+                .map(|der| {
+                    Sha256::digest(der)
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<String>()
+                })
+                .unwrap_or_else(|| "indisponível".to_string());
+            Ok(fingerprint)
+        })
+        .await
+        .map_err(|e| AppError::internal(format!("Falha na tarefa de TLS: {}", e)))?;
+        checks.push(match handshake {
+            Ok(fingerprint) => DiagnosticCheck::new(
+                "TLS",
+                CheckOutcome::Ok,
+                started,
+                format!("Handshake concluído; certificado SHA-256 {}", fingerprint),
+            ),
+            Err(e) => DiagnosticCheck::new(
+                "TLS",
+                CheckOutcome::Failed,
+                started,
+                format!(
+                    "Handshake falhou (CA não confiável ou hostname divergente?): {}",
+                    e
+                ),
+            ),
+        });
+    } else {
+        checks.push(DiagnosticCheck::skipped("TLS", "Sem conexão TCP"));
+    }
+
+    // 4. HTTP round-trip (também vale pelo proxy, então não depende do TCP direto)
+    let client = state.http.client()?;
+    let health_url = join_url(&base_url, &options.health_endpoint);
+    let mut samples = Vec::new();
+    let mut clock_skew_secs = None;
+    let mut http_error = None;
+    let mut http_status = None;
+    for _ in 0..options.latency_samples.clamp(1, 10) {
+        let sent_at = Utc::now();
+        let started = Instant::now();
+        match client.get(&health_url).timeout(STEP_TIMEOUT).send().await {
+            Ok(response) => {
+                samples.push(started.elapsed().as_millis() as u64);
+                http_status = Some(response.status());
+                if let Err(e) = state.http.check_pin(&response) {
+                    http_error = Some(e.message);
+                    break;
+                }
+                if clock_skew_secs.is_none() {
+                    clock_skew_secs = response
+                        .headers()
+                        .get(DATE)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|date| clock_skew(date, sent_at, Utc::now()));
+                }
+            }
+            Err(e) => {
+                http_error = Some(AppError::from(e).message);
+                break;
+            }
+        }
+    }
+    let latency = (!samples.is_empty()).then(|| LatencyStats {
+        samples: samples.len() as u32,
+        min_ms: samples.iter().copied().min().unwrap_or(0),
+        avg_ms: samples.iter().sum::<u64>() / samples.len() as u64,
+        max_ms: samples.iter().copied().max().unwrap_or(0),
+    });
+    let http_detail = |status: reqwest::StatusCode| {
+        format!(
+            "GET {} -> {}{}",
+            options.health_endpoint,
+            status.as_u16(),
+            latency
+                .as_ref()
+                .map(|l| format!(", média {} ms", l.avg_ms))
+                .unwrap_or_default()
+        )
+    };
+    checks.push(match (&http_error, http_status) {
+        (Some(e), _) => DiagnosticCheck {
+            name: "HTTP",
+            outcome: CheckOutcome::Failed,
+            duration_ms: samples.last().copied(),
+            detail: e.clone(),
+        },
+        (None, Some(status)) => DiagnosticCheck {
+            name: "HTTP",
+            outcome: if status.is_success() {
+                CheckOutcome::Ok
+            } else {
+                CheckOutcome::Warning
+            },
+            duration_ms: latency.as_ref().map(|l| l.avg_ms),
+            detail: http_detail(status),
+        },
+        (None, None) => DiagnosticCheck::skipped("HTTP", "Nenhuma amostra"),
+    });
+
+    // 5. Autenticação
+    match token {
+        None => checks.push(DiagnosticCheck::skipped("Autenticação", "Sem sessão ativa")),
+        Some(_) if http_error.is_some() => {
+            checks.push(DiagnosticCheck::skipped("Autenticação", "API inacessível"))
+        }
+        Some(token) => {
+            let started = Instant::now();
+            let result = client
+                .get(join_url(&base_url, &options.auth_endpoint))
+                .header(AUTHORIZATION, format!("Bearer {}", token))
+                .timeout(STEP_TIMEOUT)
+                .send()
+                .await;
+            checks.push(match result {
+                Ok(r) if r.status().is_success() => DiagnosticCheck::new(
+                    "Autenticação",
+                    CheckOutcome::Ok,
+                    started,
+                    "Token aceito".to_string(),
+                ),
+                Ok(r) if matches!(r.status().as_u16(), 401 | 403) => DiagnosticCheck::new(
+                    "Autenticação",
+                    CheckOutcome::Failed,
+                    started,
+                    format!(
+                        "Token recusado ({}); faça login novamente",
+                        r.status().as_u16()
+                    ),
+                ),
+                Ok(r) => DiagnosticCheck::new(
+                    "Autenticação",
+                    CheckOutcome::Warning,
+                    started,
+                    format!(
+                        "GET {} respondeu {}",
+                        options.auth_endpoint,
+                        r.status().as_u16()
+                    ),
+                ),
+                Err(e) => DiagnosticCheck::new(
+                    "Autenticação",
+                    CheckOutcome::Failed,
+                    started,
+                    AppError::from(e).message,
+                ),
+            });
+        }
+    }
+
+    // 6. Relógio
+    checks.push(match clock_skew_secs {
+        Some(skew) => DiagnosticCheck {
+            name: "Relógio",
+            outcome: skew_outcome(skew),
+            duration_ms: None,
+            detail: format!("Servidor está {:+} s em relação a esta máquina", skew),
+        },
+        None => DiagnosticCheck::skipped("Relógio", "Servidor não enviou o header Date"),
+    });
+
+    let healthy = checks.iter().all(|c| c.outcome != CheckOutcome::Failed);
+    let finished_at = Utc::now();
+    info!(
+        "[Diagnostics] {} em {} ms: {}",
+        base_url,
+        (finished_at - started_at).num_milliseconds(),
+        if healthy { "sem falhas" } else { "com falhas" }
+    );
+
+    Ok(DiagnosticsReport {
+        base_url,
+        proxy,
+        app_version: env!("CARGO_PKG_VERSION"),
+        started_at,
+        finished_at,
+        latency,
+        clock_skew_secs,
+        checks,
+        healthy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration as ChronoDuration, TimeZone};
+
+    #[test]
+    fn skew_uses_request_midpoint() {
+        let sent = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let received = sent + ChronoDuration::seconds(2);
+        let skew = clock_skew("Fri, 10 May 2024 12:01:01 GMT", sent, received);
+        assert_eq!(skew, Some(60));
+        assert_eq!(clock_skew("ontem", sent, received), None);
+    }
+
+    #[test]
+    fn skew_thresholds() {
+        assert_eq!(skew_outcome(5), CheckOutcome::Ok);
+        assert_eq!(skew_outcome(-45), CheckOutcome::Warning);
+        assert_eq!(skew_outcome(600), CheckOutcome::Failed);
+    }
+
+    #[test]
+    fn text_report_lists_every_check() {
+        let now = Utc::now();
+        let report = DiagnosticsReport {
+            base_url: "http://192.168.15.2:8000".to_string(),
+            proxy: None,
+            app_version: "0.0.0",
+            started_at: now,
+            finished_at: now,
+            latency: None,
+            clock_skew_secs: Some(-3),
+            checks: vec![
+                DiagnosticCheck::skipped("TLS", "URL sem HTTPS"),
+                DiagnosticCheck {
+                    name: "TCP",
+                    outcome: CheckOutcome::Failed,
+                    duration_ms: Some(10),
+                    detail: "recusado".to_string(),
+                },
+            ],
+            healthy: false,
+        };
+        let text = report.to_text();
+        assert!(text.contains("[PULADO] TLS: URL sem HTTPS"));
+        assert!(text.contains("[FALHA] TCP (10 ms): recusado"));
+        assert!(text.contains("-3 s"));
+        assert!(text.contains("com falhas"));
+    }
+}
@@ -0,0 +1,280 @@
+//! Cancelamento de chamadas da ponte e coalescência de GETs idênticos.
+//!
+//! Toda chamada pode levar um `request_id`. Uma nova chamada com o mesmo id
+//! cancela a anterior (útil para filtros digitados rapidamente) e
+//! `cancel_request` cancela explicitamente. GETs idênticos simultâneos
+//! compartilham uma única ida à rede.
+
+use super::cache::{self, CachedResponse};
+use super::ApiRequest;
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::state::AppState;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+type GetResult = AppResult<CachedResponse>;
+
+#[derive(Default)]
+pub struct InflightRegistry {
+    generation: AtomicU64,
+    tokens: Mutex<HashMap<String, (u64, CancellationToken)>>,
+    gets: Mutex<HashMap<String, broadcast::Sender<GetResult>>>,
+}
+
+impl InflightRegistry {
+    /// Registra o id, cancelando uma chamada anterior que ainda use o mesmo id
+    fn register(&self, request_id: &str) -> (u64, CancellationToken) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst);
+        let token = CancellationToken::new();
+        if let Ok(mut tokens) = self.tokens.lock() {
+            if let Some((_, previous)) =
+                tokens.insert(request_id.to_string(), (generation, token.clone()))
+            {
+                debug!(
+                    "[Inflight] {} substituída por chamada mais nova",
+                    request_id
+                );
+                previous.cancel();
+            }
+        }
+        (generation, token)
+    }
+
+    fn unregister(&self, request_id: &str, generation: u64) {
+        if let Ok(mut tokens) = self.tokens.lock() {
+            if tokens
+                .get(request_id)
+                .is_some_and(|(g, _)| *g == generation)
+            {
+                tokens.remove(request_id);
+            }
+        }
+    }
+
+    pub fn cancel(&self, request_id: &str) -> bool {
+        match self
+            .tokens
+            .lock()
+            .ok()
+            .and_then(|mut tokens| tokens.remove(request_id))
+        {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Roda `fetch` uma vez para as chamadas simultâneas com a mesma `key`;
+    /// as demais recebem o mesmo resultado
+    async fn coalesce<F, Fut>(&self, key: String, fetch: F) -> GetResult
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = GetResult>,
+    {
+        let follower = {
+            let mut gets = self
+                .gets
+                .lock()
+                .map_err(|_| AppError::internal("Erro de mutex nas chamadas em andamento"))?;
+            match gets.get(&key) {
+                Some(sender) => Some(sender.subscribe()),
+                None => {
+                    gets.insert(key.clone(), broadcast::channel(1).0);
+                    None
+                }
+            }
+        };
+
+        if let Some(mut receiver) = follower {
+            debug!("[Inflight] Reaproveitando GET em andamento: {}", key);
+            match receiver.recv().await {
+                // Se a chamada líder foi cancelada, esta segue por conta própria
+                Ok(Err(e)) if e.kind == ErrorKind::Cancelled => {}
+                Ok(result) => return result,
+                Err(_) => {}
+            }
+            return fetch().await;
+        }
+
+        // Garante a remoção da chave mesmo se esta chamada for cancelada no meio
+        struct Leader<'a> {
+            registry: &'a InflightRegistry,
+            key: String,
+            result: Option<GetResult>,
+        }
+        impl Drop for Leader<'_> {
+            fn drop(&mut self) {
+                let sender = self
+                    .registry
+                    .gets
+                    .lock()
+                    .ok()
+                    .and_then(|mut gets| gets.remove(&self.key));
+                if let Some(sender) = sender {
+                    let result = self
+                        .result
+                        .take()
+                        .unwrap_or_else(|| Err(AppError::cancelled("Requisição líder cancelada")));
+                    let _ = sender.send(result);
+                }
+            }
+        }
+
+        let mut leader = Leader {
+            registry: self,
+            key,
+            result: None,
+        };
+        let result = fetch().await;
+        leader.result = Some(result.clone());
+        result
+    }
+}
+
+/// Executa `fut`, abortando se o `request_id` for cancelado ou substituído
+pub async fn cancellable<T>(
+    state: &AppState,
+    request_id: Option<String>,
+    fut: impl Future<Output = AppResult<T>>,
+) -> AppResult<T> {
+    let Some(request_id) = request_id else {
+        return fut.await;
+    };
+
+    let registry = &state.inflight;
+    let (generation, token) = registry.register(&request_id);
+    let result = tokio::select! {
+        result = fut => result,
+        _ = token.cancelled() => Err(AppError::cancelled(format!(
+            "Requisição {} cancelada",
+            request_id
+        ))),
+    };
+    registry.unregister(&request_id, generation);
+    result
+}
+
+/// GET com cache em que chamadas idênticas simultâneas aguardam a primeira.
+/// A chave inclui a sessão, como no cache: numa troca de usuário ou perfil a
+/// chamada nova não recebe a resposta da sessão anterior.
+pub async fn coalesced_get(state: &AppState, request: &ApiRequest, allow_stale: bool) -> GetResult {
+    let key = format!("{}|{}", allow_stale, cache::scoped_key(state, request));
+    state
+        .inflight
+        .coalesce(key, || cache::get(state, request, allow_stale))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::{json, Value};
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    fn response(data: Value) -> CachedResponse {
+        CachedResponse {
+            data,
+            from_cache: false,
+            stale: false,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_identical_gets_share_one_round_trip() {
+        let registry = InflightRegistry::default();
+        let round_trips = AtomicUsize::new(0);
+        let fetch = || async {
+            round_trips.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(response(json!({ "pedidos": [1, 2] })))
+        };
+
+        let (first, second) = tauri::async_runtime::block_on(async {
+            tokio::join!(
+                registry.coalesce("false|/pedidos".to_string(), fetch),
+                registry.coalesce("false|/pedidos".to_string(), fetch),
+            )
+        });
+
+        assert_eq!(round_trips.load(Ordering::SeqCst), 1);
+        assert_eq!(first.unwrap().data, json!({ "pedidos": [1, 2] }));
+        assert_eq!(second.unwrap().data, json!({ "pedidos": [1, 2] }));
+        assert!(registry.gets.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_different_gets_are_not_coalesced() {
+        let registry = InflightRegistry::default();
+        let round_trips = AtomicUsize::new(0);
+        let fetch = || async {
+            round_trips.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(response(json!([])))
+        };
+
+        tauri::async_runtime::block_on(async {
+            let (a, b) = tokio::join!(
+                registry.coalesce("false|/pedidos".to_string(), fetch),
+                registry.coalesce("true|/pedidos".to_string(), fetch),
+            );
+            a.unwrap();
+            b.unwrap();
+        });
+
+        assert_eq!(round_trips.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_follower_fetches_alone_when_leader_is_cancelled() {
+        let registry = InflightRegistry::default();
+        let round_trips = AtomicUsize::new(0);
+        let fetch = || async {
+            round_trips.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(response(json!("ok")))
+        };
+
+        let follower = tauri::async_runtime::block_on(async {
+            let mut leader = Box::pin(registry.coalesce("false|/pedidos".to_string(), fetch));
+            let mut follower = Box::pin(registry.coalesce("false|/pedidos".to_string(), fetch));
+            // O líder começa a ida à rede e é descartado; o seguidor já estava esperando
+            tokio::select! {
+                biased;
+                _ = &mut leader => unreachable!(),
+                _ = &mut follower => unreachable!(),
+                _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+            }
+            drop(leader);
+            follower.await
+        });
+
+        assert_eq!(follower.unwrap().data, json!("ok"));
+        assert_eq!(round_trips.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_same_request_id_cancels_previous_call() {
+        let registry = InflightRegistry::default();
+        let (first, token) = registry.register("pedidos-lista");
+        let (second, newer) = registry.register("pedidos-lista");
+
+        assert!(token.is_cancelled());
+        assert!(!newer.is_cancelled());
+        // O fim da chamada antiga não remove o registro da mais nova
+        registry.unregister("pedidos-lista", first);
+        assert!(registry.cancel("pedidos-lista"));
+        assert!(newer.is_cancelled());
+        registry.unregister("pedidos-lista", second);
+        assert!(!registry.cancel("pedidos-lista"));
+    }
+}
@@ -0,0 +1,440 @@
+//! Inspetor de rede da ponte: registro opcional das últimas chamadas à API.
+//!
+//! Desligado por padrão. Quando ligado, cada requisição enviada por
+//! [`super::send`] vira um [`NetworkEntry`] num buffer circular, com headers
+//! sensíveis mascarados e corpos truncados, e é emitida no evento
+//! `network-inspector-entry`. O suporte pode exportar o buffer em JSON ou HAR
+//! sem precisar do DevTools na máquina do cliente.
+
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, CONTENT_LENGTH, CONTENT_TYPE};
+use reqwest::{Client, Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+
+pub const EVENT_ENTRY: &str = "network-inspector-entry";
+
+/// Respostas maiores que isso não são lidas para o inspetor (downloads)
+const MAX_CAPTURED_RESPONSE: u64 = 5 * 1024 * 1024;
+
+const REDACTED: &str = "***";
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+];
+const SENSITIVE_FIELDS: &[&str] = &[
+    "password",
+    "senha",
+    "token",
+    "access_token",
+    "refresh_token",
+    "session_token",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InspectorConfig {
+    pub enabled: bool,
+    /// Quantidade de chamadas mantidas no buffer
+    pub capacity: usize,
+    /// Corpos maiores que isso são truncados
+    pub max_body_bytes: usize,
+}
+
+impl Default for InspectorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: 200,
+            max_body_bytes: 4096,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkEntry {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub method: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body: Option<String>,
+    pub request_size: Option<u64>,
+    pub response_headers: Vec<(String, String)>,
+    pub response_body: Option<String>,
+    pub response_size: Option<u64>,
+    /// Falha de transporte (timeout, DNS...), quando não houve resposta
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Json,
+    Har,
+}
+
+#[derive(Default)]
+pub struct NetworkInspector {
+    app: Mutex<Option<AppHandle>>,
+    config: Mutex<InspectorConfig>,
+    entries: Mutex<VecDeque<NetworkEntry>>,
+}
+
+fn redact_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                value.to_str().unwrap_or("<binário>").to_string()
+            };
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
+
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if SENSITIVE_FIELDS.contains(&key.to_ascii_lowercase().as_str()) {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Mascara campos sensíveis (se for JSON) e trunca o corpo em `max` bytes
+fn render_body(bytes: &[u8], max: usize) -> String {
+    let text = match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => String::from_utf8_lossy(bytes).to_string(),
+    };
+    if text.len() <= max {
+        return text;
+    }
+    let mut cut = max;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    format!("{}… [{} bytes truncados]", &text[..cut], text.len() - cut)
+}
+
+fn redact_url(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if SENSITIVE_FIELDS.contains(&k.to_ascii_lowercase().as_str()) {
+                    REDACTED.to_string()
+                } else {
+                    v.to_string()
+                };
+                (k.to_string(), v)
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+fn is_textual(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|ct| ct.contains("json") || ct.starts_with("text/"))
+        .unwrap_or(true)
+}
+
+impl NetworkInspector {
+    pub fn attach(&self, app: AppHandle) {
+        if let Ok(mut slot) = self.app.lock() {
+            *slot = Some(app);
+        }
+    }
+
+    pub fn config(&self) -> AppResult<InspectorConfig> {
+        Ok(self
+            .config
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na config do inspetor"))?
+            .clone())
+    }
+
+    pub fn configure(&self, config: InspectorConfig) -> AppResult<()> {
+        if config.capacity == 0 {
+            return Err(AppError::validation(
+                "Capacidade do inspetor precisa ser maior que zero",
+            ));
+        }
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no inspetor"))?;
+        while entries.len() > config.capacity {
+            entries.pop_front();
+        }
+        if !config.enabled {
+            entries.clear();
+        }
+        *self
+            .config
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na config do inspetor"))? = config;
+        Ok(())
+    }
+
+    fn enabled(&self) -> bool {
+        self.config.lock().map(|c| c.enabled).unwrap_or(false)
+    }
+
+    /// Chamadas registradas, da mais recente para a mais antiga
+    pub fn entries(&self, limit: Option<usize>) -> AppResult<Vec<NetworkEntry>> {
+        let entries = self
+            .entries
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no inspetor"))?;
+        Ok(entries
+            .iter()
+            .rev()
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+
+    pub fn clear(&self) -> AppResult<()> {
+        self.entries
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no inspetor"))?
+            .clear();
+        Ok(())
+    }
+
+    pub fn export(&self, format: ExportFormat) -> AppResult<Value> {
+        let mut entries = self.entries(None)?;
+        entries.reverse();
+        Ok(match format {
+            ExportFormat::Json => serde_json::to_value(entries)?,
+            ExportFormat::Har => to_har(&entries),
+        })
+    }
+
+    fn record(&self, entry: NetworkEntry) {
+        let capacity = self.config.lock().map(|c| c.capacity).unwrap_or(0);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push_back(entry.clone());
+            while entries.len() > capacity {
+                entries.pop_front();
+            }
+        }
+        if let Some(app) = self.app.lock().ok().and_then(|a| a.clone()) {
+            let _ = app.emit(EVENT_ENTRY, entry);
+        }
+    }
+
+    /// Executa a requisição; com o inspetor ligado, registra a chamada.
+    ///
+    /// Respostas textuais são lidas por inteiro para registrar o corpo e
+    /// depois reconstruídas com os mesmos headers e extensões (inclusive o
+    /// `TlsInfo` usado no pinning), então quem chama não percebe a diferença.
+    /// Respostas binárias ou grandes (downloads) passam sem ser lidas.
+    pub async fn execute(&self, client: &Client, request: Request) -> AppResult<Response> {
+        if !self.enabled() {
+            return Ok(client.execute(request).await?);
+        }
+        let max_body = self.config().map(|c| c.max_body_bytes).unwrap_or(0);
+
+        let request_bytes = request.body().and_then(|b| b.as_bytes());
+        let mut entry = NetworkEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            started_at: Utc::now(),
+            duration_ms: 0,
+            method: request.method().to_string(),
+            url: redact_url(request.url()),
+            status: None,
+            request_headers: redact_headers(request.headers()),
+            request_body: request_bytes.map(|b| render_body(b, max_body)),
+            request_size: request_bytes.map(|b| b.len() as u64),
+            response_headers: Vec::new(),
+            response_body: None,
+            response_size: None,
+            error: None,
+        };
+        if request.body().is_some() && request_bytes.is_none() {
+            entry.request_body = Some("<stream>".to_string());
+        }
+
+        let started = Instant::now();
+        let result = client.execute(request).await;
+        let mut response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                entry.duration_ms = started.elapsed().as_millis() as u64;
+                entry.error = Some(e.to_string());
+                self.record(entry);
+                return Err(e.into());
+            }
+        };
+
+        entry.status = Some(response.status().as_u16());
+        entry.response_headers = redact_headers(response.headers());
+        entry.response_size = response.content_length();
+
+        let capturable = is_textual(response.headers())
+            && response
+                .content_length()
+                .is_none_or(|len| len <= MAX_CAPTURED_RESPONSE);
+        if !capturable {
+            entry.duration_ms = started.elapsed().as_millis() as u64;
+            self.record(entry);
+            return Ok(response);
+        }
+
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let extensions = std::mem::take(response.extensions_mut());
+        let bytes = match response.bytes().await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                entry.duration_ms = started.elapsed().as_millis() as u64;
+                entry.error = Some(e.to_string());
+                self.record(entry);
+                return Err(e.into());
+            }
+        };
+        entry.duration_ms = started.elapsed().as_millis() as u64;
+        entry.response_size = Some(bytes.len() as u64);
+        entry.response_body = Some(render_body(&bytes, max_body));
+        self.record(entry);
+
+        let mut rebuilt = http::Response::new(bytes);
+        *rebuilt.status_mut() = status;
+        *rebuilt.version_mut() = version;
+        *rebuilt.headers_mut() = headers;
+        *rebuilt.extensions_mut() = extensions;
+        rebuilt.headers_mut().remove(CONTENT_LENGTH);
+        Ok(Response::from(rebuilt))
+    }
+}
+
+fn har_headers(headers: &[(String, String)]) -> Value {
+    Value::Array(
+        headers
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect(),
+    )
+}
+
+fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Converte as chamadas para HAR 1.2, abrível no DevTools ou no Fiddler
+fn to_har(entries: &[NetworkEntry]) -> Value {
+    let har_entries: Vec<Value> = entries
+        .iter()
+        .map(|e| {
+            let mut request = json!({
+                "method": e.method,
+                "url": e.url,
+                "httpVersion": "HTTP/1.1",
+                "headers": har_headers(&e.request_headers),
+                "queryString": [],
+                "cookies": [],
+                "headersSize": -1,
+                "bodySize": e.request_size.map_or(-1, |s| s as i64),
+            });
+            if let Some(ref body) = e.request_body {
+                request["postData"] = json!({
+                    "mimeType": header_value(&e.request_headers, "content-type")
+                        .unwrap_or("application/json"),
+                    "text": body,
+                });
+            }
+            json!({
+                "startedDateTime": e.started_at.to_rfc3339(),
+                "time": e.duration_ms,
+                "request": request,
+                "response": {
+                    "status": e.status.unwrap_or(0),
+                    "statusText": e.error.clone().unwrap_or_default(),
+                    "httpVersion": "HTTP/1.1",
+                    "headers": har_headers(&e.response_headers),
+                    "cookies": [],
+                    "content": {
+                        "size": e.response_size.map_or(-1, |s| s as i64),
+                        "mimeType": header_value(&e.response_headers, "content-type")
+                            .unwrap_or(""),
+                        "text": e.response_body.clone().unwrap_or_default(),
+                    },
+                    "redirectURL": "",
+                    "headersSize": -1,
+                    "bodySize": e.response_size.map_or(-1, |s| s as i64),
+                },
+                "cache": {},
+                "timings": { "send": 0, "wait": e.duration_ms, "receive": 0 },
+            })
+        })
+        .collect();
+
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "SGP", "version": env!("CARGO_PKG_VERSION") },
+            "entries": har_entries,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_sensitive_fields_and_truncates() {
+        let body = br#"{"login":"ana","senha":"123","itens":[{"token":"abc"}]}"#;
+        let rendered = render_body(body, 1000);
+        assert!(!rendered.contains("123"));
+        assert!(!rendered.contains("abc"));
+        assert!(rendered.contains("ana"));
+
+        let long = "x".repeat(50);
+        assert!(render_body(long.as_bytes(), 10).starts_with("xxxxxxxxxx…"));
+    }
+
+    #[test]
+    fn redacts_authorization_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer segredo".parse().unwrap());
+        headers.insert("accept", "application/json".parse().unwrap());
+        let redacted = redact_headers(&headers);
+        assert!(redacted.contains(&("authorization".to_string(), REDACTED.to_string())));
+        assert!(redacted.contains(&("accept".to_string(), "application/json".to_string())));
+    }
+}
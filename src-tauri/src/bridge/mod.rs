@@ -0,0 +1,269 @@
+//! Núcleo da ponte entre o frontend e a API Python.
+//!
+//! Os comandos em `commands/api.rs` apenas montam um [`ApiRequest`] e delegam
+//! para [`execute`], que cuida de URL, headers, retentativas e tratamento de erro.
+
+pub mod auth;
+pub mod cache;
+pub mod diagnostics;
+pub mod inflight;
+pub mod inspector;
+pub mod paging;
+pub mod policy;
+pub mod presence;
+pub mod queue;
+pub mod realtime;
+pub mod retry;
+pub mod transfer;
+
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use reqwest::{Method, RequestBuilder, Response};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use tracing::warn;
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+/// Requisição já validada, pronta para ser enviada (e reenviada) à API
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    pub method: Method,
+    pub endpoint: String,
+    pub params: Option<HashMap<String, String>>,
+    pub body: Option<Value>,
+    pub idempotency_key: Option<String>,
+}
+
+impl ApiRequest {
+    pub fn get(endpoint: String, params: Option<HashMap<String, String>>) -> Self {
+        Self {
+            method: Method::GET,
+            endpoint,
+            params,
+            body: None,
+            idempotency_key: None,
+        }
+    }
+
+    /// Mutações sempre carregam uma Idempotency-Key, gerada aqui se o frontend não enviar
+    pub fn mutation(
+        method: &str,
+        endpoint: String,
+        body: Option<Value>,
+        idempotency_key: Option<String>,
+    ) -> AppResult<Self> {
+        let method = parse_mutation_method(method)?;
+        Ok(Self {
+            method,
+            endpoint,
+            params: None,
+            body,
+            idempotency_key: Some(
+                idempotency_key.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            ),
+        })
+    }
+}
+
+pub fn parse_mutation_method(method: &str) -> AppResult<Method> {
+    match method.to_uppercase().as_str() {
+        "POST" => Ok(Method::POST),
+        "PUT" => Ok(Method::PUT),
+        "PATCH" => Ok(Method::PATCH),
+        "DELETE" => Ok(Method::DELETE),
+        _ => Err(AppError::validation(format!(
+            "Método HTTP não suportado: {}",
+            method
+        ))),
+    }
+}
+
+pub fn build_url(base_url: &str, endpoint: &str) -> String {
+    let normalized_base = base_url.trim_end_matches('/');
+    if endpoint.starts_with('/') {
+        format!("{}{}", normalized_base, endpoint)
+    } else {
+        format!("{}/{}", normalized_base, endpoint)
+    }
+}
+
+/// Envia a requisição aplicando a política de retentativa configurada
+pub async fn execute(state: &AppState, request: &ApiRequest) -> AppResult<Value> {
+    with_retry(state, request, || send_once(state, request)).await
+}
+
+/// Executa `op` repetidamente enquanto o erro for transitório e a política permitir.
+/// Um 401 dispara uma única tentativa de renovar o token antes de repetir `op`.
+pub(crate) async fn with_retry<T, F, Fut>(
+    state: &AppState,
+    request: &ApiRequest,
+    mut op: F,
+) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    let policy = state
+        .retry_policy
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex na política de retry"))?
+        .clone();
+
+    let mut attempt = 1;
+    let mut refreshed = false;
+    loop {
+        let token_used = state.auth_token.lock().ok().and_then(|t| t.clone());
+        match op().await {
+            Err(e) if e.status == Some(401) && !refreshed => {
+                refreshed = true;
+                if !state.session.refresh(state, token_used, &e).await {
+                    return Err(e);
+                }
+            }
+            Err(e)
+                if e.retryable
+                    && attempt < policy.max_attempts
+                    && policy.allows(&request.method) =>
+            {
+                let delay = policy.delay_for(attempt);
+                warn!(
+                    "[Bridge] {} {} falhou (tentativa {}/{}): {}. Nova tentativa em {:?}",
+                    request.method, request.endpoint, attempt, policy.max_attempts, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Monta e envia a requisição sem interpretar o status da resposta
+pub(crate) async fn dispatch(
+    state: &AppState,
+    request: &ApiRequest,
+    extra_headers: &[(&str, String)],
+) -> AppResult<Response> {
+    let mut builder = prepare(state, request, extra_headers).await?;
+
+    if let Some(ref b) = request.body {
+        builder = builder.json(b);
+    }
+
+    send(state, builder).await
+}
+
+/// Monta a requisição com URL, token, headers e timeout, sem corpo. Usado
+/// diretamente por chamadas com corpo não-JSON (upload multipart).
+pub(crate) async fn prepare(
+    state: &AppState,
+    request: &ApiRequest,
+    extra_headers: &[(&str, String)],
+) -> AppResult<RequestBuilder> {
+    state
+        .policy
+        .check(request, state.session.role().as_deref())?;
+
+    let base_url = state
+        .api_base_url
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex no base_url"))?
+        .clone();
+    let token = state
+        .auth_token
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex no auth_token"))?
+        .clone();
+
+    if base_url.is_empty() {
+        return Err(AppError::config(
+            "API Base URL não configurada no Rust Core",
+        ));
+    }
+
+    state.http.verify_host(&base_url).await?;
+
+    let url = build_url(&base_url, &request.endpoint);
+    let mut builder = state.http.client()?.request(request.method.clone(), &url);
+
+    if let Some(ref p) = request.params {
+        builder = builder.query(p);
+    }
+
+    if let Some(t) = token {
+        builder = builder.header("Authorization", format!("Bearer {}", t));
+    }
+
+    if let Some(ref key) = request.idempotency_key {
+        builder = builder.header(IDEMPOTENCY_HEADER, key);
+    }
+
+    let profile_headers = state
+        .extra_headers
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex nos headers extras"))?
+        .clone();
+    for (name, value) in &profile_headers {
+        builder = builder.header(name, value);
+    }
+
+    for (name, value) in extra_headers {
+        builder = builder.header(*name, value);
+    }
+
+    // Classe de endpoint (ex: relatórios) tem prioridade sobre o timeout do perfil
+    let profile_timeout = *state
+        .request_timeout
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex no timeout"))?;
+    if let Some(timeout) = state
+        .http
+        .timeout_for(&request.endpoint)
+        .or(profile_timeout)
+    {
+        builder = builder.timeout(timeout);
+    }
+
+    // Bypass Ngrok limits if available
+    builder = builder.header("ngrok-skip-browser-warning", "any");
+    builder = builder.header("Accept", "application/json");
+
+    Ok(builder)
+}
+
+/// Envia uma requisição montada por [`prepare`] (registrando-a no inspetor de
+/// rede, se ligado) e confere o pin do certificado
+pub(crate) async fn send(state: &AppState, builder: RequestBuilder) -> AppResult<Response> {
+    let (client, request) = builder.build_split();
+    let response = state.inspector.execute(&client, request?).await?;
+    state.http.check_pin(&response)?;
+    Ok(response)
+}
+
+/// Converte uma resposta em JSON, transformando status de erro em [`AppError`]
+pub(crate) async fn read_json_response(response: Response) -> AppResult<Value> {
+    let status = response.status();
+    if !status.is_success() {
+        let err_text = response.text().await.unwrap_or_default();
+        return Err(AppError::from_status(status.as_u16(), err_text));
+    }
+
+    if status.as_u16() == 204 {
+        return Ok(Value::Null);
+    }
+
+    let text = response.text().await?;
+    if text.is_empty() {
+        return Ok(Value::Null);
+    }
+
+    let json: Value = serde_json::from_str(&text)?;
+    Ok(json)
+}
+
+pub(crate) async fn send_once(state: &AppState, request: &ApiRequest) -> AppResult<Value> {
+    let response = dispatch(state, request, &[]).await?;
+    read_json_response(response).await
+}
@@ -0,0 +1,409 @@
+//! Leitura paginada de listas longas, entregue página a página ao frontend.
+//!
+//! Em vez de um único GET com `limit=1000` que trava a UI e o IPC, a ponte
+//! percorre o endpoint página a página (offset/limit ou cursor) e envia cada
+//! página por um `Channel` do Tauri assim que ela chega. A leitura para ao fim
+//! da lista, ao atingir `max_items`, quando o frontend descarta o canal ou
+//! quando `cancel_request` é chamado com o `stream_id`.
+
+use super::{execute, ApiRequest};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use tauri::ipc::Channel;
+use tracing::debug;
+
+const MAX_PAGE_SIZE: u32 = 1000;
+
+fn default_offset_param() -> String {
+    "offset".to_string()
+}
+
+fn default_limit_param() -> String {
+    "limit".to_string()
+}
+
+fn default_cursor_param() -> String {
+    "cursor".to_string()
+}
+
+fn default_next_cursor_field() -> String {
+    "next_cursor".to_string()
+}
+
+fn default_items_field() -> String {
+    "items".to_string()
+}
+
+fn default_page_size() -> u32 {
+    100
+}
+
+/// Como o endpoint pagina. `/pedidos/` usa `skip`/`limit`, `/print-logs/` usa
+/// `offset`/`limit`; endpoints com cursor devolvem `{items, next_cursor}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum PageStrategy {
+    Offset {
+        #[serde(default = "default_offset_param")]
+        offset_param: String,
+        #[serde(default = "default_limit_param")]
+        limit_param: String,
+    },
+    Cursor {
+        #[serde(default = "default_cursor_param")]
+        cursor_param: String,
+        #[serde(default = "default_limit_param")]
+        limit_param: String,
+        #[serde(default = "default_next_cursor_field")]
+        next_cursor_field: String,
+    },
+}
+
+impl Default for PageStrategy {
+    fn default() -> Self {
+        Self::Offset {
+            offset_param: default_offset_param(),
+            limit_param: default_limit_param(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PagedQuery {
+    pub endpoint: String,
+    #[serde(default)]
+    pub params: HashMap<String, String>,
+    #[serde(default = "default_page_size")]
+    pub page_size: u32,
+    /// Para depois de entregar esta quantidade de itens
+    pub max_items: Option<u64>,
+    #[serde(default)]
+    pub strategy: PageStrategy,
+    /// Campo com a lista quando a resposta é um objeto em vez de array
+    #[serde(default = "default_items_field")]
+    pub items_field: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PageEvent {
+    Page {
+        index: u32,
+        items: Vec<Value>,
+        /// Total de itens entregues até aqui, incluindo esta página
+        delivered: u64,
+    },
+    Done {
+        pages: u32,
+        items: u64,
+        /// `true` se parou por `max_items` antes do fim da lista
+        truncated: bool,
+    },
+}
+
+/// Separa os itens da página e, no modo cursor, o próximo cursor
+fn split_page(
+    body: Value,
+    items_field: &str,
+    next_cursor_field: Option<&str>,
+) -> AppResult<(Vec<Value>, Option<String>)> {
+    match body {
+        Value::Array(items) => Ok((items, None)),
+        Value::Object(mut object) => {
+            let items = match object.remove(items_field) {
+                Some(Value::Array(items)) => items,
+                _ => {
+                    return Err(AppError::parse(format!(
+                        "Resposta paginada sem o campo de lista '{}'",
+                        items_field
+                    )))
+                }
+            };
+            let cursor = next_cursor_field
+                .and_then(|field| object.remove(field))
+                .and_then(|v| match v {
+                    Value::String(s) if !s.is_empty() => Some(s),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                });
+            Ok((items, cursor))
+        }
+        Value::Null => Ok((Vec::new(), None)),
+        _ => Err(AppError::parse("Resposta paginada em formato inesperado")),
+    }
+}
+
+pub async fn stream(
+    state: &AppState,
+    query: PagedQuery,
+    channel: &Channel<PageEvent>,
+) -> AppResult<()> {
+    walk(
+        query,
+        |request| async move { execute(state, &request).await },
+        |event| {
+            channel
+                .send(event)
+                .map_err(|_| AppError::cancelled("Frontend deixou de ouvir as páginas"))
+        },
+    )
+    .await
+}
+
+/// Percorre as páginas buscando cada uma com `fetch` e entregando-as a
+/// `emit`. Um erro de `emit` (canal fechado) interrompe a leitura sem
+/// buscar a próxima página.
+async fn walk<F, Fut, E>(query: PagedQuery, mut fetch: F, mut emit: E) -> AppResult<()>
+where
+    F: FnMut(ApiRequest) -> Fut,
+    Fut: Future<Output = AppResult<Value>>,
+    E: FnMut(PageEvent) -> AppResult<()>,
+{
+    if query.page_size == 0 || query.page_size > MAX_PAGE_SIZE {
+        return Err(AppError::validation(format!(
+            "page_size deve estar entre 1 e {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+
+    let page_size = query.page_size as u64;
+    let mut offset: u64 = 0;
+    let mut cursor: Option<String> = None;
+    let mut pages: u32 = 0;
+    let mut delivered: u64 = 0;
+
+    loop {
+        let remaining = query.max_items.map(|max| max.saturating_sub(delivered));
+        if remaining == Some(0) {
+            break;
+        }
+        let limit = remaining.map_or(page_size, |r| r.min(page_size));
+
+        let mut params = query.params.clone();
+        let next_cursor_field = match query.strategy {
+            PageStrategy::Offset {
+                ref offset_param,
+                ref limit_param,
+            } => {
+                params.insert(offset_param.clone(), offset.to_string());
+                params.insert(limit_param.clone(), limit.to_string());
+                None
+            }
+            PageStrategy::Cursor {
+                ref cursor_param,
+                ref limit_param,
+                ref next_cursor_field,
+            } => {
+                if let Some(ref c) = cursor {
+                    params.insert(cursor_param.clone(), c.clone());
+                }
+                params.insert(limit_param.clone(), limit.to_string());
+                Some(next_cursor_field.as_str())
+            }
+        };
+
+        let request = ApiRequest::get(query.endpoint.clone(), Some(params));
+        let body = fetch(request).await?;
+        let (items, next_cursor) = split_page(body, &query.items_field, next_cursor_field)?;
+
+        let count = items.len() as u64;
+        delivered += count;
+        offset += count;
+        if count > 0 {
+            emit(PageEvent::Page {
+                index: pages,
+                items,
+                delivered,
+            })?;
+            pages += 1;
+        }
+        debug!(
+            "[Paging] {} página {} ({} itens)",
+            query.endpoint, pages, count
+        );
+
+        let finished = match query.strategy {
+            PageStrategy::Offset { .. } => count < limit,
+            PageStrategy::Cursor { .. } => next_cursor.is_none() || count == 0,
+        };
+        if finished {
+            break;
+        }
+        cursor = next_cursor;
+    }
+
+    let truncated = query.max_items.is_some_and(|max| delivered >= max);
+    emit(PageEvent::Done {
+        pages,
+        items: delivered,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use serde_json::json;
+    use std::cell::RefCell;
+
+    fn query(page_size: u32, max_items: Option<u64>, strategy: PageStrategy) -> PagedQuery {
+        PagedQuery {
+            endpoint: "/print-logs/".to_string(),
+            params: HashMap::new(),
+            page_size,
+            max_items,
+            strategy,
+            items_field: default_items_field(),
+        }
+    }
+
+    fn param(request: &ApiRequest, name: &str) -> u64 {
+        request.params.as_ref().unwrap()[name].parse().unwrap()
+    }
+
+    /// Endpoint offset/limit sobre `total` itens; devolve as requisições feitas
+    /// e os eventos emitidos
+    fn run_offset(
+        total: u64,
+        query: PagedQuery,
+    ) -> (AppResult<()>, Vec<ApiRequest>, Vec<PageEvent>) {
+        let requests = RefCell::new(Vec::new());
+        let mut events = Vec::new();
+        let result = tauri::async_runtime::block_on(walk(
+            query,
+            |request| {
+                let offset = param(&request, "offset");
+                let limit = param(&request, "limit");
+                requests.borrow_mut().push(request);
+                let items: Vec<Value> = (offset..total.min(offset + limit))
+                    .map(|i| json!({ "id": i }))
+                    .collect();
+                std::future::ready(Ok(Value::Array(items)))
+            },
+            |event| {
+                events.push(event);
+                Ok(())
+            },
+        ));
+        (result, requests.into_inner(), events)
+    }
+
+    fn done(events: &[PageEvent]) -> (u32, u64, bool) {
+        match events.last() {
+            Some(PageEvent::Done {
+                pages,
+                items,
+                truncated,
+            }) => (*pages, *items, *truncated),
+            other => panic!("último evento não é done: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_offset_stops_on_short_or_empty_page() {
+        let (result, requests, events) = run_offset(250, query(100, None, Default::default()));
+        assert!(result.is_ok());
+        assert_eq!(requests.len(), 3);
+        assert_eq!(done(&events), (3, 250, false));
+
+        // Total múltiplo do tamanho da página: a página vazia encerra sem evento
+        let (_, requests, events) = run_offset(200, query(100, None, Default::default()));
+        assert_eq!(requests.len(), 3);
+        assert_eq!(events.len(), 3);
+        assert_eq!(done(&events), (2, 200, false));
+    }
+
+    #[test]
+    fn test_max_items_shrinks_last_page_and_stops() {
+        let (result, requests, events) =
+            run_offset(1000, query(100, Some(150), Default::default()));
+        assert!(result.is_ok());
+        let limits: Vec<u64> = requests.iter().map(|r| param(r, "limit")).collect();
+        assert_eq!(limits, vec![100, 50]);
+        assert_eq!(param(&requests[1], "offset"), 100);
+        assert_eq!(done(&events), (2, 150, true));
+    }
+
+    #[test]
+    fn test_cursor_follows_next_cursor_until_absent() {
+        let requests = RefCell::new(Vec::new());
+        let mut delivered = Vec::new();
+        let strategy = PageStrategy::Cursor {
+            cursor_param: default_cursor_param(),
+            limit_param: default_limit_param(),
+            next_cursor_field: default_next_cursor_field(),
+        };
+        let result = tauri::async_runtime::block_on(walk(
+            query(2, None, strategy),
+            |request| {
+                let cursor = request
+                    .params
+                    .as_ref()
+                    .and_then(|p| p.get("cursor").cloned());
+                requests.borrow_mut().push(cursor.clone());
+                let body = match cursor.as_deref() {
+                    None => json!({ "items": [1, 2], "next_cursor": "b" }),
+                    Some("b") => json!({ "items": [3, 4], "next_cursor": 7 }),
+                    _ => json!({ "items": [5], "next_cursor": null }),
+                };
+                std::future::ready(Ok(body))
+            },
+            |event| {
+                if let PageEvent::Page { delivered: d, .. } = event {
+                    delivered.push(d);
+                }
+                Ok(())
+            },
+        ));
+        assert!(result.is_ok());
+        assert_eq!(
+            requests.into_inner(),
+            vec![None, Some("b".to_string()), Some("7".to_string())]
+        );
+        assert_eq!(delivered, vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn test_closed_channel_stops_before_next_fetch() {
+        let fetches = RefCell::new(0);
+        let result = tauri::async_runtime::block_on(walk(
+            query(10, None, Default::default()),
+            |_| {
+                *fetches.borrow_mut() += 1;
+                std::future::ready(Ok(json!((0..10).collect::<Vec<u32>>())))
+            },
+            |_| Err(AppError::cancelled("Frontend deixou de ouvir as páginas")),
+        ));
+        assert_eq!(result.unwrap_err().kind, ErrorKind::Cancelled);
+        assert_eq!(fetches.into_inner(), 1);
+    }
+
+    #[test]
+    fn test_fetch_error_ends_stream_without_done() {
+        let mut events = 0;
+        let result = tauri::async_runtime::block_on(walk(
+            query(10, None, Default::default()),
+            |_| std::future::ready(Err(AppError::network("sem rede"))),
+            |_| {
+                events += 1;
+                Ok(())
+            },
+        ));
+        assert_eq!(result.unwrap_err().kind, ErrorKind::Network);
+        assert_eq!(events, 0);
+    }
+
+    #[test]
+    fn test_page_size_is_validated() {
+        for size in [0, MAX_PAGE_SIZE + 1] {
+            let (result, requests, _) = run_offset(10, query(size, None, Default::default()));
+            assert_eq!(result.unwrap_err().kind, ErrorKind::Validation);
+            assert!(requests.is_empty());
+        }
+    }
+}
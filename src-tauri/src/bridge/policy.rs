@@ -0,0 +1,400 @@
+//! Política de endpoints aplicada antes de qualquer requisição sair da ponte.
+//!
+//! O WebView pode pedir qualquer método/endpoint a `rust_api_get` e
+//! `rust_api_mutate`, sempre com o token do usuário. A política em
+//! `<app_data>/api_policy.json` limita o que a ponte aceita encaminhar: padrões
+//! de caminho permitidos por método, papéis exigidos e tamanho máximo do corpo.
+//! Não há comando para alterá-la a partir do frontend; o arquivo é editado
+//! pela TI. A API continua sendo a autoridade final sobre permissões.
+
+use super::ApiRequest;
+use crate::error::{AppError, AppResult};
+use crate::storage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+pub const EVENT_VIOLATION: &str = "api-policy-violation";
+
+/// Quantidade de violações mantidas em memória para o suporte consultar
+const VIOLATION_HISTORY: usize = 100;
+
+/// Permite `methods` em caminhos que casem com `pattern`.
+///
+/// As regras são avaliadas em ordem e a primeira que cobrir método e caminho
+/// decide; regras mais restritas vêm antes das genéricas. No padrão, `*` casa um segmento e `**` casa zero ou mais segmentos
+/// (ex: `/pedidos/**` cobre `/pedidos` e `/pedidos/12/itens`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Métodos HTTP em maiúsculas, ou `*` para qualquer um
+    pub methods: Vec<String>,
+    pub pattern: String,
+    /// Papéis que podem usar a regra; vazio libera para qualquer sessão
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiPolicy {
+    pub enabled: bool,
+    pub rules: Vec<PolicyRule>,
+    /// Tamanho máximo do corpo JSON de uma mutação
+    pub max_body_bytes: usize,
+    /// Tamanho máximo de um arquivo enviado por `rust_api_upload`
+    pub max_upload_bytes: u64,
+}
+
+fn rule(methods: &[&str], pattern: &str, roles: &[&str]) -> PolicyRule {
+    PolicyRule {
+        methods: methods.iter().map(|m| m.to_string()).collect(),
+        pattern: pattern.to_string(),
+        roles: roles.iter().map(|r| r.to_string()).collect(),
+    }
+}
+
+impl Default for ApiPolicy {
+    fn default() -> Self {
+        const WRITE: &[&str] = &["POST", "PUT", "PATCH", "DELETE"];
+        let mut rules = vec![
+            // Leituras das telas de administração, antes da leitura geral
+            rule(&["GET"], "/users/**", &["admin"]),
+            rule(&["GET"], "/relatorios-fechamentos/**", &["admin"]),
+            rule(&["GET"], "/**", &[]),
+            // Operações em massa sobre pedidos, antes da regra geral de /pedidos
+            rule(&["DELETE"], "/pedidos/all", &["admin"]),
+            rule(&["POST"], "/pedidos/reset-ids", &["admin"]),
+        ];
+        for resource in [
+            "/auth/**",
+            "/pedidos/**",
+            "/producoes/**",
+            "/fichas/**",
+            "/clientes/**",
+            "/designers/**",
+            "/vendedores/**",
+            "/materiais/**",
+            "/maquinas/**",
+            "/relatorios/**",
+            "/relatorios-fechamentos/**",
+            "/print-logs/**",
+            "/safira/**",
+            "/presence/**",
+        ] {
+            rules.push(rule(WRITE, resource, &[]));
+        }
+        for admin_resource in ["/users/**", "/tipos-pagamentos/**", "/tipos-envios/**"] {
+            rules.push(rule(WRITE, admin_resource, &["admin"]));
+        }
+
+        Self {
+            enabled: true,
+            rules,
+            max_body_bytes: 2 * 1024 * 1024,
+            max_upload_bytes: 50 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyViolation {
+    pub at: DateTime<Utc>,
+    pub method: String,
+    pub endpoint: String,
+    pub role: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Default)]
+pub struct PolicyEngine {
+    app: Mutex<Option<AppHandle>>,
+    policy: Mutex<ApiPolicy>,
+    violations: Mutex<VecDeque<PolicyViolation>>,
+}
+
+/// Rejeita caminhos que o parser de URL reescreveria (`..`, `//`, barras
+/// invertidas, pontos codificados) e devolve o caminho sem a query string,
+/// com a `/` inicial que [`super::build_url`] acrescenta quando falta
+fn normalized_path(endpoint: &str) -> Result<String, String> {
+    let path = endpoint.split('?').next().unwrap_or_default();
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{}", path)
+    };
+    let lower = path.to_ascii_lowercase();
+    if path.contains('\\')
+        || path.contains('#')
+        || path.contains("//")
+        || lower.contains("%2e")
+        || lower.contains("%2f")
+        || lower.contains("%5c")
+        || path
+            .split('/')
+            .any(|segment| segment == "." || segment == "..")
+    {
+        return Err("endpoint com segmentos de caminho inválidos".to_string());
+    }
+    Ok(path)
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_segments(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => {
+                (*first == "*" || first == segment) && matches_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    matches_segments(&segments(pattern), &segments(path))
+}
+
+impl PolicyRule {
+    fn covers(&self, method: &str, path: &str) -> bool {
+        self.methods
+            .iter()
+            .any(|m| m == "*" || m.eq_ignore_ascii_case(method))
+            && path_matches(&self.pattern, path)
+    }
+}
+
+impl ApiPolicy {
+    /// Confere método, caminho, papel e tamanho do corpo
+    pub fn evaluate(
+        &self,
+        method: &str,
+        endpoint: &str,
+        role: Option<&str>,
+        body_bytes: usize,
+    ) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let path = normalized_path(endpoint)?;
+        if body_bytes > self.max_body_bytes {
+            return Err(format!(
+                "corpo de {} bytes excede o limite de {}",
+                body_bytes, self.max_body_bytes
+            ));
+        }
+
+        match self.rules.iter().find(|r| r.covers(method, &path)) {
+            Some(rule)
+                if rule.roles.is_empty()
+                    || role.is_some_and(|r| rule.roles.iter().any(|x| x == r)) =>
+            {
+                Ok(())
+            }
+            Some(_) => Err(format!(
+                "papel {} não pode usar {} {}",
+                role.unwrap_or("(nenhum)"),
+                method,
+                path
+            )),
+            None => Err(format!(
+                "{} {} fora da lista de endpoints permitidos",
+                method, path
+            )),
+        }
+    }
+}
+
+impl PolicyEngine {
+    pub fn attach(&self, app: AppHandle) {
+        if let Ok(mut slot) = self.app.lock() {
+            *slot = Some(app);
+        }
+    }
+
+    /// Carrega a política; na primeira execução grava a padrão para a TI editar
+    pub fn load(&self, path: PathBuf) -> AppResult<()> {
+        let policy = match storage::read_json::<ApiPolicy>(&path)? {
+            Some(policy) => policy,
+            None => {
+                let policy = ApiPolicy::default();
+                storage::write_json(&path, &policy)?;
+                info!("[Policy] Política padrão gravada em {}", path.display());
+                policy
+            }
+        };
+        if !policy.enabled {
+            warn!(
+                "[Policy] Política de endpoints desativada em {}",
+                path.display()
+            );
+        }
+        *self
+            .policy
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na política de endpoints"))? = policy;
+        Ok(())
+    }
+
+    pub fn policy(&self) -> AppResult<ApiPolicy> {
+        Ok(self
+            .policy
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na política de endpoints"))?
+            .clone())
+    }
+
+    /// Valida a requisição contra a política; chamado antes de montar a
+    /// requisição. `role` é o papel da sessão obtido pelo core no login.
+    pub fn check(&self, request: &ApiRequest, role: Option<&str>) -> AppResult<()> {
+        let body_bytes = match request.body {
+            Some(ref body) => serde_json::to_vec(body)?.len(),
+            None => 0,
+        };
+        self.policy()?
+            .evaluate(request.method.as_str(), &request.endpoint, role, body_bytes)
+            .map_err(|reason| {
+                self.report(request.method.as_str(), &request.endpoint, role, &reason);
+                AppError::permission(format!("Requisição bloqueada pela política: {}", reason))
+            })
+    }
+
+    pub fn check_upload_size(&self, endpoint: &str, bytes: u64) -> AppResult<()> {
+        let policy = self.policy()?;
+        if policy.enabled && bytes > policy.max_upload_bytes {
+            let reason = format!(
+                "arquivo de {} bytes excede o limite de {}",
+                bytes, policy.max_upload_bytes
+            );
+            self.report("POST", endpoint, None, &reason);
+            return Err(AppError::permission(format!(
+                "Upload bloqueado pela política: {}",
+                reason
+            )));
+        }
+        Ok(())
+    }
+
+    fn report(&self, method: &str, endpoint: &str, role: Option<&str>, reason: &str) {
+        warn!("[Policy] Bloqueado {} {}: {}", method, endpoint, reason);
+        let violation = PolicyViolation {
+            at: Utc::now(),
+            method: method.to_string(),
+            endpoint: endpoint.to_string(),
+            role: role.map(|r| r.to_string()),
+            reason: reason.to_string(),
+        };
+        if let Ok(mut violations) = self.violations.lock() {
+            violations.push_back(violation.clone());
+            while violations.len() > VIOLATION_HISTORY {
+                violations.pop_front();
+            }
+        }
+        if let Some(app) = self.app.lock().ok().and_then(|a| a.clone()) {
+            let _ = app.emit(EVENT_VIOLATION, violation);
+        }
+    }
+
+    /// Violações recentes, da mais nova para a mais antiga
+    pub fn violations(&self) -> AppResult<Vec<PolicyViolation>> {
+        Ok(self
+            .violations
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex nas violações de política"))?
+            .iter()
+            .rev()
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_patterns() {
+        assert!(path_matches("/pedidos/**", "/pedidos"));
+        assert!(path_matches("/pedidos/**", "/pedidos/12/itens"));
+        assert!(path_matches("/pedidos/*/status", "/pedidos/12/status"));
+        assert!(!path_matches("/pedidos/*/status", "/pedidos/12/itens"));
+        assert!(!path_matches("/pedidos/**", "/pedidos-admin"));
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy = ApiPolicy::default();
+        assert!(policy.evaluate("GET", "/pedidos?page=2", None, 0).is_ok());
+        assert!(policy
+            .evaluate("PATCH", "/pedidos/7", Some("user"), 10)
+            .is_ok());
+        assert!(policy
+            .evaluate("DELETE", "/users/3", Some("user"), 0)
+            .is_err());
+        assert!(policy
+            .evaluate("DELETE", "/users/3", Some("admin"), 0)
+            .is_ok());
+        assert!(policy
+            .evaluate("POST", "/admin/reset", Some("admin"), 0)
+            .is_err());
+        assert!(policy
+            .evaluate("DELETE", "/pedidos/all", Some("user"), 0)
+            .is_err());
+        assert!(policy.evaluate("DELETE", "/pedidos/all", None, 0).is_err());
+        assert!(policy
+            .evaluate("DELETE", "/pedidos/all", Some("admin"), 0)
+            .is_ok());
+        assert!(policy
+            .evaluate("POST", "/pedidos/reset-ids", Some("user"), 0)
+            .is_err());
+        assert!(policy
+            .evaluate("POST", "/pedidos/reset-ids", Some("admin"), 0)
+            .is_ok());
+        assert!(policy
+            .evaluate("DELETE", "/pedidos/12", Some("user"), 0)
+            .is_ok());
+        assert!(policy
+            .evaluate("POST", "/pedidos/../users/1", None, 0)
+            .is_err());
+        assert!(policy
+            .evaluate("POST", "/pedidos/%2e%2e/users", None, 0)
+            .is_err());
+        assert!(policy
+            .evaluate("POST", "/pedidos", None, policy.max_body_bytes + 1)
+            .is_err());
+        assert!(policy.evaluate("GET", "pedidos/7", None, 0).is_ok());
+        assert!(policy.evaluate("GET", "/users", Some("user"), 0).is_err());
+        assert!(policy.evaluate("GET", "/users/3", None, 0).is_err());
+        assert!(policy.evaluate("GET", "/users", Some("admin"), 0).is_ok());
+        assert!(policy
+            .evaluate(
+                "GET",
+                "/relatorios-fechamentos/pedidos/relatorio",
+                Some("user"),
+                0
+            )
+            .is_err());
+        assert!(policy
+            .evaluate(
+                "GET",
+                "/relatorios-fechamentos/pedidos/relatorio",
+                Some("admin"),
+                0
+            )
+            .is_ok());
+        assert!(policy
+            .evaluate("GET", "/tipos-pagamentos", Some("user"), 0)
+            .is_ok());
+        assert!(policy
+            .evaluate("DELETE", "users/3", Some("user"), 0)
+            .is_err());
+    }
+}
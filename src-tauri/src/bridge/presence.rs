@@ -0,0 +1,269 @@
+//! Presença da estação de trabalho e estado de conectividade com a API.
+//!
+//! Uma tarefa em segundo plano envia um heartbeat periódico com o usuário
+//! logado e a estação. Pelo resultado (e pela latência) o estado é
+//! classificado em `online`, `degraded` ou `offline` e emitido no evento
+//! `presence-status` para a UI mostrar o indicador de conexão.
+//!
+//! Cada heartbeat é uma única tentativa, sem o retry da ponte: a próxima
+//! rodada já é a nova tentativa, e a latência medida é a de uma chamada só.
+//! Uma API sem a rota de presença (404) fica `unsupported`.
+
+use super::{send_once, ApiRequest};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, info, warn};
+
+pub const EVENT_STATUS: &str = "presence-status";
+
+/// Falhas seguidas até considerar a estação offline
+const OFFLINE_AFTER_FAILURES: u32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresenceConfig {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub interval_secs: u64,
+    /// Acima dessa latência o estado vira `degraded`
+    pub degraded_latency_ms: u64,
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            endpoint: "/presence/heartbeat".to_string(),
+            interval_secs: 30,
+            degraded_latency_ms: 1500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Connectivity {
+    /// Ainda não houve heartbeat (ex: antes do login)
+    Unknown,
+    Online,
+    Degraded,
+    Offline,
+    /// A API respondeu 404: o servidor não tem rota de presença
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenceStatus {
+    pub state: Connectivity,
+    pub client_id: String,
+    pub workstation: String,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+pub struct PresenceMonitor {
+    app: Mutex<Option<AppHandle>>,
+    config: Mutex<PresenceConfig>,
+    status: Mutex<PresenceStatus>,
+    /// Evita heartbeats simultâneos (tarefa periódica + `send_heartbeat`)
+    sending: tokio::sync::Mutex<()>,
+}
+
+fn workstation_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "desconhecida".to_string())
+}
+
+impl Default for PresenceMonitor {
+    fn default() -> Self {
+        Self {
+            app: Mutex::new(None),
+            config: Mutex::new(PresenceConfig::default()),
+            status: Mutex::new(PresenceStatus {
+                state: Connectivity::Unknown,
+                client_id: uuid::Uuid::new_v4().to_string(),
+                workstation: workstation_name(),
+                last_success_at: None,
+                last_attempt_at: None,
+                last_latency_ms: None,
+                consecutive_failures: 0,
+                last_error: None,
+            }),
+            sending: tokio::sync::Mutex::new(()),
+        }
+    }
+}
+
+impl PresenceMonitor {
+    pub fn attach(&self, app: AppHandle) {
+        if let Ok(mut slot) = self.app.lock() {
+            *slot = Some(app);
+        }
+    }
+
+    pub fn config(&self) -> AppResult<PresenceConfig> {
+        Ok(self
+            .config
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na config de presença"))?
+            .clone())
+    }
+
+    pub fn configure(&self, config: PresenceConfig) -> AppResult<()> {
+        if config.interval_secs < 5 {
+            return Err(AppError::validation(
+                "Intervalo de heartbeat deve ser de ao menos 5 segundos",
+            ));
+        }
+        *self
+            .config
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na config de presença"))? = config;
+        Ok(())
+    }
+
+    pub fn status(&self) -> AppResult<PresenceStatus> {
+        Ok(self
+            .status
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no status de presença"))?
+            .clone())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut PresenceStatus)) -> PresenceStatus {
+        let snapshot = match self.status.lock() {
+            Ok(mut status) => {
+                let previous = status.state;
+                f(&mut status);
+                if status.state != previous {
+                    info!(
+                        "[Presence] Conectividade: {:?} -> {:?}",
+                        previous, status.state
+                    );
+                }
+                status.clone()
+            }
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        if let Some(app) = self.app.lock().ok().and_then(|a| a.clone()) {
+            let _ = app.emit(EVENT_STATUS, snapshot.clone());
+        }
+        snapshot
+    }
+}
+
+/// Envia um heartbeat agora e reclassifica a conectividade.
+/// `client_id` substitui o id da estação (o frontend pode manter o seu).
+pub async fn heartbeat(state: &AppState, client_id: Option<String>) -> AppResult<PresenceStatus> {
+    let presence = &state.presence;
+    let _guard = presence.sending.lock().await;
+
+    let has_token = state
+        .auth_token
+        .lock()
+        .map(|t| t.is_some())
+        .unwrap_or(false);
+    if !has_token {
+        return Err(AppError::new(
+            crate::error::ErrorKind::Auth,
+            "Heartbeat exige uma sessão ativa",
+        ));
+    }
+
+    let config = presence.config()?;
+    if let Some(id) = client_id.filter(|id| !id.trim().is_empty()) {
+        presence.update(|s| s.client_id = id);
+    }
+    let current = presence.status()?;
+    let body = json!({
+        "client_id": current.client_id,
+        "workstation": current.workstation,
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "timestamp": Utc::now().to_rfc3339(),
+    });
+    let request = ApiRequest::mutation("POST", config.endpoint.clone(), Some(body), None)?;
+
+    let started = Instant::now();
+    let result = send_once(state, &request).await;
+    let latency = started.elapsed().as_millis() as u64;
+
+    let reached = match result {
+        Ok(_) => Ok(()),
+        Err(e) if e.status == Some(404) => {
+            debug!("[Presence] API sem rota de presença: {}", e);
+            return Ok(presence.update(|s| {
+                s.last_attempt_at = Some(Utc::now());
+                s.last_latency_ms = Some(latency);
+                s.consecutive_failures = 0;
+                s.last_error = Some(e.message.clone());
+                s.state = Connectivity::Unsupported;
+            }));
+        }
+        // A API respondeu com erro de cliente: o servidor está de pé
+        Err(e) if !e.is_unreachable() && e.status.is_some_and(|s| s < 500) => {
+            debug!("[Presence] Heartbeat respondido com erro: {}", e);
+            Ok(())
+        }
+        Err(e) => Err(e),
+    };
+
+    let status = match reached {
+        Ok(()) => presence.update(|s| {
+            s.last_attempt_at = Some(Utc::now());
+            s.last_success_at = s.last_attempt_at;
+            s.last_latency_ms = Some(latency);
+            s.consecutive_failures = 0;
+            s.last_error = None;
+            s.state = if latency > config.degraded_latency_ms {
+                Connectivity::Degraded
+            } else {
+                Connectivity::Online
+            };
+        }),
+        Err(e) => presence.update(|s| {
+            s.last_attempt_at = Some(Utc::now());
+            s.last_latency_ms = None;
+            s.consecutive_failures += 1;
+            s.last_error = Some(e.message.clone());
+            s.state = if s.consecutive_failures >= OFFLINE_AFTER_FAILURES {
+                Connectivity::Offline
+            } else {
+                Connectivity::Degraded
+            };
+        }),
+    };
+    Ok(status)
+}
+
+/// Heartbeat periódico enquanto houver sessão ativa
+pub fn spawn_heartbeat_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let state = app.state::<AppState>();
+            let config = state.presence.config().unwrap_or_default();
+            tokio::time::sleep(Duration::from_secs(config.interval_secs.max(5))).await;
+
+            let logged_in = state
+                .auth_token
+                .lock()
+                .map(|t| t.is_some())
+                .unwrap_or(false);
+            if !config.enabled || !logged_in {
+                continue;
+            }
+            if let Err(e) = heartbeat(&state, None).await {
+                warn!("[Presence] Heartbeat falhou: {}", e);
+            }
+        }
+    });
+}
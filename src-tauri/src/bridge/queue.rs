@@ -0,0 +1,585 @@
+//! Fila offline persistente de mutações.
+//!
+//! Quando `rust_api_mutate` não alcança a API, a chamada é gravada em
+//! `<app_data>/offline_queue.json` e reenviada, na ordem original, assim que a
+//! conexão volta. O resultado de cada reenvio é emitido como evento Tauri.
+//!
+//! Cada item guarda a sessão (base URL + usuário) em que foi criado e só é
+//! reenviado nessa mesma sessão: trocar de perfil ou de conta não manda a
+//! mutação para outro servidor nem com as credenciais de outra pessoa. Um item
+//! em conflito segura os seguintes do mesmo recurso até ser descartado, e uma
+//! mutação nova de um recurso com itens na fila entra atrás deles em vez de ir
+//! direto para a API.
+
+use super::cache::resource_of;
+use super::{auth, ApiRequest};
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::state::AppState;
+use crate::storage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+pub const EVENT_SYNCED: &str = "offline-queue-synced";
+pub const EVENT_CONFLICT: &str = "offline-queue-conflict";
+pub const EVENT_CHANGED: &str = "offline-queue-changed";
+
+/// Intervalo entre tentativas automáticas de esvaziar a fila
+const REPLAY_INTERVAL_SECS: u64 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueuedStatus {
+    Pending,
+    /// A API rejeitou o reenvio (409, 422...). Fica na fila até o operador descartar.
+    Conflict,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedMutation {
+    pub id: String,
+    pub method: String,
+    pub endpoint: String,
+    pub body: Option<Value>,
+    pub idempotency_key: String,
+    pub created_at: DateTime<Utc>,
+    pub attempts: u32,
+    pub status: QueuedStatus,
+    pub last_error: Option<String>,
+    /// Sessão em que a mutação foi feita (`auth::session_scope`)
+    #[serde(default)]
+    pub scope: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStatus {
+    pub pending: usize,
+    pub conflicts: usize,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub replaying: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SyncedPayload {
+    id: String,
+    endpoint: String,
+    response: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ConflictPayload {
+    id: String,
+    endpoint: String,
+    error: AppError,
+}
+
+#[derive(Default)]
+pub struct OfflineQueue {
+    path: Mutex<Option<PathBuf>>,
+    items: Mutex<Vec<QueuedMutation>>,
+    last_sync_at: Mutex<Option<DateTime<Utc>>>,
+    replaying: AtomicBool,
+}
+
+impl OfflineQueue {
+    /// Carrega a fila gravada em disco. Chamado no `setup`, quando o diretório
+    /// de dados do app já é conhecido.
+    pub fn load(&self, path: PathBuf) -> AppResult<()> {
+        let items: Vec<QueuedMutation> = storage::read_json(&path)?.unwrap_or_default();
+        if !items.is_empty() {
+            info!(
+                "[OfflineQueue] {} mutações pendentes carregadas",
+                items.len()
+            );
+        }
+        *self.lock_items()? = items;
+        *self
+            .path
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no caminho da fila offline"))? =
+            Some(path);
+        Ok(())
+    }
+
+    fn lock_items(&self) -> AppResult<std::sync::MutexGuard<'_, Vec<QueuedMutation>>> {
+        self.items
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na fila offline"))
+    }
+
+    fn persist(&self, items: &[QueuedMutation]) -> AppResult<()> {
+        let path = self
+            .path
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no caminho da fila offline"))?
+            .clone();
+        match path {
+            Some(p) => storage::write_json(&p, items),
+            None => Err(AppError::config("Fila offline ainda não inicializada")),
+        }
+    }
+
+    pub fn enqueue(
+        &self,
+        request: &ApiRequest,
+        error: &AppError,
+        scope: String,
+    ) -> AppResult<String> {
+        let item = QueuedMutation {
+            id: uuid::Uuid::new_v4().to_string(),
+            method: request.method.to_string(),
+            endpoint: request.endpoint.clone(),
+            body: request.body.clone(),
+            idempotency_key: request
+                .idempotency_key
+                .clone()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            created_at: Utc::now(),
+            attempts: 1,
+            status: QueuedStatus::Pending,
+            last_error: Some(error.message.clone()),
+            scope,
+        };
+        let id = item.id.clone();
+
+        let mut items = self.lock_items()?;
+        items.push(item);
+        self.persist(&items)?;
+        Ok(id)
+    }
+
+    /// Se a sessão `scope` ainda tem itens (pendentes ou em conflito) no recurso
+    /// de `endpoint`. Uma mutação nova desse recurso não pode passar na frente deles.
+    pub fn has_queued(&self, endpoint: &str, scope: &str) -> AppResult<bool> {
+        let resource = resource_of(endpoint);
+        Ok(self
+            .lock_items()?
+            .iter()
+            .any(|item| item.scope == scope && resource_of(&item.endpoint) == resource))
+    }
+
+    pub fn list(&self) -> AppResult<Vec<QueuedMutation>> {
+        Ok(self.lock_items()?.clone())
+    }
+
+    pub fn discard(&self, id: &str) -> AppResult<bool> {
+        let mut items = self.lock_items()?;
+        let before = items.len();
+        items.retain(|item| item.id != id);
+        let removed = items.len() != before;
+        if removed {
+            self.persist(&items)?;
+        }
+        Ok(removed)
+    }
+
+    pub fn status(&self) -> AppResult<QueueStatus> {
+        let items = self.lock_items()?;
+        let conflicts = items
+            .iter()
+            .filter(|item| item.status == QueuedStatus::Conflict)
+            .count();
+        Ok(QueueStatus {
+            pending: items.len() - conflicts,
+            conflicts,
+            last_sync_at: *self
+                .last_sync_at
+                .lock()
+                .map_err(|_| AppError::internal("Erro de mutex na fila offline"))?,
+            replaying: self.replaying.load(Ordering::SeqCst),
+        })
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        apply: impl FnOnce(&mut Vec<QueuedMutation>, usize),
+    ) -> AppResult<()> {
+        let mut items = self.lock_items()?;
+        if let Some(index) = items.iter().position(|item| item.id == id) {
+            apply(&mut items, index);
+            self.persist(&items)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reenvia as mutações pendentes em ordem. Para na primeira falha de conexão,
+/// já que as seguintes falhariam pelo mesmo motivo.
+pub async fn replay(app: &AppHandle) -> AppResult<usize> {
+    let state = app.state::<AppState>();
+    let queue = &state.offline_queue;
+
+    if queue.replaying.swap(true, Ordering::SeqCst) {
+        return Ok(0);
+    }
+
+    let result = replay_pending(app, queue).await;
+
+    queue.replaying.store(false, Ordering::SeqCst);
+    if let Ok(status) = queue.status() {
+        let _ = app.emit(EVENT_CHANGED, status);
+    }
+    result
+}
+
+/// Erros 4xx (exceto autenticação e os transitórios 408/429) significam que a
+/// API recebeu e recusou a mutação: reenviar não vai adiantar, então o item
+/// vira conflito.
+fn is_rejection(error: &AppError) -> bool {
+    error.kind == ErrorKind::Permission
+        || (error.kind != ErrorKind::Auth
+            && !error.retryable
+            && matches!(error.status, Some(400..=499)))
+}
+
+/// Resultado de um item reenviado, repassado a quem emite os eventos
+enum ReplayOutcome {
+    Synced(Value),
+    Conflict(AppError),
+}
+
+async fn replay_pending(app: &AppHandle, queue: &OfflineQueue) -> AppResult<usize> {
+    let state = app.state::<AppState>();
+    let state: &AppState = &state;
+
+    let synced = replay_scope(
+        queue,
+        &auth::session_scope(state),
+        move |request| async move { super::send_once(state, &request).await },
+        |item, outcome| match outcome {
+            ReplayOutcome::Synced(response) => {
+                state.response_cache.invalidate_resource(&item.endpoint);
+                let _ = app.emit(
+                    EVENT_SYNCED,
+                    SyncedPayload {
+                        id: item.id.clone(),
+                        endpoint: item.endpoint.clone(),
+                        response,
+                    },
+                );
+            }
+            ReplayOutcome::Conflict(error) => {
+                let _ = app.emit(
+                    EVENT_CONFLICT,
+                    ConflictPayload {
+                        id: item.id.clone(),
+                        endpoint: item.endpoint.clone(),
+                        error,
+                    },
+                );
+            }
+        },
+    )
+    .await?;
+
+    if synced > 0 {
+        if let Ok(mut last) = queue.last_sync_at.lock() {
+            *last = Some(Utc::now());
+        }
+    }
+
+    Ok(synced)
+}
+
+/// Reenvia, em ordem, os itens pendentes da sessão `scope`. Itens de outras
+/// sessões ficam intocados; os de um recurso com conflito aguardam.
+async fn replay_scope<S, F>(
+    queue: &OfflineQueue,
+    scope: &str,
+    mut send: S,
+    mut notify: impl FnMut(&QueuedMutation, ReplayOutcome),
+) -> AppResult<usize>
+where
+    S: FnMut(ApiRequest) -> F,
+    F: Future<Output = AppResult<Value>>,
+{
+    let items: Vec<QueuedMutation> = queue
+        .list()?
+        .into_iter()
+        .filter(|item| item.scope == scope)
+        .collect();
+
+    let mut blocked: HashSet<String> = items
+        .iter()
+        .filter(|item| item.status == QueuedStatus::Conflict)
+        .map(|item| resource_of(&item.endpoint).to_string())
+        .collect();
+
+    let mut synced = 0;
+    for item in items {
+        if item.status != QueuedStatus::Pending {
+            continue;
+        }
+        let resource = resource_of(&item.endpoint).to_string();
+        if blocked.contains(&resource) {
+            info!(
+                "[OfflineQueue] {} {} aguardando conflito anterior em /{}",
+                item.method, item.endpoint, resource
+            );
+            continue;
+        }
+
+        let result = match super::parse_mutation_method(&item.method) {
+            Ok(method) => {
+                send(ApiRequest {
+                    method,
+                    endpoint: item.endpoint.clone(),
+                    params: None,
+                    body: item.body.clone(),
+                    idempotency_key: Some(item.idempotency_key.clone()),
+                })
+                .await
+            }
+            Err(e) => {
+                warn!("[OfflineQueue] Item {} inválido: {}", item.id, e);
+                Err(e)
+            }
+        };
+
+        match result {
+            Ok(response) => {
+                queue.update(&item.id, |items, index| {
+                    items.remove(index);
+                })?;
+                synced += 1;
+                info!(
+                    "[OfflineQueue] {} {} sincronizado",
+                    item.method, item.endpoint
+                );
+                notify(&item, ReplayOutcome::Synced(response));
+            }
+            Err(e) if e.kind != ErrorKind::Validation && !is_rejection(&e) => {
+                queue.update(&item.id, |items, index| {
+                    items[index].attempts += 1;
+                    items[index].last_error = Some(e.message.clone());
+                })?;
+                break;
+            }
+            Err(e) => {
+                warn!(
+                    "[OfflineQueue] {} {} rejeitado: {}",
+                    item.method, item.endpoint, e
+                );
+                queue.update(&item.id, |items, index| {
+                    items[index].attempts += 1;
+                    items[index].status = QueuedStatus::Conflict;
+                    items[index].last_error = Some(e.message.clone());
+                })?;
+                blocked.insert(resource);
+                notify(&item, ReplayOutcome::Conflict(e));
+            }
+        }
+    }
+
+    Ok(synced)
+}
+
+/// Inicia a tarefa em segundo plano que tenta esvaziar a fila periodicamente
+pub fn spawn_replay_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(REPLAY_INTERVAL_SECS)).await;
+
+            let has_pending = app
+                .state::<AppState>()
+                .offline_queue
+                .status()
+                .map(|s| s.pending > 0)
+                .unwrap_or(false);
+
+            if has_pending {
+                if let Err(e) = replay(&app).await {
+                    warn!("[OfflineQueue] Erro ao reenviar fila: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn temp_queue() -> (OfflineQueue, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("sgp-queue-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("offline_queue.json");
+        let queue = OfflineQueue::default();
+        queue.load(path.clone()).unwrap();
+        (queue, dir)
+    }
+
+    fn mutation(method: &str, endpoint: &str) -> ApiRequest {
+        ApiRequest::mutation(method, endpoint.to_string(), None, None).unwrap()
+    }
+
+    #[test]
+    fn test_queue_survives_reload() {
+        let (queue, dir) = temp_queue();
+        let offline = AppError::network("sem conexão");
+        let id = queue
+            .enqueue(
+                &mutation("POST", "/pedidos/"),
+                &offline,
+                "http://a#user:1".to_string(),
+            )
+            .unwrap();
+
+        let reloaded = OfflineQueue::default();
+        reloaded.load(dir.join("offline_queue.json")).unwrap();
+        let items = reloaded.list().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, id);
+        assert_eq!(items[0].scope, "http://a#user:1");
+        assert_eq!(items[0].status, QueuedStatus::Pending);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_replay_only_sends_items_of_the_current_session() {
+        let (queue, dir) = temp_queue();
+        let offline = AppError::network("sem conexão");
+        queue
+            .enqueue(
+                &mutation("POST", "/pedidos/"),
+                &offline,
+                "http://a#user:1".to_string(),
+            )
+            .unwrap();
+        queue
+            .enqueue(
+                &mutation("POST", "/clientes/"),
+                &offline,
+                "http://b#user:2".to_string(),
+            )
+            .unwrap();
+
+        let sent = RefCell::new(Vec::new());
+        let synced = tauri::async_runtime::block_on(replay_scope(
+            &queue,
+            "http://a#user:1",
+            |request| {
+                sent.borrow_mut().push(request.endpoint.clone());
+                async { Ok(Value::Null) }
+            },
+            |_, _| {},
+        ))
+        .unwrap();
+
+        assert_eq!(synced, 1);
+        assert_eq!(*sent.borrow(), vec!["/pedidos/".to_string()]);
+        let left = queue.list().unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].scope, "http://b#user:2");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_conflict_blocks_later_items_of_the_same_resource() {
+        let (queue, dir) = temp_queue();
+        let offline = AppError::network("sem conexão");
+        let scope = "http://a#user:1".to_string();
+        let first = queue
+            .enqueue(&mutation("PUT", "/pedidos/7"), &offline, scope.clone())
+            .unwrap();
+        let second = queue
+            .enqueue(
+                &mutation("PATCH", "/pedidos/7/status"),
+                &offline,
+                scope.clone(),
+            )
+            .unwrap();
+        queue
+            .enqueue(&mutation("POST", "/clientes/"), &offline, scope.clone())
+            .unwrap();
+
+        let sent = RefCell::new(Vec::new());
+        let synced = tauri::async_runtime::block_on(replay_scope(
+            &queue,
+            &scope,
+            |request| {
+                sent.borrow_mut().push(request.endpoint.clone());
+                let conflict = request.endpoint == "/pedidos/7";
+                async move {
+                    if conflict {
+                        Err(AppError::from_status(409, "versão antiga".to_string()))
+                    } else {
+                        Ok(Value::Null)
+                    }
+                }
+            },
+            |_, _| {},
+        ))
+        .unwrap();
+
+        assert_eq!(synced, 1);
+        assert_eq!(
+            *sent.borrow(),
+            vec!["/pedidos/7".to_string(), "/clientes/".to_string()]
+        );
+        let items = queue.list().unwrap();
+        let status_of = |id: &str| items.iter().find(|i| i.id == id).unwrap().status;
+        assert_eq!(status_of(&first), QueuedStatus::Conflict);
+        assert_eq!(status_of(&second), QueuedStatus::Pending);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_has_queued_matches_resource_and_session() {
+        let (queue, dir) = temp_queue();
+        let offline = AppError::network("sem conexão");
+        queue
+            .enqueue(&mutation("PUT", "/pedidos/7"), &offline, "s".to_string())
+            .unwrap();
+
+        assert!(queue.has_queued("/pedidos/7/status", "s").unwrap());
+        assert!(queue.has_queued("/pedidos/", "s").unwrap());
+        assert!(!queue.has_queued("/pedidos/7", "outra").unwrap());
+        assert!(!queue.has_queued("/pedidos-itens/1", "s").unwrap());
+        assert!(!queue.has_queued("/clientes/", "s").unwrap());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_invalid_item_becomes_conflict() {
+        let (queue, dir) = temp_queue();
+        let offline = AppError::network("sem conexão");
+        let id = queue
+            .enqueue(&mutation("POST", "/pedidos/"), &offline, "s".to_string())
+            .unwrap();
+        queue
+            .update(&id, |items, index| items[index].method = "GET".to_string())
+            .unwrap();
+
+        let conflicts = RefCell::new(0);
+        tauri::async_runtime::block_on(replay_scope(
+            &queue,
+            "s",
+            |_| async { Ok(Value::Null) },
+            |_, outcome| {
+                if let ReplayOutcome::Conflict(_) = outcome {
+                    *conflicts.borrow_mut() += 1;
+                }
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(*conflicts.borrow(), 1);
+        assert_eq!(queue.list().unwrap()[0].status, QueuedStatus::Conflict);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
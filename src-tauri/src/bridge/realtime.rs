@@ -0,0 +1,614 @@
+//! Conexão WebSocket única com `/ws/orders`, mantida pelo core.
+//!
+//! Antes cada hook do WebView abria o próprio socket e precisava evitar
+//! conexões duplicadas e laços de reconexão; um reload da página derrubava
+//! tudo. Agora uma única tarefa em segundo plano mantém a conexão usando a
+//! base URL e o token do [`AppState`], com ping periódico, watchdog e
+//! reconexão com backoff exponencial. Cada janela assina os tipos de mensagem
+//! que quer (`order_*`, `notification`...) e recebe só esses no evento
+//! `realtime-event`; as assinaturas ficam no Rust e sobrevivem a reloads.
+//!
+//! O socket segue a mesma configuração do cliente HTTP: passa pelo proxy
+//! (túnel `CONNECT`), usa as CAs extras e confere o pin do certificado antes
+//! de mandar qualquer credencial. O token nunca vai na URL (que acaba em logs
+//! de proxy e do servidor); ele segue só na mensagem `authenticate`.
+
+use super::retry::RetryPolicy;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, Notify};
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{Connector, MaybeTlsStream};
+use tracing::{debug, info, warn};
+
+pub const EVENT_MESSAGE: &str = "realtime-event";
+pub const EVENT_STATUS: &str = "realtime-status";
+
+const WS_PATH: &str = "/ws/orders";
+/// Cloudflare derruba túneis ociosos; o ping mantém a conexão viva
+const PING_INTERVAL: Duration = Duration::from_secs(15);
+/// Sem nenhuma mensagem do servidor por esse tempo, a conexão é dada como morta
+const IDLE_TIMEOUT: Duration = Duration::from_secs(35);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Motivo de fechamento usado pela API quando o mesmo usuário conecta em outro lugar
+const SESSION_REPLACED_REASON: &str = "Nova conexão do mesmo usuário";
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RealtimeStatus {
+    pub connected: bool,
+    pub reconnect_attempts: u32,
+    pub last_event_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Reconexão suspensa (token recusado ou sessão aberta em outro lugar)
+    /// até um novo token ou `realtime_connect`
+    pub halted: bool,
+}
+
+#[derive(Default)]
+pub struct RealtimeHub {
+    app: Mutex<Option<AppHandle>>,
+    enabled: AtomicBool,
+    started: AtomicBool,
+    /// Acorda a tarefa para reavaliar a conexão (token novo, connect/disconnect)
+    wake: Notify,
+    outbox: Mutex<Option<mpsc::UnboundedSender<Message>>>,
+    /// Padrões de tipo de mensagem assinados por cada janela
+    subscriptions: Mutex<HashMap<String, HashSet<String>>>,
+    status: Mutex<RealtimeStatus>,
+}
+
+enum Outcome {
+    /// Falha transitória; reconectar com backoff
+    Failed { was_connected: bool, error: String },
+    /// Não adianta reconectar com o mesmo token
+    Halted(String),
+    /// Configuração mudou durante a conexão; reavaliar imediatamente
+    Woken,
+}
+
+fn topic_matches(pattern: &str, message_type: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => message_type.starts_with(prefix),
+        None => pattern == message_type,
+    }
+}
+
+fn close_outcome(frame: Option<CloseFrame<'_>>) -> Outcome {
+    let Some(frame) = frame else {
+        return Outcome::Failed {
+            was_connected: true,
+            error: "Conexão fechada pelo servidor".to_string(),
+        };
+    };
+    let code = u16::from(frame.code);
+    match code {
+        1008 => Outcome::Halted(if frame.reason.is_empty() {
+            "Token inválido ou ausente".to_string()
+        } else {
+            frame.reason.to_string()
+        }),
+        1000 if frame.reason == SESSION_REPLACED_REASON => {
+            Outcome::Halted("Outra sessão ativa - conexão fechada pelo servidor".to_string())
+        }
+        _ => Outcome::Failed {
+            was_connected: true,
+            error: format!("Conexão fechada pelo servidor ({} {})", code, frame.reason),
+        },
+    }
+}
+
+/// URL `ws(s)://<host>/ws/orders` a partir da configuração da ponte; `None`
+/// sem base URL ou sem sessão
+fn ws_target(state: &AppState) -> Option<reqwest::Url> {
+    let base_url = state.api_base_url.lock().ok()?.clone();
+    let has_token = state.auth_token.lock().ok()?.is_some();
+    if base_url.is_empty() || !has_token {
+        return None;
+    }
+    let mut url = reqwest::Url::parse(&base_url).ok()?;
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    url.set_scheme(scheme).ok()?;
+    url.set_path(WS_PATH);
+    url.set_query(None);
+    url.set_fragment(None);
+    Some(url)
+}
+
+/// Tamanho máximo da resposta do proxy ao `CONNECT`
+const MAX_PROXY_RESPONSE: usize = 8 * 1024;
+
+/// Abre o TCP até o host do WebSocket, por um túnel `CONNECT` quando o
+/// cliente HTTP tem proxy configurado
+async fn open_stream(state: &AppState, url: &reqwest::Url) -> AppResult<TcpStream> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| AppError::config("URL do WebSocket sem host"))?;
+    let target = format!("{}:{}", host, url.port_or_known_default().unwrap_or(80));
+
+    let Some(proxy_url) = state.http.config()?.proxy_url else {
+        return Ok(TcpStream::connect(&target).await?);
+    };
+    let proxy = reqwest::Url::parse(&proxy_url)
+        .map_err(|e| AppError::validation(format!("Proxy inválido: {}", e)))?;
+    if proxy.scheme() != "http" {
+        return Err(AppError::config(format!(
+            "Proxy {} não suportado no WebSocket; use http://",
+            proxy.scheme()
+        )));
+    }
+    let proxy_host = proxy
+        .host_str()
+        .ok_or_else(|| AppError::validation("Proxy sem host"))?;
+    let mut stream = TcpStream::connect(format!(
+        "{}:{}",
+        proxy_host,
+        proxy.port_or_known_default().unwrap_or(80)
+    ))
+    .await?;
+
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if !proxy.username().is_empty() {
+        let decode = |s: &str| {
+            urlencoding::decode(s)
+                .map(|d| d.into_owned())
+                .unwrap_or_else(|_| s.to_string())
+        };
+        let credentials = format!(
+            "{}:{}",
+            decode(proxy.username()),
+            decode(proxy.password().unwrap_or_default())
+        );
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            general_purpose::STANDARD.encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // Lê byte a byte até o fim dos headers para não consumir o início do TLS
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= MAX_PROXY_RESPONSE {
+            return Err(AppError::parse("Resposta do proxy grande demais"));
+        }
+        if stream.read(&mut byte).await? == 0 {
+            return Err(AppError::network(
+                "Proxy fechou a conexão durante o CONNECT",
+            ));
+        }
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status_line = head.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(AppError::network(format!(
+            "Proxy recusou o túnel para {}: {}",
+            target, status_line
+        )));
+    }
+    Ok(stream)
+}
+
+impl RealtimeHub {
+    pub fn attach(&self, app: AppHandle) {
+        if let Ok(mut slot) = self.app.lock() {
+            *slot = Some(app);
+        }
+    }
+
+    fn app(&self) -> Option<AppHandle> {
+        self.app.lock().ok().and_then(|a| a.clone())
+    }
+
+    /// Inicia a tarefa de conexão uma única vez; chamado no `setup`
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if let Some(app) = self.app() {
+            spawn_supervisor(app);
+        }
+    }
+
+    pub fn connect(&self) {
+        self.enabled.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    pub fn disconnect(&self) {
+        self.enabled.store(false, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    /// Base URL ou token mudaram: refaz a conexão com os dados novos
+    pub fn reconnect(&self) {
+        self.wake.notify_one();
+    }
+
+    pub fn status(&self) -> AppResult<RealtimeStatus> {
+        Ok(self
+            .status
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no status do WebSocket"))?
+            .clone())
+    }
+
+    fn update_status(&self, f: impl FnOnce(&mut RealtimeStatus)) {
+        let snapshot = match self.status.lock() {
+            Ok(mut status) => {
+                f(&mut status);
+                status.clone()
+            }
+            Err(_) => return,
+        };
+        if let Some(app) = self.app() {
+            let _ = app.emit(EVENT_STATUS, snapshot);
+        }
+    }
+
+    /// Adiciona padrões de tipo (`order_created`, `order_*` ou `*`) às assinaturas da janela
+    pub fn subscribe(&self, window: &str, topics: Vec<String>) -> AppResult<()> {
+        if topics.iter().any(|t| t.trim().is_empty()) {
+            return Err(AppError::validation("Tópico vazio"));
+        }
+        self.subscriptions
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex nas assinaturas"))?
+            .entry(window.to_string())
+            .or_default()
+            .extend(topics);
+        Ok(())
+    }
+
+    /// Remove os tópicos informados, ou todos se `topics` for `None`
+    pub fn unsubscribe(&self, window: &str, topics: Option<Vec<String>>) -> AppResult<()> {
+        let mut subscriptions = self
+            .subscriptions
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex nas assinaturas"))?;
+        match topics {
+            Some(topics) => {
+                if let Some(set) = subscriptions.get_mut(window) {
+                    for topic in &topics {
+                        set.remove(topic);
+                    }
+                }
+            }
+            None => {
+                subscriptions.remove(window);
+            }
+        }
+        Ok(())
+    }
+
+    /// Envia uma mensagem (ex: broadcast de pedido alterado) pela conexão ativa
+    pub fn send(&self, message: &Value) -> AppResult<()> {
+        let outbox = self
+            .outbox
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no WebSocket"))?
+            .clone();
+        match outbox {
+            Some(tx) if tx.send(Message::Text(message.to_string())).is_ok() => Ok(()),
+            _ => Err(AppError::network("WebSocket não conectado")),
+        }
+    }
+
+    /// Entrega a mensagem só às janelas que assinaram o tipo dela
+    fn fan_out(&self, message: &Value) {
+        let Some(app) = self.app() else { return };
+        let message_type = message
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default();
+        let targets: Vec<String> = match self.subscriptions.lock() {
+            Ok(subscriptions) => subscriptions
+                .iter()
+                .filter(|(_, topics)| topics.iter().any(|t| topic_matches(t, message_type)))
+                .map(|(label, _)| label.clone())
+                .collect(),
+            Err(_) => return,
+        };
+        for label in targets {
+            let _ = app.emit_to(label.as_str(), EVENT_MESSAGE, message);
+        }
+    }
+
+    /// Trata uma mensagem de texto; devolve a resposta a enviar, se houver
+    fn handle_text(&self, text: &str) -> Option<Message> {
+        // Sem emitir `realtime-status`: seria um evento a cada mensagem
+        if let Ok(mut status) = self.status.lock() {
+            status.last_event_at = Some(Utc::now());
+        }
+        let message: Value = match serde_json::from_str(text) {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("[Realtime] Mensagem inválida recebida: {}", e);
+                return None;
+            }
+        };
+        match message.get("type").and_then(|t| t.as_str()) {
+            Some("ping") => Some(Message::Text(
+                json!({ "type": "pong", "timestamp": Utc::now().timestamp_millis() }).to_string(),
+            )),
+            Some("pong") => None,
+            _ => {
+                self.fan_out(&message);
+                None
+            }
+        }
+    }
+}
+
+async fn run_connection(state: &AppState, url: reqwest::Url) -> Outcome {
+    let hub = &state.realtime;
+    let failed = |error: String| Outcome::Failed {
+        was_connected: false,
+        error,
+    };
+
+    let connector = match state.http.native_tls_connector() {
+        Ok(c) => Connector::NativeTls(c),
+        Err(e) => return failed(e.message),
+    };
+
+    let connect = async {
+        let stream = open_stream(state, &url)
+            .await
+            .map_err(|e| failed(format!("Falha ao conectar WebSocket: {}", e.message)))?;
+        match tokio_tungstenite::client_async_tls_with_config(
+            url.as_str(),
+            stream,
+            None,
+            Some(connector),
+        )
+        .await
+        {
+            Ok((ws, _)) => Ok(ws),
+            Err(tungstenite::Error::Http(response))
+                if matches!(response.status().as_u16(), 401 | 403) =>
+            {
+                Err(Outcome::Halted(
+                    "Conexão recusada pelo WebSocket".to_string(),
+                ))
+            }
+            Err(e) => Err(failed(format!("Falha ao conectar WebSocket: {}", e))),
+        }
+    };
+    let ws = tokio::select! {
+        result = tokio::time::timeout(CONNECT_TIMEOUT, connect) => match result {
+            Ok(Ok(ws)) => ws,
+            Ok(Err(outcome)) => return outcome,
+            Err(_) => return failed("Tempo limite ao conectar WebSocket".to_string()),
+        },
+        _ = hub.wake.notified() => return Outcome::Woken,
+    };
+
+    // O pin é conferido antes de o token sair pela conexão
+    let cert = match ws.get_ref() {
+        MaybeTlsStream::NativeTls(tls) => tls
+            .get_ref()
+            .peer_certificate()
+            .ok()
+            .flatten()
+            .and_then(|c| c.to_der().ok()),
+        _ => None,
+    };
+    if let Err(e) = state.http.check_certificate(cert.as_deref()) {
+        return Outcome::Halted(e.message);
+    }
+
+    info!(
+        "[Realtime] Conectado a {}{}",
+        url.host_str().unwrap_or_default(),
+        WS_PATH
+    );
+    let (mut sink, mut stream) = ws.split();
+
+    // A API autentica o socket pela primeira mensagem após o handshake
+    let token = state.auth_token.lock().ok().and_then(|t| t.clone());
+    if let Some(token) = token {
+        let auth = json!({ "type": "authenticate", "token": token }).to_string();
+        if let Err(e) = sink.send(Message::Text(auth)).await {
+            return failed(format!("Falha ao autenticar WebSocket: {}", e));
+        }
+    }
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    if let Ok(mut outbox) = hub.outbox.lock() {
+        *outbox = Some(tx);
+    }
+    hub.update_status(|s| {
+        s.connected = true;
+        s.reconnect_attempts = 0;
+        s.last_error = None;
+        s.halted = false;
+    });
+
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    ping.tick().await;
+    let mut last_seen = Instant::now();
+
+    let outcome = loop {
+        tokio::select! {
+            incoming = stream.next() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    last_seen = Instant::now();
+                    if let Some(reply) = hub.handle_text(&text) {
+                        let _ = sink.send(reply).await;
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => break close_outcome(frame),
+                Some(Ok(_)) => last_seen = Instant::now(),
+                Some(Err(e)) => break Outcome::Failed { was_connected: true, error: e.to_string() },
+                None => break close_outcome(None),
+            },
+            outgoing = rx.recv() => {
+                if let Some(message) = outgoing {
+                    if let Err(e) = sink.send(message).await {
+                        break Outcome::Failed { was_connected: true, error: e.to_string() };
+                    }
+                }
+            }
+            _ = ping.tick() => {
+                if last_seen.elapsed() > IDLE_TIMEOUT {
+                    break Outcome::Failed {
+                        was_connected: true,
+                        error: "Heartbeat timeout".to_string(),
+                    };
+                }
+                let message = json!({ "type": "ping", "timestamp": Utc::now().timestamp_millis() });
+                if let Err(e) = sink.send(Message::Text(message.to_string())).await {
+                    break Outcome::Failed { was_connected: true, error: e.to_string() };
+                }
+            }
+            _ = hub.wake.notified() => break Outcome::Woken,
+        }
+    };
+
+    if let Ok(mut outbox) = hub.outbox.lock() {
+        *outbox = None;
+    }
+    let _ = sink.send(Message::Close(None)).await;
+    hub.update_status(|s| s.connected = false);
+    outcome
+}
+
+fn spawn_supervisor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let backoff = RetryPolicy {
+            max_attempts: u32::MAX,
+            base_delay_ms: 1_000,
+            max_delay_ms: 60_000,
+            retry_mutations: false,
+        };
+        let mut attempt: u32 = 0;
+
+        loop {
+            let state = app.state::<AppState>();
+            let hub = &state.realtime;
+
+            let target = if hub.enabled.load(Ordering::SeqCst) {
+                ws_target(&state)
+            } else {
+                None
+            };
+            let Some(url) = target else {
+                hub.update_status(|s| s.connected = false);
+                hub.wake.notified().await;
+                continue;
+            };
+
+            match run_connection(&state, url).await {
+                Outcome::Woken => {
+                    debug!("[Realtime] Configuração alterada, reavaliando conexão");
+                    attempt = 0;
+                }
+                Outcome::Halted(reason) => {
+                    warn!("[Realtime] Reconexão suspensa: {}", reason);
+                    attempt = 0;
+                    hub.update_status(|s| {
+                        s.connected = false;
+                        s.halted = true;
+                        s.last_error = Some(reason);
+                    });
+                    hub.wake.notified().await;
+                }
+                Outcome::Failed {
+                    was_connected,
+                    error,
+                } => {
+                    attempt = if was_connected { 1 } else { attempt + 1 };
+                    let delay = backoff.delay_for(attempt);
+                    warn!(
+                        "[Realtime] {} (tentativa {}). Reconectando em {:?}",
+                        error, attempt, delay
+                    );
+                    hub.update_status(|s| {
+                        s.connected = false;
+                        s.reconnect_attempts = attempt;
+                        s.last_error = Some(error);
+                    });
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = hub.wake.notified() => attempt = 0,
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_client::HttpClientConfig;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_ws_target_keeps_token_out_of_url() {
+        let state = AppState::new();
+        *state.api_base_url.lock().unwrap() = "https://api.exemplo.com/?x=1".to_string();
+        assert!(ws_target(&state).is_none());
+
+        *state.auth_token.lock().unwrap() = Some("segredo".to_string());
+        let url = ws_target(&state).unwrap();
+        assert_eq!(url.as_str(), "wss://api.exemplo.com/ws/orders");
+    }
+
+    #[test]
+    fn test_open_stream_tunnels_through_proxy() {
+        tauri::async_runtime::block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let proxy_addr = listener.local_addr().unwrap();
+            let proxy = tokio::spawn(async move {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 512];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                socket
+                    .write_all(b"HTTP/1.1 200 Connection established\r\n\r\nola")
+                    .await
+                    .unwrap();
+                String::from_utf8(request).unwrap()
+            });
+
+            let state = AppState::new();
+            state
+                .http
+                .reconfigure(HttpClientConfig {
+                    proxy_url: Some(format!("http://usuario:s%40nha@{}", proxy_addr)),
+                    ..HttpClientConfig::default()
+                })
+                .unwrap();
+            let url = reqwest::Url::parse("wss://api.exemplo.com/ws/orders").unwrap();
+            let mut stream = open_stream(&state, &url).await.unwrap();
+
+            let mut greeting = [0u8; 3];
+            stream.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(&greeting, b"ola");
+
+            let request = proxy.await.unwrap();
+            assert!(request.starts_with("CONNECT api.exemplo.com:443 HTTP/1.1\r\n"));
+            let credentials = general_purpose::STANDARD.encode("usuario:s@nha");
+            assert!(request.contains(&format!("Proxy-Authorization: Basic {}", credentials)));
+        });
+    }
+}
@@ -0,0 +1,70 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Política de retentativa das chamadas da ponte com a API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Número total de tentativas (1 = sem retry)
+    pub max_attempts: u32,
+    /// Atraso da primeira retentativa, dobrado a cada nova tentativa
+    pub base_delay_ms: u64,
+    /// Teto do atraso entre tentativas
+    pub max_delay_ms: u64,
+    /// Permite retentar POST/PUT/PATCH/DELETE (sempre com Idempotency-Key)
+    pub retry_mutations: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 300,
+            max_delay_ms: 5_000,
+            retry_mutations: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn allows(&self, method: &Method) -> bool {
+        *method == Method::GET || self.retry_mutations
+    }
+
+    /// Atraso antes da tentativa `attempt + 1` (backoff exponencial com jitter de até 20%)
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(16));
+        let capped = exp.min(self.max_delay_ms);
+        let jitter = (uuid::Uuid::new_v4().as_u128() % 1000) as u64 * capped / 5_000;
+        Duration::from_millis(capped + jitter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay_ms: 100,
+            max_delay_ms: 1_000,
+            retry_mutations: false,
+        };
+
+        let first = policy.delay_for(1).as_millis();
+        let second = policy.delay_for(2).as_millis();
+        let last = policy.delay_for(10).as_millis();
+
+        assert!((100..=120).contains(&first));
+        assert!((200..=240).contains(&second));
+        assert!((1_000..=1_200).contains(&last));
+
+        assert!(policy.allows(&Method::GET));
+        assert!(!policy.allows(&Method::POST));
+    }
+}
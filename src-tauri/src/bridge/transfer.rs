@@ -0,0 +1,474 @@
+//! Upload e download de arquivos pela ponte, em streaming.
+//!
+//! Os bytes nunca passam pelo IPC: o upload lê o arquivo direto do disco e o
+//! download grava direto no disco. O progresso é emitido no evento
+//! `transfer-progress` e cada transferência pode ser cancelada pelo id; o
+//! cancelamento interrompe na hora até uma leitura parada na rede.
+//!
+//! O download é gravado em `<nome>.<id>.part` ao lado do destino e só então
+//! ganha o nome final. Um arquivo que já exista no destino nunca é
+//! sobrescrito: o download recebe `nome (1).ext`, `nome (2).ext`...
+
+use super::{prepare, read_json_response, send, with_retry, ApiRequest};
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::state::AppState;
+use futures_util::{Stream, StreamExt};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Body, Method};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+pub const EVENT_PROGRESS: &str = "transfer-progress";
+
+/// Transferências podem levar bem mais que o timeout padrão das chamadas JSON
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Tempo máximo sem receber nenhum byte durante um download. Uma conexão
+/// parada falha aqui em vez de esperar o `TRANSFER_TIMEOUT` inteiro.
+const CHUNK_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Emite progresso a cada tantos bytes (além do início e do fim)
+const PROGRESS_STEP: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Upload,
+    Download,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ProgressPayload {
+    id: String,
+    direction: Direction,
+    transferred: u64,
+    total: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadResult {
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// Tokens de cancelamento das transferências em andamento
+#[derive(Default)]
+pub struct TransferRegistry {
+    active: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl TransferRegistry {
+    fn register(&self, id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Ok(mut active) = self.active.lock() {
+            active.insert(id.to_string(), token.clone());
+        }
+        token
+    }
+
+    fn finish(&self, id: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(id);
+        }
+    }
+
+    /// Marca a transferência como cancelada; retorna `false` se ela não existe
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.active.lock().ok().and_then(|a| a.get(id).cloned()) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+struct Progress {
+    app: AppHandle,
+    id: String,
+    direction: Direction,
+    total: Option<u64>,
+    transferred: u64,
+    last_emitted: u64,
+}
+
+impl Progress {
+    fn new(app: &AppHandle, id: &str, direction: Direction, total: Option<u64>) -> Self {
+        let progress = Self {
+            app: app.clone(),
+            id: id.to_string(),
+            direction,
+            total,
+            transferred: 0,
+            last_emitted: 0,
+        };
+        progress.emit();
+        progress
+    }
+
+    fn advance(&mut self, bytes: u64) {
+        self.transferred += bytes;
+        let done = self.total.is_some_and(|t| self.transferred >= t);
+        if done || self.transferred - self.last_emitted >= PROGRESS_STEP {
+            self.last_emitted = self.transferred;
+            self.emit();
+        }
+    }
+
+    fn emit(&self) {
+        let _ = self.app.emit(
+            EVENT_PROGRESS,
+            ProgressPayload {
+                id: self.id.clone(),
+                direction: self.direction,
+                transferred: self.transferred,
+                total: self.total,
+            },
+        );
+    }
+}
+
+fn cancelled(id: &str) -> AppError {
+    AppError::cancelled(format!("Transferência {} cancelada", id))
+}
+
+/// Envia `file_path` como multipart (campo `field_name`) para `endpoint`
+pub async fn upload(
+    app: &AppHandle,
+    state: &AppState,
+    id: &str,
+    endpoint: String,
+    file_path: &Path,
+    field_name: &str,
+    fields: HashMap<String, String>,
+) -> AppResult<Value> {
+    let total = tokio::fs::metadata(file_path)
+        .await
+        .map_err(|e| AppError::io(format!("Erro ao ler arquivo para upload: {}", e)))?
+        .len();
+    state.policy.check_upload_size(&endpoint, total)?;
+    let file_name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "arquivo".to_string());
+    let mime = infer::get_from_path(file_path)
+        .ok()
+        .flatten()
+        .map(|t| t.mime_type().to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let request = ApiRequest {
+        method: Method::POST,
+        endpoint,
+        params: None,
+        body: None,
+        idempotency_key: Some(uuid::Uuid::new_v4().to_string()),
+    };
+    let cancel = state.transfers.register(id);
+
+    // Cada tentativa reabre o arquivo: o stream da tentativa anterior já foi consumido
+    let attempts = with_retry(state, &request, || {
+        let cancel = cancel.clone();
+        let fields = fields.clone();
+        let file_name = file_name.clone();
+        let mime = mime.clone();
+        let request = &request;
+        async move {
+            if cancel.is_cancelled() {
+                return Err(cancelled(id));
+            }
+            let file = tokio::fs::File::open(file_path)
+                .await
+                .map_err(|e| AppError::io(format!("Erro ao abrir arquivo para upload: {}", e)))?;
+
+            let mut progress = Progress::new(app, id, Direction::Upload, Some(total));
+            let stream = ReaderStream::new(file).map(move |chunk| {
+                if cancel.is_cancelled() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Interrupted,
+                        "cancelado",
+                    ));
+                }
+                if let Ok(ref bytes) = chunk {
+                    progress.advance(bytes.len() as u64);
+                }
+                chunk
+            });
+
+            let part = Part::stream_with_length(Body::wrap_stream(stream), total)
+                .file_name(file_name)
+                .mime_str(&mime)
+                .map_err(|e| AppError::validation(format!("Tipo MIME inválido: {}", e)))?;
+            let mut form = Form::new().part(field_name.to_string(), part);
+            for (key, value) in fields {
+                form = form.text(key, value);
+            }
+
+            let builder = prepare(state, request, &[])
+                .await?
+                .timeout(TRANSFER_TIMEOUT)
+                .multipart(form);
+            read_json_response(send(state, builder).await?).await
+        }
+    });
+    let result = tokio::select! {
+        result = attempts => result,
+        _ = cancel.cancelled() => Err(cancelled(id)),
+    };
+
+    let was_cancelled = cancel.is_cancelled();
+    state.transfers.finish(id);
+    if was_cancelled {
+        return Err(cancelled(id));
+    }
+    if result.is_ok() {
+        info!(
+            "[Transfer] Upload {} concluído ({} bytes)",
+            file_name, total
+        );
+    }
+    result
+}
+
+/// Baixa `endpoint` direto para `dest`, passando por um arquivo `.part`.
+/// O caminho devolvido pode ser outro se `dest` já existir.
+pub async fn download(
+    app: &AppHandle,
+    state: &AppState,
+    id: &str,
+    endpoint: String,
+    params: Option<HashMap<String, String>>,
+    dest: PathBuf,
+) -> AppResult<DownloadResult> {
+    let request = ApiRequest::get(endpoint, params);
+    let cancel = state.transfers.register(id);
+    let result = download_inner(app, state, id, &request, &dest, &cancel).await;
+    state.transfers.finish(id);
+    result
+}
+
+async fn download_inner(
+    app: &AppHandle,
+    state: &AppState,
+    id: &str,
+    request: &ApiRequest,
+    dest: &Path,
+    cancel: &CancellationToken,
+) -> AppResult<DownloadResult> {
+    let attempts = with_retry(state, request, move || async move {
+        let builder = prepare(state, request, &[])
+            .await?
+            .timeout(TRANSFER_TIMEOUT);
+        let response = send(state, builder).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let err_text = response.text().await.unwrap_or_default();
+            return Err(AppError::from_status(status.as_u16(), err_text));
+        }
+        Ok(response)
+    });
+    let response = tokio::select! {
+        response = attempts => response?,
+        _ = cancel.cancelled() => return Err(cancelled(id)),
+    };
+
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| AppError::io(format!("Erro ao criar diretório de download: {}", e)))?;
+    }
+    let part_path = part_path_for(dest, id);
+    let mut file = tokio::fs::File::create(&part_path)
+        .await
+        .map_err(|e| AppError::io(format!("Erro ao criar arquivo de download: {}", e)))?;
+
+    let mut progress = Progress::new(app, id, Direction::Download, response.content_length());
+    let mut stream = response.bytes_stream();
+
+    let outcome: AppResult<()> = async {
+        loop {
+            let chunk = tokio::select! {
+                chunk = next_chunk(&mut stream, CHUNK_IDLE_TIMEOUT) => chunk?,
+                _ = cancel.cancelled() => return Err(cancelled(id)),
+            };
+            let Some(chunk) = chunk else {
+                break;
+            };
+            let bytes = chunk?;
+            file.write_all(&bytes)
+                .await
+                .map_err(|e| AppError::io(format!("Erro ao gravar download: {}", e)))?;
+            progress.advance(bytes.len() as u64);
+        }
+        file.flush()
+            .await
+            .map_err(|e| AppError::io(format!("Erro ao gravar download: {}", e)))?;
+        Ok(())
+    }
+    .await;
+
+    drop(file);
+    if let Err(e) = outcome {
+        let _ = tokio::fs::remove_file(&part_path).await;
+        return Err(e);
+    }
+
+    let saved = match finalize(&part_path, dest).await {
+        Ok(saved) => saved,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(e);
+        }
+    };
+    progress.emit();
+    info!("[Transfer] Download salvo em {}", saved.display());
+
+    Ok(DownloadResult {
+        path: saved.to_string_lossy().to_string(),
+        bytes: progress.transferred,
+    })
+}
+
+/// Próximo pedaço do stream; falha com `timeout` se nada chegar em `idle`
+async fn next_chunk<S>(stream: &mut S, idle: Duration) -> AppResult<Option<S::Item>>
+where
+    S: Stream + Unpin,
+{
+    tokio::time::timeout(idle, stream.next())
+        .await
+        .map_err(|_| {
+            AppError::new(
+                ErrorKind::Timeout,
+                format!("Download parado há mais de {}s", idle.as_secs()),
+            )
+        })
+}
+
+/// `<nome>.<id>.part` ao lado do destino; o id separa downloads simultâneos
+/// para o mesmo arquivo
+fn part_path_for(dest: &Path, id: &str) -> PathBuf {
+    let name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "download".to_string());
+    let id: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dest.with_file_name(format!("{}.{}.part", name, id))
+}
+
+/// `nome (n).ext` no mesmo diretório de `dest`
+fn numbered(dest: &Path, n: u32) -> PathBuf {
+    let stem = dest
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match dest.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()),
+        None => format!("{} ({})", stem, n),
+    };
+    dest.with_file_name(name)
+}
+
+/// Dá ao `.part` o primeiro nome livre entre `dest`, `nome (1).ext`... O hard
+/// link falha se o nome já existe, então nada é sobrescrito mesmo se outro
+/// processo criar o arquivo no meio do caminho.
+async fn finalize(part_path: &Path, dest: &Path) -> AppResult<PathBuf> {
+    for n in 0..1000 {
+        let candidate = if n == 0 {
+            dest.to_path_buf()
+        } else {
+            numbered(dest, n)
+        };
+        match tokio::fs::hard_link(part_path, &candidate).await {
+            Ok(()) => {
+                let _ = tokio::fs::remove_file(part_path).await;
+                return Ok(candidate);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            // Sistema de arquivos sem hard link (FAT, alguns compartilhamentos)
+            Err(_) => {
+                if tokio::fs::try_exists(&candidate).await.unwrap_or(true) {
+                    continue;
+                }
+                tokio::fs::rename(part_path, &candidate)
+                    .await
+                    .map_err(|e| AppError::io(format!("Erro ao finalizar download: {}", e)))?;
+                return Ok(candidate);
+            }
+        }
+    }
+    Err(AppError::io(format!(
+        "Nenhum nome livre para salvar {}",
+        dest.display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_path_includes_name_and_id() {
+        let dest = Path::new("/tmp/pedidos/relatorio.pdf");
+        assert_eq!(
+            part_path_for(dest, "abc-123"),
+            Path::new("/tmp/pedidos/relatorio.pdf.abc-123.part")
+        );
+        assert_eq!(
+            part_path_for(dest, "../x"),
+            Path::new("/tmp/pedidos/relatorio.pdf.___x.part")
+        );
+    }
+
+    #[test]
+    fn test_finalize_never_overwrites() {
+        let dir = std::env::temp_dir().join(format!("sgp-transfer-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dest = dir.join("arte.png");
+        std::fs::write(&dest, b"existente").unwrap();
+
+        let part = part_path_for(&dest, "1");
+        std::fs::write(&part, b"novo").unwrap();
+        let saved = tauri::async_runtime::block_on(finalize(&part, &dest)).unwrap();
+
+        assert_eq!(saved, dir.join("arte (1).png"));
+        assert_eq!(std::fs::read(&dest).unwrap(), b"existente");
+        assert_eq!(std::fs::read(&saved).unwrap(), b"novo");
+        assert!(!part.exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_stalled_download_times_out() {
+        let result = tauri::async_runtime::block_on(async {
+            let mut stalled = futures_util::stream::pending::<u8>();
+            next_chunk(&mut stalled, Duration::from_millis(20)).await
+        });
+        assert_eq!(result.unwrap_err().kind, ErrorKind::Timeout);
+
+        let result = tauri::async_runtime::block_on(async {
+            let mut stream = futures_util::stream::iter([1u8]);
+            next_chunk(&mut stream, Duration::from_millis(20)).await
+        });
+        assert_eq!(result.unwrap(), Some(1));
+    }
+}
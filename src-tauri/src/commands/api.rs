@@ -1,122 +1,233 @@
+use crate::bridge::auth::{self, RefreshConfig};
+use crate::bridge::cache::{CachedResponse, TtlRule};
+use crate::bridge::inflight;
+use crate::bridge::paging::{self, PageEvent, PagedQuery};
+use crate::bridge::policy::{ApiPolicy, PolicyViolation};
+use crate::bridge::retry::RetryPolicy;
+use crate::bridge::{self, ApiRequest};
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::http_client::HttpClientConfig;
 use crate::state::AppState;
 use serde_json::Value;
+use tauri::ipc::Channel;
 use tauri::{command, State};
 use std::collections::HashMap;
+use tracing::{info, warn};
 
+/// Define base URL e token da ponte. Trocar qualquer um dos dois descarta o
+/// papel da sessão; um token informado aqui nunca ganha papel de administrador.
 #[command]
 pub async fn set_api_config(
     base_url: String,
     token: Option<String>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    *state.api_base_url.lock().map_err(|_| "Erro de mutex no base_url".to_string())? = base_url;
-    *state.auth_token.lock().map_err(|_| "Erro de mutex no auth_token".to_string())? = token;
+) -> AppResult<()> {
+    auth::apply_connection(&state, base_url, token, None)?;
     Ok(())
 }
 
+/// Login feito pelo core: o token e o papel da sessão vêm da resposta da API
+#[command]
+pub async fn auth_login(
+    username: String,
+    password: String,
+    state: State<'_, AppState>,
+) -> AppResult<Value> {
+    auth::login(&state, username, password).await
+}
+
+/// Configura o endpoint (e o refresh token, se houver) usados para renovar a sessão após um 401
+#[command]
+pub async fn set_refresh_config(
+    config: RefreshConfig,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    state.session.set_config(config)
+}
+
+/// GET na API. Chamadas idênticas simultâneas compartilham a mesma requisição.
+///
+/// Com `request_id`, a chamada pode ser cancelada por `cancel_request`, e uma
+/// nova chamada com o mesmo id cancela a anterior (a resposta obsoleta nunca chega).
 #[command]
 pub async fn rust_api_get(
     endpoint: String,
     params: Option<HashMap<String, String>>,
+    request_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
-    let base_url = state.api_base_url.lock().unwrap().clone();
-    let token = state.auth_token.lock().unwrap().clone();
-
-    if base_url.is_empty() {
-        return Err("API Base URL não configurada no Rust Core".to_string());
-    }
-
-    let normalized_base = base_url.trim_end_matches('/');
-    let normalized_endpoint = if endpoint.starts_with('/') { &endpoint } else { &format!("/{}", endpoint) };
-    let mut url = format!("{}{}", normalized_base, normalized_endpoint);
-
-    let mut request = state.client.get(&url);
-
-    if let Some(ref p) = params {
-        request = request.query(p);
-    }
-
-    if let Some(t) = token {
-        request = request.header("Authorization", format!("Bearer {}", t));
-    }
-    
-    // Bypass Ngrok limits if available
-    request = request.header("ngrok-skip-browser-warning", "any");
-    request = request.header("Accept", "application/json");
-
-    let response = request.send().await.map_err(|e| format!("Network Erro: {}", e))?;
-    
-    let status = response.status();
-    if !status.is_success() {
-        let err_text = response.text().await.unwrap_or_default();
-        return Err(format!("{} - {}", status.as_u16(), err_text));
-    }
-
-    // Try to parse the json explicitly
-    let json: Value = response.json().await.map_err(|e| format!("Failed to parse JSON: {}", e))?;
+) -> AppResult<Value> {
+    let request = ApiRequest::get(endpoint, params);
+    let response = inflight::cancellable(
+        &state,
+        request_id,
+        inflight::coalesced_get(&state, &request, false),
+    )
+    .await?;
+    Ok(response.data)
+}
 
-    Ok(json)
+/// Igual a `rust_api_get`, mas sem conexão devolve a última resposta em cache
+/// com `stale: true` em vez de falhar
+#[command]
+pub async fn rust_api_get_cached(
+    endpoint: String,
+    params: Option<HashMap<String, String>>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<CachedResponse> {
+    let request = ApiRequest::get(endpoint, params);
+    inflight::cancellable(
+        &state,
+        request_id,
+        inflight::coalesced_get(&state, &request, true),
+    )
+    .await
 }
 
+/// Percorre um endpoint paginado e envia cada página em `on_page` assim que
+/// chega, terminando com um evento `done`. Com `stream_id`, `cancel_request`
+/// interrompe a leitura entre páginas.
+#[command]
+pub async fn rust_api_get_paged(
+    query: PagedQuery,
+    stream_id: Option<String>,
+    on_page: Channel<PageEvent>,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    inflight::cancellable(&state, stream_id, paging::stream(&state, query, &on_page)).await
+}
 
+/// Envia POST/PUT/PATCH/DELETE. `idempotency_key` é gerada se omitida e
+/// reaproveitada nas retentativas, para a API descartar duplicatas.
+///
+/// Se a API estiver inacessível, a mutação vai para a fila offline (a menos que
+/// `queue_offline` seja `false`) e o erro retornado traz o `queue_id`. Se o
+/// recurso já tem itens da sessão na fila, a mutação entra atrás deles (erro
+/// `conflict` com `queue_id`) para não ser aplicada fora de ordem; com
+/// `queue_offline: false`, só é recusada com `conflict`.
+///
+/// Cancelar uma mutação pelo `request_id` só descarta a resposta: o servidor
+/// pode já ter aplicado a alteração.
 #[command]
 pub async fn rust_api_mutate(
     method: String,
     endpoint: String,
     body: Option<Value>,
+    idempotency_key: Option<String>,
+    queue_offline: Option<bool>,
+    request_id: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
-    let base_url = state.api_base_url.lock().unwrap().clone();
-    let token = state.auth_token.lock().unwrap().clone();
+) -> AppResult<Value> {
+    let request = ApiRequest::mutation(&method, endpoint, body, idempotency_key)?;
+    let queue_offline = queue_offline.unwrap_or(true);
+
+    let scope = auth::session_scope(&state);
+    if state.offline_queue.has_queued(&request.endpoint, &scope)? {
+        let mut e = AppError::new(
+            ErrorKind::Conflict,
+            "Há alterações desse recurso aguardando na fila offline",
+        );
+        if queue_offline {
+            let id = state.offline_queue.enqueue(&request, &e, scope)?;
+            info!(
+                "[Bridge] {} {} enfileirado atrás de itens pendentes ({})",
+                request.method, request.endpoint, id
+            );
+            e.queue_id = Some(id);
+        }
+        return Err(e);
+    }
 
-    if base_url.is_empty() {
-        return Err("API Base URL não configurada no Rust Core".to_string());
+    match inflight::cancellable(&state, request_id, bridge::execute(&state, &request)).await {
+        Ok(value) => {
+            state.response_cache.invalidate_resource(&request.endpoint);
+            Ok(value)
+        }
+        Err(mut e) if e.is_unreachable() && queue_offline => {
+            match state.offline_queue.enqueue(&request, &e, scope) {
+                Ok(id) => {
+                    warn!(
+                        "[Bridge] {} {} sem conexão, salvo na fila offline ({})",
+                        request.method, request.endpoint, id
+                    );
+                    e.queue_id = Some(id);
+                }
+                Err(queue_err) => {
+                    warn!("[Bridge] Falha ao salvar na fila offline: {}", queue_err);
+                }
+            }
+            Err(e)
+        }
+        result => result,
     }
+}
 
-    let normalized_base = base_url.trim_end_matches('/');
-    let normalized_endpoint = if endpoint.starts_with('/') { &endpoint } else { &format!("/{}", endpoint) };
-    let url = format!("{}{}", normalized_base, normalized_endpoint);
+/// Cancela a chamada em andamento com esse `request_id`; retorna `false` se
+/// ela já terminou. A chamada cancelada falha com erro `cancelled`.
+#[command]
+pub async fn cancel_request(request_id: String, state: State<'_, AppState>) -> AppResult<bool> {
+    Ok(state.inflight.cancel(&request_id))
+}
 
-    let req_method = match method.to_uppercase().as_str() {
-        "POST" => reqwest::Method::POST,
-        "PUT" => reqwest::Method::PUT,
-        "PATCH" => reqwest::Method::PATCH,
-        "DELETE" => reqwest::Method::DELETE,
-        _ => return Err(format!("Método HTTP não suportado: {}", method)),
-    };
+/// Política de endpoints em vigor (somente leitura; é editada em `api_policy.json`)
+#[command]
+pub async fn get_api_policy(state: State<'_, AppState>) -> AppResult<ApiPolicy> {
+    state.policy.policy()
+}
 
-    let mut request = state.client.request(req_method, &url);
+/// Requisições bloqueadas recentemente pela política, da mais nova para a mais antiga
+#[command]
+pub async fn api_policy_violations(state: State<'_, AppState>) -> AppResult<Vec<PolicyViolation>> {
+    state.policy.violations()
+}
 
-    if let Some(t) = token {
-        request = request.header("Authorization", format!("Bearer {}", t));
-    }
-    
-    // Bypass Ngrok limits if available
-    request = request.header("ngrok-skip-browser-warning", "any");
-    request = request.header("Accept", "application/json");
+/// Atualiza a política de retentativa usada por `rust_api_get`/`rust_api_mutate`
+#[command]
+pub async fn set_retry_policy(policy: RetryPolicy, state: State<'_, AppState>) -> AppResult<()> {
+    *state
+        .retry_policy
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex na política de retry"))? = policy;
+    Ok(())
+}
 
-    if let Some(b) = body {
-        request = request.json(&b);
-    }
+#[command]
+pub async fn get_retry_policy(state: State<'_, AppState>) -> AppResult<RetryPolicy> {
+    Ok(state
+        .retry_policy
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex na política de retry"))?
+        .clone())
+}
 
-    let response = request.send().await.map_err(|e| format!("Network Erro: {}", e))?;
-    
-    let status = response.status();
-    if !status.is_success() {
-        let err_text = response.text().await.unwrap_or_default();
-        return Err(format!("{} - {}", status.as_u16(), err_text));
-    }
+/// Define os TTLs por prefixo de endpoint usados pelo cache de GET
+#[command]
+pub async fn set_cache_ttls(rules: Vec<TtlRule>, state: State<'_, AppState>) -> AppResult<()> {
+    state.response_cache.set_ttl_rules(rules)
+}
 
-    if status.as_u16() == 204 {
-        return Ok(Value::Null);
-    }
+#[command]
+pub async fn get_cache_ttls(state: State<'_, AppState>) -> AppResult<Vec<TtlRule>> {
+    state.response_cache.ttl_rules()
+}
 
-    let text = response.text().await.unwrap_or_default();
-    if text.is_empty() {
-         return Ok(Value::Null);
-    }
-    
-    let json: Value = serde_json::from_str(&text).map_err(|e| format!("Falha no Parse JSON: {}", e))?;
-    Ok(json)
+/// Descarta todas as respostas em cache (memória e disco)
+#[command]
+pub async fn clear_api_cache(state: State<'_, AppState>) -> AppResult<()> {
+    state.response_cache.clear()
+}
+
+/// Configuração atual do cliente HTTP (timeouts, proxy, CA, pins)
+#[command]
+pub async fn get_http_client_config(state: State<'_, AppState>) -> AppResult<HttpClientConfig> {
+    state.http.config()
+}
+
+/// Reconstrói o cliente HTTP com a nova configuração, sem reiniciar o app
+#[command]
+pub async fn set_http_client_config(
+    config: HttpClientConfig,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    state.http.reconfigure(config)
 }
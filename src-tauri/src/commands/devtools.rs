@@ -1,9 +1,10 @@
+use crate::error::{AppError, AppResult};
 use tauri::{AppHandle, Manager};
 use tracing::{info, warn};
 
 /// Abre o DevTools na janela principal
 #[tauri::command]
-pub async fn open_devtools(app_handle: AppHandle) -> Result<(), String> {
+pub async fn open_devtools(app_handle: AppHandle) -> AppResult<()> {
     info!("Tentando abrir DevTools...");
 
     // Obter a janela principal
@@ -13,13 +14,13 @@ pub async fn open_devtools(app_handle: AppHandle) -> Result<(), String> {
         Ok(())
     } else {
         warn!("Janela principal não encontrada");
-        Err("Janela principal não encontrada".to_string())
+        Err(AppError::internal("Janela principal não encontrada"))
     }
 }
 
 /// Fecha o DevTools na janela principal
 #[tauri::command]
-pub async fn close_devtools(app_handle: AppHandle) -> Result<(), String> {
+pub async fn close_devtools(app_handle: AppHandle) -> AppResult<()> {
     info!("Tentando fechar DevTools...");
 
     // Obter a janela principal
@@ -29,13 +30,13 @@ pub async fn close_devtools(app_handle: AppHandle) -> Result<(), String> {
         Ok(())
     } else {
         warn!("Janela principal não encontrada");
-        Err("Janela principal não encontrada".to_string())
+        Err(AppError::internal("Janela principal não encontrada"))
     }
 }
 
 /// Alterna o estado do DevTools (abre se fechado, fecha se aberto)
 #[tauri::command]
-pub async fn toggle_devtools(app_handle: AppHandle) -> Result<(), String> {
+pub async fn toggle_devtools(app_handle: AppHandle) -> AppResult<()> {
     info!("Alternando estado do DevTools...");
 
     // Obter a janela principal
@@ -47,13 +48,13 @@ pub async fn toggle_devtools(app_handle: AppHandle) -> Result<(), String> {
         Ok(())
     } else {
         warn!("Janela principal não encontrada");
-        Err("Janela principal não encontrada".to_string())
+        Err(AppError::internal("Janela principal não encontrada"))
     }
 }
 
 /// Verifica se o DevTools está aberto
 #[tauri::command]
-pub async fn is_devtools_open(app_handle: AppHandle) -> Result<bool, String> {
+pub async fn is_devtools_open(app_handle: AppHandle) -> AppResult<bool> {
     // Obter a janela principal
     if let Some(_window) = app_handle.get_webview_window("main") {
         // Nota: O Tauri não tem um método direto para verificar se DevTools está aberto
@@ -61,13 +62,13 @@ pub async fn is_devtools_open(app_handle: AppHandle) -> Result<bool, String> {
         // Em uma implementação real, você poderia usar eventos ou outras técnicas
         Ok(false)
     } else {
-        Err("Janela principal não encontrada".to_string())
+        Err(AppError::internal("Janela principal não encontrada"))
     }
 }
 
 /// Testa o sistema DevTools
 #[tauri::command]
-pub async fn test_devtools_system(app_handle: AppHandle) -> Result<String, String> {
+pub async fn test_devtools_system(app_handle: AppHandle) -> AppResult<String> {
     info!("Testando sistema DevTools...");
 
     // Obter a janela principal
@@ -80,6 +81,6 @@ pub async fn test_devtools_system(app_handle: AppHandle) -> Result<String, Strin
         info!("✅ DevTools fechado com sucesso no teste");
         Ok("Sistema DevTools testado com sucesso!".to_string())
     } else {
-        Err("Janela principal não encontrada".to_string())
+        Err(AppError::internal("Janela principal não encontrada"))
     }
 }
@@ -0,0 +1,27 @@
+use crate::bridge::diagnostics::{self, DiagnosticsOptions, DiagnosticsReport};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use tauri::{command, AppHandle, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Roda o diagnóstico de rede contra a URL configurada. Com
+/// `copy_to_clipboard`, a versão em texto do relatório vai para a área de
+/// transferência para ser colada no chamado de suporte.
+#[command]
+pub async fn run_network_diagnostics(
+    options: Option<DiagnosticsOptions>,
+    copy_to_clipboard: Option<bool>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<DiagnosticsReport> {
+    let report = diagnostics::run(&state, options.unwrap_or_default()).await?;
+    if copy_to_clipboard.unwrap_or(false) {
+        app.clipboard().write_text(report.to_text()).map_err(|e| {
+            AppError::internal(format!(
+                "Falha ao copiar para a área de transferência: {}",
+                e
+            ))
+        })?;
+    }
+    Ok(report)
+}
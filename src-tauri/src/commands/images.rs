@@ -1,12 +1,16 @@
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::image_processing::{self, ProcessOptions};
+use crate::image_store::{self, CachedEntry, CleanupReport, ImageQuota, ImageUsage, StoredImage};
+use crate::sandbox;
+use crate::state::AppState;
+use crate::thumbnails::{self, ImageVariant};
 use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
-use std::collections::hash_map::DefaultHasher;
 use std::fs;
-use std::hash::{Hash, Hasher};
-use std::path::Path;
-use tauri::{command, AppHandle, Manager};
-use tracing::{debug, error, info};
-use uuid::Uuid;
+use std::path::PathBuf;
+use tauri::{command, AppHandle, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+use tracing::{debug, info};
 
 // Estrutura para armazenar metadados de imagem
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,131 +21,130 @@ pub struct ImageMetadata {
     pub mime_type: String,
     pub uploaded: bool,
     pub server_reference: Option<String>, // Referência retornada pela API
+    /// SHA-256 do conteúdo
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// Referência estável (`sha256:<hash>`), aceita por `get_local_image_path`
+    #[serde(default)]
+    pub reference: Option<String>,
+}
+
+impl From<StoredImage> for ImageMetadata {
+    fn from(stored: StoredImage) -> Self {
+        let file_name = stored
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self {
+            local_path: stored.path.to_string_lossy().to_string(),
+            file_name,
+            file_size: stored.entry.size,
+            mime_type: stored.entry.mime_type,
+            uploaded: false,
+            server_reference: None,
+            reference: Some(image_store::reference_for(&stored.entry.hash)),
+            content_hash: Some(stored.entry.hash),
+        }
+    }
 }
 
 /// Obtém o diretório de imagens do app
-fn get_images_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+pub(crate) fn get_images_dir(app: &AppHandle) -> AppResult<std::path::PathBuf> {
     // Usar a mesma API que manual_updater.rs usa
     let app_data_dir = app
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Erro ao obter diretório de dados do app: {}", e))?;
+        .map_err(|e| AppError::io(format!("Erro ao obter diretório de dados do app: {}", e)))?;
 
     let images_dir = app_data_dir.join("images");
     Ok(images_dir)
 }
 
-/// Salva uma imagem localmente no diretório de dados do app
+/// Agenda as miniaturas de uma imagem recém-salva
+fn queue_thumbnails(state: &AppState, hash: &str, path: &std::path::Path) {
+    if let Ok(root) = state.images.root() {
+        thumbnails::generate_in_background(root, hash.to_string(), path.to_path_buf());
+    }
+}
+
+/// Salva uma imagem localmente no diretório de dados do app.
+/// O arquivo é endereçado pelo SHA-256: salvar a mesma arte de novo
+/// devolve a imagem já existente.
 #[command]
 pub async fn save_image_locally(
-    app: AppHandle,
+    state: State<'_, AppState>,
     image_data: Vec<u8>, // Bytes da imagem (não base64)
     mime_type: String,
-) -> Result<ImageMetadata, String> {
+) -> AppResult<ImageMetadata> {
     info!(
         "Salvando imagem localmente (tamanho: {} bytes, tipo: {})",
         image_data.len(),
         mime_type
     );
 
-    // 1. Criar diretório de imagens locais se não existir
-    let images_dir = get_images_dir(&app)?;
-    fs::create_dir_all(&images_dir)
-        .map_err(|e| format!("Erro ao criar diretório de imagens: {}", e))?;
-
-    // 2. Gerar nome único para o arquivo
-    let file_id = Uuid::new_v4();
-    let extension = match mime_type.as_str() {
-        "image/jpeg" | "image/jpg" => "jpg",
-        "image/png" => "png",
-        "image/webp" => "webp",
-        "image/gif" => "gif",
+    // O conteúdo manda; o tipo informado só vale se o conteúdo não for
+    // reconhecido. O arquivo é guardado como veio, sem conversão.
+    let mime_type = match infer::get(&image_data).map(|t| t.mime_type()) {
+        Some(detected) if image_store::is_supported_mime(detected) => detected.to_string(),
+        _ if image_store::is_supported_mime(&mime_type) => mime_type,
         _ => {
-            // Tentar inferir tipo do conteúdo
-            let inferred = infer::get(&image_data);
-            match inferred {
-                Some(t) => match t.mime_type() {
-                    "image/jpeg" => "jpg",
-                    "image/png" => "png",
-                    "image/webp" => "webp",
-                    "image/gif" => "gif",
-                    _ => "jpg", // fallback
-                },
-                None => "jpg", // fallback
-            }
+            return Err(AppError::validation(format!(
+                "Formato de arquivo não suportado: {}",
+                mime_type
+            )))
         }
     };
-    let file_name = format!("{}.{}", file_id, extension);
-    let file_path = images_dir.join(&file_name);
-
-    // 3. Salvar arquivo localmente
-    fs::write(&file_path, image_data).map_err(|e| format!("Erro ao salvar imagem: {}", e))?;
 
-    let file_size = fs::metadata(&file_path)
-        .map_err(|e| format!("Erro ao obter metadados do arquivo: {}", e))?
-        .len();
-
-    info!("Imagem salva localmente: {}", file_path.display());
+    let stored = state.images.put(&image_data, &mime_type)?;
+    queue_thumbnails(&state, &stored.entry.hash, &stored.path);
+    if stored.deduplicated {
+        info!("Imagem já existia no armazenamento: {}", stored.entry.hash);
+    } else {
+        info!("Imagem salva localmente: {}", stored.path.display());
+    }
 
-    // 4. Retornar metadados
-    Ok(ImageMetadata {
-        local_path: file_path.to_string_lossy().to_string(),
-        file_name: file_name.clone(),
-        file_size,
-        mime_type,
-        uploaded: false,
-        server_reference: None,
-    })
+    Ok(stored.into())
 }
 
-/// Obtém o caminho local de uma imagem (cache ou caminho direto)
+/// Obtém o caminho local de uma imagem (caminho direto ou qualquer
+/// referência conhecida pelo índice de imagens)
 #[command]
 pub async fn get_local_image_path(
     app: AppHandle,
-    image_reference: String, // Pode ser caminho local ou referência do servidor
-) -> Result<Option<String>, String> {
-    // 1. Se for caminho local e existir, retornar diretamente
-    if Path::new(&image_reference).exists() {
+    state: State<'_, AppState>,
+    image_reference: String, // Caminho local, `sha256:<hash>`, URL ou referência do servidor
+) -> AppResult<Option<String>> {
+    // 1. Caminho local dentro do diretório de imagens: retornar diretamente
+    let images_dir = get_images_dir(&app)?;
+    if sandbox::authorize(&images_dir, &image_reference, &state.file_grants, None).is_ok() {
         info!("Imagem encontrada no caminho local: {}", image_reference);
         return Ok(Some(image_reference));
     }
 
-    // 2. Verificar cache local por referência do servidor
-    let images_dir = get_images_dir(&app)?;
-
-    // Tentar encontrar arquivo com nome baseado na referência
-    // A referência pode ser um hash ou nome de arquivo
-    let cache_file = images_dir.join(format!("{}.cache", image_reference));
-
-    if cache_file.exists() {
-        match fs::read_to_string(&cache_file) {
-            Ok(cached_path) => {
-                if Path::new(&cached_path).exists() {
-                    info!("Imagem encontrada no cache: {}", cached_path);
-                    return Ok(Some(cached_path));
-                } else {
-                    // Cache inválido, remover
-                    let _ = fs::remove_file(&cache_file);
-                }
-            }
-            Err(e) => {
-                error!("Erro ao ler cache: {}", e);
-            }
+    // 2. Consultar o índice (hash, URL, referência do servidor, nome de arquivo)
+    match state.images.resolve(&image_reference)? {
+        Some(path) => {
+            debug!("Imagem resolvida pelo índice: {}", path.display());
+            Ok(Some(path.to_string_lossy().to_string()))
         }
-    }
-
-    // 3. Tentar encontrar arquivo diretamente no diretório de imagens
-    // Se a referência for um nome de arquivo simples
-    if let Some(file_name) = Path::new(&image_reference).file_name() {
-        let potential_path = images_dir.join(file_name);
-        if potential_path.exists() {
-            info!("Imagem encontrada por nome: {}", potential_path.display());
-            return Ok(Some(potential_path.to_string_lossy().to_string()));
+        None => {
+            info!("Imagem não encontrada localmente: {}", image_reference);
+            Ok(None)
         }
     }
+}
 
-    info!("Imagem não encontrada localmente: {}", image_reference);
-    Ok(None)
+/// Valida o caminho pedido pelo WebView: só o diretório de imagens ou o
+/// arquivo liberado pelo `grant_token` de [`pick_image_file`]
+fn authorize_read(
+    app: &AppHandle,
+    state: &AppState,
+    local_path: &str,
+    grant_token: Option<&str>,
+) -> AppResult<PathBuf> {
+    let images_dir = get_images_dir(app)?;
+    sandbox::authorize(&images_dir, local_path, &state.file_grants, grant_token)
 }
 
 /// Carrega imagem local como base64 (apenas para preview/impressão)
@@ -149,19 +152,15 @@ pub async fn get_local_image_path(
 #[command]
 pub async fn load_local_image_as_base64(
     app: AppHandle,
+    state: State<'_, AppState>,
     local_path: String,
-) -> Result<String, String> {
-    // Verificar se o caminho está dentro do diretório permitido
-    let images_dir = get_images_dir(&app)?;
-    let path = Path::new(&local_path);
-
-    // Verificar segurança: caminho deve estar dentro do diretório de imagens
-    if !path.starts_with(&images_dir) && !path.exists() {
-        return Err("Caminho de imagem inválido ou não autorizado".to_string());
-    }
+    grant_token: Option<String>,
+) -> AppResult<String> {
+    let path = authorize_read(&app, &state, &local_path, grant_token.as_deref())?;
 
     // Carregar imagem do disco
-    let image_data = fs::read(&local_path).map_err(|e| format!("Erro ao ler imagem: {}", e))?;
+    let image_data =
+        fs::read(&path).map_err(|e| AppError::io(format!("Erro ao ler imagem: {}", e)))?;
 
     // Detectar mime type
     let mime_type = infer::get(&image_data)
@@ -182,17 +181,16 @@ pub async fn load_local_image_as_base64(
 
 /// Lê arquivo de imagem como array de bytes
 #[command]
-pub async fn read_image_file(app: AppHandle, local_path: String) -> Result<Vec<u8>, String> {
-    let images_dir = get_images_dir(&app)?;
-    let path = Path::new(&local_path);
+pub async fn read_image_file(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    local_path: String,
+    grant_token: Option<String>,
+) -> AppResult<Vec<u8>> {
+    let path = authorize_read(&app, &state, &local_path, grant_token.as_deref())?;
 
-    // Verificar segurança
-    if !path.starts_with(&images_dir) && !path.exists() {
-        return Err("Caminho de imagem inválido ou não autorizado".to_string());
-    }
-
-    let image_data =
-        fs::read(&local_path).map_err(|e| format!("Erro ao ler arquivo de imagem: {}", e))?;
+    let image_data = fs::read(&path)
+        .map_err(|e| AppError::io(format!("Erro ao ler arquivo de imagem: {}", e)))?;
 
     info!(
         "Arquivo de imagem lido: {} (tamanho: {} bytes)",
@@ -202,78 +200,115 @@ pub async fn read_image_file(app: AppHandle, local_path: String) -> Result<Vec<u
     Ok(image_data)
 }
 
+/// Arquivo escolhido pelo usuário no diálogo nativo
+#[derive(Debug, Clone, Serialize)]
+pub struct PickedFile {
+    pub path: String,
+    pub file_name: String,
+    pub file_size: u64,
+    /// Libera a leitura deste arquivo em `read_image_file`/`load_local_image_as_base64`
+    pub grant_token: String,
+}
+
+/// Abre o seletor de arquivos nativo e libera a leitura do arquivo escolhido.
+/// `None` se o usuário cancelar.
+#[command]
+pub async fn pick_image_file(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<Option<PickedFile>> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter(
+            "Imagens e PDF",
+            &[
+                "png", "jpg", "jpeg", "webp", "gif", "tif", "tiff", "bmp", "pdf",
+            ],
+        )
+        .pick_file(move |file| {
+            let _ = tx.send(file);
+        });
+    let Some(file) = rx
+        .await
+        .map_err(|_| AppError::internal("Diálogo de arquivo fechado sem resposta"))?
+    else {
+        return Ok(None);
+    };
+    let path = file
+        .into_path()
+        .map_err(|e| AppError::validation(format!("Arquivo inválido: {}", e)))?;
+
+    let grant_token = state.file_grants.grant(&path)?;
+    let file_size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    info!("Arquivo liberado pelo usuário: {}", path.display());
+    Ok(Some(PickedFile {
+        file_name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        file_size,
+        grant_token,
+    }))
+}
+
+/// Revoga a liberação de um arquivo escolhido no diálogo
+#[command]
+pub async fn revoke_file_grant(state: State<'_, AppState>, grant_token: String) -> AppResult<bool> {
+    Ok(state.file_grants.revoke(&grant_token))
+}
+
 /// Cacheia uma imagem baixada da URL no diretório local
 #[command]
 pub async fn cache_image_from_url(
-    app: AppHandle,
+    state: State<'_, AppState>,
     image_url: String,
     image_data: Vec<u8>,
-) -> Result<ImageMetadata, String> {
+) -> AppResult<ImageMetadata> {
     debug!(
         "Cacheando imagem da URL: {} (tamanho: {} bytes)",
         image_url,
         image_data.len()
     );
 
-    // Gerar hash da URL para usar como identificador
-    let mut hasher = DefaultHasher::new();
-    image_url.hash(&mut hasher);
-    let url_hash = format!("{:016x}", hasher.finish());
-
-    let images_dir = get_images_dir(&app)?;
-    fs::create_dir_all(&images_dir)
-        .map_err(|e| format!("Erro ao criar diretório de imagens: {}", e))?;
-
     // Detectar tipo da imagem
     let mime_type = infer::get(&image_data)
         .map(|t| t.mime_type())
         .unwrap_or("image/jpeg");
 
-    let extension = match mime_type {
-        "image/jpeg" | "image/jpg" => "jpg",
-        "image/png" => "png",
-        "image/webp" => "webp",
-        "image/gif" => "gif",
-        _ => "jpg",
-    };
-
-    let file_name = format!("cached_{}.{}", url_hash, extension);
-    let file_path = images_dir.join(&file_name);
-
-    // Salvar arquivo
-    fs::write(&file_path, &image_data)
-        .map_err(|e| format!("Erro ao salvar imagem em cache: {}", e))?;
-
-    // Criar arquivo de cache com referência
-    let cache_file = images_dir.join(format!("{}.cache", image_url));
-    fs::write(&cache_file, file_path.to_string_lossy().as_ref())
-        .map_err(|e| format!("Erro ao criar arquivo de cache: {}", e))?;
-
-    let file_size = fs::metadata(&file_path)
-        .map_err(|e| format!("Erro ao obter metadados: {}", e))?
-        .len();
+    let (entry, file_path) = state
+        .images
+        .put_cached(&image_url, &image_data, mime_type)?;
 
+    queue_thumbnails(&state, &entry.hash, &file_path);
     debug!("Imagem cacheada: {}", file_path.display());
 
     Ok(ImageMetadata {
         local_path: file_path.to_string_lossy().to_string(),
-        file_name,
-        file_size,
-        mime_type: mime_type.to_string(),
+        file_name: entry.file_name,
+        file_size: entry.size,
+        mime_type: entry.mime_type,
         uploaded: false,
         server_reference: Some(image_url),
+        reference: Some(image_store::reference_for(&entry.hash)),
+        content_hash: Some(entry.hash),
     })
 }
 
-/// Processa e salva uma imagem (redimensiona se necessário)
+/// Processa e salva uma imagem: gira conforme o EXIF, reduz só se passar
+/// das dimensões máximas e mantém PNG quando há transparência. Sem mudanças
+/// a fazer, os bytes originais são guardados como vieram, a menos que
+/// `strip_metadata` peça a remoção do EXIF.
 #[command]
 pub async fn process_and_save_image(
-    app: AppHandle,
+    state: State<'_, AppState>,
     image_data: Vec<u8>,
     max_width: Option<u32>,
     max_height: Option<u32>,
     quality: Option<u8>,
-) -> Result<ImageMetadata, String> {
+    strip_metadata: Option<bool>,
+) -> AppResult<ImageMetadata> {
     info!(
         "Processando imagem (tamanho: {} bytes, max: {:?}x{:?}, qualidade: {:?})",
         image_data.len(),
@@ -282,43 +317,174 @@ pub async fn process_and_save_image(
         quality
     );
 
-    // 1. Carregar imagem
-    let img = image::load_from_memory(&image_data)
-        .map_err(|e| format!("Erro ao carregar imagem: {}", e))?;
-
-    // 2. Redimensionar
-    let processed = if let (Some(w), Some(h)) = (max_width, max_height) {
-        info!("Redimensionando imagem para {}x{}", w, h);
-        img.resize(w, h, image::imageops::FilterType::Lanczos3)
-    } else {
-        img
+    let options = ProcessOptions {
+        max_width,
+        max_height,
+        quality: quality.unwrap_or(image_processing::DEFAULT_QUALITY),
+        strip_metadata: strip_metadata.unwrap_or(false),
     };
+    let processed = tauri::async_runtime::spawn_blocking(move || {
+        image_processing::process(&image_data, &options)
+    })
+    .await
+    .map_err(|e| AppError::internal(format!("Erro ao processar imagem: {}", e)))??;
 
-    // 3. Salvar processada
-    let file_id = Uuid::new_v4();
-    let images_dir = get_images_dir(&app)?;
-    fs::create_dir_all(&images_dir)
-        .map_err(|e| format!("Erro ao criar diretório de imagens: {}", e))?;
+    let stored = state.images.put(&processed.data, processed.mime_type)?;
+    queue_thumbnails(&state, &stored.entry.hash, &stored.path);
 
-    let file_path = images_dir.join(format!("{}.jpg", file_id));
+    info!(
+        "Imagem processada e salva: {} ({}x{}, {}, {})",
+        stored.path.display(),
+        processed.width,
+        processed.height,
+        processed.mime_type,
+        if processed.reencoded {
+            "regravada"
+        } else {
+            "original"
+        }
+    );
 
-    // Salvar como JPEG
-    processed
-        .save_with_format(&file_path, image::ImageFormat::Jpeg)
-        .map_err(|e| format!("Erro ao salvar imagem processada: {}", e))?;
+    Ok(stored.into())
+}
 
-    let file_size = fs::metadata(&file_path)
-        .map_err(|e| format!("Erro ao obter metadados: {}", e))?
-        .len();
+/// Hash da imagem a partir de qualquer referência conhecida pelo índice:
+/// `sha256:<hash>`, referência do servidor ou caminho do arquivo local
+fn resolve_hash(state: &AppState, reference: &str) -> AppResult<String> {
+    if let Some(hash) = image_store::parse_reference(reference) {
+        return Ok(hash);
+    }
+    state
+        .images
+        .resolve_entry(reference)?
+        .map(|(hash, _)| hash)
+        .ok_or_else(|| AppError::validation(format!("Imagem {} não encontrada", reference)))
+}
 
-    info!("Imagem processada e salva: {}", file_path.display());
+/// Registra que um item de pedido usa a imagem (`order_item` no formato
+/// `pedido:<id>:item:<id>`). Devolve o total de referências.
+#[command]
+pub async fn image_add_reference(
+    state: State<'_, AppState>,
+    reference: String,
+    order_item: String,
+) -> AppResult<usize> {
+    let hash = resolve_hash(&state, &reference)?;
+    state.images.add_ref(&hash, &order_item)
+}
 
-    Ok(ImageMetadata {
-        local_path: file_path.to_string_lossy().to_string(),
-        file_name: format!("{}.jpg", file_id),
-        file_size,
-        mime_type: "image/jpeg".to_string(),
-        uploaded: false,
-        server_reference: None,
+/// Solta a referência do item. A imagem sem itens fica no disco até a
+/// limpeza de órfãs, depois do prazo da cota.
+#[command]
+pub async fn image_release_reference(
+    state: State<'_, AppState>,
+    reference: String,
+    order_item: String,
+) -> AppResult<usize> {
+    let hash = resolve_hash(&state, &reference)?;
+    state.images.release(&hash, &order_item)
+}
+
+/// Registra a referência devolvida pela API no upload, para que ela também
+/// resolva para o arquivo local
+#[command]
+pub async fn image_link_server_reference(
+    state: State<'_, AppState>,
+    reference: String,
+    server_reference: String,
+) -> AppResult<()> {
+    let hash = resolve_hash(&state, &reference)?;
+    state.images.link_server_reference(&hash, &server_reference)
+}
+
+/// Itens de pedido que usam a imagem
+#[command]
+pub async fn image_references(
+    state: State<'_, AppState>,
+    reference: String,
+) -> AppResult<Vec<String>> {
+    let hash = resolve_hash(&state, &reference)?;
+    Ok(state
+        .images
+        .entry(&hash)?
+        .map(|entry| entry.refs.into_iter().collect())
+        .unwrap_or_default())
+}
+
+/// Corpos das mutações na fila offline; imagens citadas neles ainda vão
+/// ser enviadas e não contam como órfãs
+fn pending_upload_bodies(state: &AppState) -> AppResult<Vec<String>> {
+    Ok(state
+        .offline_queue
+        .list()?
+        .into_iter()
+        .filter_map(|m| m.body.map(|b| b.to_string()))
+        .collect())
+}
+
+/// Espaço ocupado por conteúdo, downloads em cache e órfãs
+#[command]
+pub async fn image_storage_usage(state: State<'_, AppState>) -> AppResult<ImageUsage> {
+    let pending = pending_upload_bodies(&state)?;
+    state.images.usage(&pending)
+}
+
+/// Aplica a cota do cache e lista as órfãs; com `remove_orphans` também as apaga
+#[command]
+pub async fn image_storage_cleanup(
+    state: State<'_, AppState>,
+    remove_orphans: Option<bool>,
+) -> AppResult<CleanupReport> {
+    let pending = pending_upload_bodies(&state)?;
+    state
+        .images
+        .cleanup(remove_orphans.unwrap_or(false), &pending)
+}
+
+#[command]
+pub async fn image_quota_get(state: State<'_, AppState>) -> AppResult<ImageQuota> {
+    state.images.quota()
+}
+
+/// Altera a cota; devolve os downloads removidos para caber nela
+#[command]
+pub async fn image_quota_set(
+    state: State<'_, AppState>,
+    quota: ImageQuota,
+) -> AppResult<Vec<CachedEntry>> {
+    state.images.set_quota(quota)
+}
+
+/// Melhor variante para exibir a imagem com o maior lado em `size` px:
+/// a menor miniatura que cubra o tamanho, ou o original. Com
+/// `as_data_url` a imagem vem embutida, pronta para um `<img>`.
+#[command]
+pub async fn image_best_variant(
+    state: State<'_, AppState>,
+    reference: String,
+    size: u32,
+    as_data_url: Option<bool>,
+) -> AppResult<ImageVariant> {
+    let (hash, source) = state
+        .images
+        .resolve_entry(&reference)?
+        .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Imagem não encontrada localmente"))?;
+    let root = state.images.root()?;
+
+    let mut variant = tauri::async_runtime::spawn_blocking(move || {
+        thumbnails::best_variant(&root, &hash, &source, size)
     })
+    .await
+    .map_err(|e| AppError::internal(format!("Falha na tarefa de miniatura: {}", e)))??;
+
+    if as_data_url.unwrap_or(false) {
+        let data = fs::read(&variant.path)
+            .map_err(|e| AppError::io(format!("Erro ao ler imagem: {}", e)))?;
+        variant.data_url = Some(format!(
+            "data:{};base64,{}",
+            variant.mime_type,
+            general_purpose::STANDARD.encode(&data)
+        ));
+    }
+    Ok(variant)
 }
@@ -0,0 +1,43 @@
+use crate::bridge::inspector::{ExportFormat, InspectorConfig, NetworkEntry};
+use crate::error::AppResult;
+use crate::state::AppState;
+use serde_json::Value;
+use tauri::{command, State};
+
+#[command]
+pub async fn network_inspector_config(state: State<'_, AppState>) -> AppResult<InspectorConfig> {
+    state.inspector.config()
+}
+
+/// Liga/desliga o registro de chamadas e ajusta o tamanho do buffer.
+/// Desligar descarta o que já foi registrado.
+#[command]
+pub async fn network_inspector_configure(
+    config: InspectorConfig,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    state.inspector.configure(config)
+}
+
+/// Últimas chamadas registradas, da mais recente para a mais antiga
+#[command]
+pub async fn network_inspector_entries(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<NetworkEntry>> {
+    state.inspector.entries(limit)
+}
+
+#[command]
+pub async fn network_inspector_clear(state: State<'_, AppState>) -> AppResult<()> {
+    state.inspector.clear()
+}
+
+/// Exporta o buffer como lista JSON (`json`) ou arquivo HAR 1.2 (`har`)
+#[command]
+pub async fn network_inspector_export(
+    format: ExportFormat,
+    state: State<'_, AppState>,
+) -> AppResult<Value> {
+    state.inspector.export(format)
+}
@@ -1,6 +1,15 @@
 pub mod devtools;
+pub mod diagnostics;
 pub mod images;
+pub mod inspector;
 pub mod pdf;
+pub mod pedidos;
+pub mod presence;
+pub mod profiles;
+pub mod queue;
+pub mod realtime;
+pub mod transfer;
 pub mod update;
+pub mod vault;
 
 pub mod api;
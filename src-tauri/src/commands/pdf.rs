@@ -1,3 +1,4 @@
+use crate::error::{AppError, AppResult};
 use crate::pdf_generator::PdfGenerator;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
@@ -39,7 +40,7 @@ struct PageGroup {
 pub async fn generate_production_pdf(
     app: AppHandle,
     request: PdfGenerationRequest,
-) -> Result<String, String> {
+) -> AppResult<String> {
     // 1. Group items into pages of 2
     let pages: Vec<PageGroup> = request
         .items
@@ -50,33 +51,34 @@ pub async fn generate_production_pdf(
         .collect();
 
     // 2. Create PDF generator
-    let generator =
-        PdfGenerator::new().map_err(|e| format!("Failed to initialize PDF generator: {}", e))?;
+    let generator = PdfGenerator::new()
+        .map_err(|e| AppError::internal(format!("Failed to initialize PDF generator: {}", e)))?;
 
     // 3. Render template with grouped data
     let html = render_template(&request.template_html, &pages)
-        .map_err(|e| format!("Failed to render template: {}", e))?;
+        .map_err(|e| AppError::validation(format!("Failed to render template: {}", e)))?;
 
     // 4. Generate PDF
     let pdf_bytes = generator
         .generate_from_html(&html)
-        .map_err(|e| format!("Failed to generate PDF: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Failed to generate PDF: {}", e)))?;
 
     // 5. Save to file
     let app_data_dir = app
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        .map_err(|e| AppError::io(format!("Failed to get app data dir: {}", e)))?;
 
     let pdf_dir = app_data_dir.join("pdfs");
     std::fs::create_dir_all(&pdf_dir)
-        .map_err(|e| format!("Failed to create PDF directory: {}", e))?;
+        .map_err(|e| AppError::io(format!("Failed to create PDF directory: {}", e)))?;
 
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
     let filename = format!("producao_{}.pdf", timestamp);
     let pdf_path = pdf_dir.join(&filename);
 
-    std::fs::write(&pdf_path, pdf_bytes).map_err(|e| format!("Failed to write PDF file: {}", e))?;
+    std::fs::write(&pdf_path, pdf_bytes)
+        .map_err(|e| AppError::io(format!("Failed to write PDF file: {}", e)))?;
 
     Ok(pdf_path.to_string_lossy().to_string())
 }
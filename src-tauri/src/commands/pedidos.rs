@@ -0,0 +1,83 @@
+use crate::error::AppResult;
+use crate::pedidos::{
+    self, Paginacao, Pedido, PedidoFiltro, PedidoPayload, PedidosPage, StatusChange,
+};
+use crate::state::AppState;
+use tauri::{command, State};
+
+/// Lista pedidos com filtros e paginação (`page` começa em 1)
+#[command]
+pub async fn pedidos_list(
+    filtro: Option<PedidoFiltro>,
+    pagina: Option<Paginacao>,
+    state: State<'_, AppState>,
+) -> AppResult<PedidosPage> {
+    pedidos::list(
+        &state,
+        &filtro.unwrap_or_default(),
+        pagina.unwrap_or_default(),
+    )
+    .await
+}
+
+#[command]
+pub async fn pedido_get(id: i64, state: State<'_, AppState>) -> AppResult<Pedido> {
+    pedidos::get_by_id(&state, id).await
+}
+
+#[command]
+pub async fn pedido_create(
+    payload: PedidoPayload,
+    idempotency_key: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<Pedido> {
+    pedidos::create(&state, &payload, idempotency_key).await
+}
+
+/// Edição parcial: só os campos presentes em `payload` são enviados
+#[command]
+pub async fn pedido_update(
+    id: i64,
+    payload: PedidoPayload,
+    state: State<'_, AppState>,
+) -> AppResult<Pedido> {
+    pedidos::update(&state, id, &payload).await
+}
+
+#[command]
+pub async fn pedido_change_status(
+    id: i64,
+    change: StatusChange,
+    state: State<'_, AppState>,
+) -> AppResult<Pedido> {
+    pedidos::change_status(&state, id, &change).await
+}
+
+/// Salva um rascunho novo, sem as validações de pedido completo
+#[command]
+pub async fn pedido_draft_save(
+    payload: PedidoPayload,
+    idempotency_key: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<Pedido> {
+    pedidos::save_draft(&state, &payload, idempotency_key).await
+}
+
+#[command]
+pub async fn pedido_draft_update(
+    id: i64,
+    payload: PedidoPayload,
+    state: State<'_, AppState>,
+) -> AppResult<Pedido> {
+    pedidos::update_draft(&state, id, &payload).await
+}
+
+#[command]
+pub async fn pedidos_drafts_list(state: State<'_, AppState>) -> AppResult<Vec<Pedido>> {
+    pedidos::list_drafts(&state).await
+}
+
+#[command]
+pub async fn pedido_draft_promote(id: i64, state: State<'_, AppState>) -> AppResult<Pedido> {
+    pedidos::promote_draft(&state, id).await
+}
@@ -0,0 +1,27 @@
+use crate::bridge::presence::{self, PresenceConfig, PresenceStatus};
+use crate::error::AppResult;
+use crate::state::AppState;
+use tauri::{command, State};
+
+/// Envia um heartbeat imediato e devolve o estado de conectividade atualizado
+#[command]
+pub async fn send_heartbeat(
+    client_id: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<PresenceStatus> {
+    presence::heartbeat(&state, client_id).await
+}
+
+#[command]
+pub async fn presence_status(state: State<'_, AppState>) -> AppResult<PresenceStatus> {
+    state.presence.status()
+}
+
+/// Altera endpoint/intervalo do heartbeat; vale a partir do próximo ciclo
+#[command]
+pub async fn presence_configure(
+    config: PresenceConfig,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    state.presence.configure(config)
+}
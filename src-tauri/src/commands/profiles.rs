@@ -0,0 +1,137 @@
+use crate::bridge::{auth, build_url};
+use crate::error::{AppError, AppResult};
+use crate::profiles::{ApiProfile, ProfileSummary};
+use crate::state::AppState;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{command, State};
+use tracing::{info, warn};
+
+/// Resultado da sonda `/health` de um perfil
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileHealth {
+    pub ok: bool,
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Aplica o perfil à ponte: URL, token, headers extras e timeout
+pub(crate) fn apply_profile(state: &AppState, profile: &ApiProfile) -> AppResult<()> {
+    auth::apply_connection(state, profile.base_url.clone(), profile.token.clone(), None)?;
+    *state
+        .extra_headers
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex nos headers extras"))? =
+        profile.headers.clone();
+    *state
+        .request_timeout
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex no timeout"))? =
+        profile.timeout_secs.map(Duration::from_secs);
+
+    // Respostas em cache pertencem ao servidor anterior
+    if let Err(e) = state.response_cache.clear() {
+        warn!(
+            "[Profiles] Falha ao limpar cache ao trocar de perfil: {}",
+            e
+        );
+    }
+    Ok(())
+}
+
+#[command]
+pub async fn profiles_list(state: State<'_, AppState>) -> AppResult<Vec<ProfileSummary>> {
+    state.profiles.list()
+}
+
+/// Cria ou atualiza um perfil. Sem `id`, um novo é gerado. Sem `token`, o token
+/// já salvo no perfil é mantido.
+#[command]
+pub async fn profile_save(
+    id: Option<String>,
+    name: String,
+    base_url: String,
+    token: Option<String>,
+    timeout_secs: Option<u64>,
+    headers: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+) -> AppResult<String> {
+    let id = id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let token = match token {
+        Some(t) => Some(t),
+        None => state.profiles.get(&id).ok().and_then(|p| p.token),
+    };
+    let profile = ApiProfile {
+        id: id.clone(),
+        name,
+        base_url,
+        token,
+        timeout_secs,
+        headers: headers.unwrap_or_default(),
+    };
+    state.profiles.save(profile.clone())?;
+
+    // Editar o perfil ativo tem efeito imediato
+    if state
+        .profiles
+        .active()?
+        .is_some_and(|active| active.id == id)
+    {
+        apply_profile(&state, &profile)?;
+    }
+    Ok(id)
+}
+
+#[command]
+pub async fn profile_activate(id: String, state: State<'_, AppState>) -> AppResult<()> {
+    let profile = state.profiles.set_active(&id)?;
+    apply_profile(&state, &profile)?;
+    info!(
+        "[Profiles] Perfil ativo: {} ({})",
+        profile.name, profile.base_url
+    );
+    Ok(())
+}
+
+#[command]
+pub async fn profile_delete(id: String, state: State<'_, AppState>) -> AppResult<bool> {
+    state.profiles.delete(&id)
+}
+
+/// Faz um GET em `/health` do servidor do perfil, sem alterar o perfil ativo
+#[command]
+pub async fn profile_health(id: String, state: State<'_, AppState>) -> AppResult<ProfileHealth> {
+    let profile = state.profiles.get(&id)?;
+    let url = build_url(&profile.base_url, "/health");
+
+    let mut request = state
+        .http
+        .client()?
+        .get(&url)
+        .timeout(Duration::from_secs(profile.timeout_secs.unwrap_or(5)))
+        .header("ngrok-skip-browser-warning", "any");
+    for (name, value) in &profile.headers {
+        request = request.header(name, value);
+    }
+
+    let started = Instant::now();
+    let result = request.send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    Ok(match result {
+        Ok(response) => ProfileHealth {
+            ok: response.status().is_success(),
+            status: Some(response.status().as_u16()),
+            latency_ms,
+            error: None,
+        },
+        Err(e) => ProfileHealth {
+            ok: false,
+            status: None,
+            latency_ms,
+            error: Some(AppError::from(e).message),
+        },
+    })
+}
@@ -0,0 +1,28 @@
+use crate::bridge::queue::{self, QueueStatus, QueuedMutation};
+use crate::error::AppResult;
+use crate::state::AppState;
+use tauri::{command, AppHandle, State};
+
+/// Resumo da fila offline (pendentes, conflitos, último sync)
+#[command]
+pub async fn offline_queue_status(state: State<'_, AppState>) -> AppResult<QueueStatus> {
+    state.offline_queue.status()
+}
+
+/// Lista as mutações guardadas na fila offline, na ordem de reenvio
+#[command]
+pub async fn offline_queue_list(state: State<'_, AppState>) -> AppResult<Vec<QueuedMutation>> {
+    state.offline_queue.list()
+}
+
+/// Remove uma mutação da fila (ex: conflito que o operador decidiu descartar)
+#[command]
+pub async fn offline_queue_discard(id: String, state: State<'_, AppState>) -> AppResult<bool> {
+    state.offline_queue.discard(&id)
+}
+
+/// Força o reenvio imediato da fila, sem esperar o próximo ciclo automático
+#[command]
+pub async fn offline_queue_replay(app: AppHandle) -> AppResult<usize> {
+    queue::replay(&app).await
+}
@@ -0,0 +1,50 @@
+use crate::bridge::realtime::RealtimeStatus;
+use crate::error::AppResult;
+use crate::state::AppState;
+use serde_json::Value;
+use tauri::{command, State, Window};
+
+/// Liga a conexão WebSocket gerenciada pelo core (idempotente)
+#[command]
+pub async fn realtime_connect(state: State<'_, AppState>) -> AppResult<()> {
+    state.realtime.connect();
+    Ok(())
+}
+
+#[command]
+pub async fn realtime_disconnect(state: State<'_, AppState>) -> AppResult<()> {
+    state.realtime.disconnect();
+    Ok(())
+}
+
+#[command]
+pub async fn realtime_status(state: State<'_, AppState>) -> AppResult<RealtimeStatus> {
+    state.realtime.status()
+}
+
+/// Passa a entregar a esta janela as mensagens cujo `type` casa com `topics`
+/// (`order_created`, `order_*`, `*`). Repetir a assinatura não duplica nada.
+#[command]
+pub async fn realtime_subscribe(
+    topics: Vec<String>,
+    window: Window,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    state.realtime.subscribe(window.label(), topics)
+}
+
+/// Remove os tópicos informados, ou todas as assinaturas da janela
+#[command]
+pub async fn realtime_unsubscribe(
+    topics: Option<Vec<String>>,
+    window: Window,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    state.realtime.unsubscribe(window.label(), topics)
+}
+
+/// Envia uma mensagem (ex: broadcast de pedido alterado) aos outros clientes
+#[command]
+pub async fn realtime_send(message: Value, state: State<'_, AppState>) -> AppResult<()> {
+    state.realtime.send(&message)
+}
@@ -0,0 +1,83 @@
+use crate::bridge::transfer::{self, DownloadResult};
+use crate::commands::images::get_images_dir;
+use crate::error::{AppError, AppResult};
+use crate::sandbox;
+use crate::state::AppState;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{command, AppHandle, Manager, State};
+
+/// Envia um arquivo do diretório de imagens como multipart, em streaming.
+/// O progresso sai no evento `transfer-progress` com o `transfer_id`.
+#[command]
+pub async fn rust_api_upload(
+    app: AppHandle,
+    endpoint: String,
+    local_path: String,
+    field_name: Option<String>,
+    fields: Option<HashMap<String, String>>,
+    transfer_id: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<Value> {
+    // Só arquivos dentro do diretório de imagens podem ser enviados
+    let path = sandbox::authorize(
+        &get_images_dir(&app)?,
+        &local_path,
+        &state.file_grants,
+        None,
+    )?;
+
+    let id = transfer_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    transfer::upload(
+        &app,
+        &state,
+        &id,
+        endpoint,
+        &path,
+        field_name.as_deref().unwrap_or("file"),
+        fields.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Baixa um arquivo da API direto para o disco, sem passar pelo IPC.
+/// O destino é `<app_data>/downloads/<file_name>` ou, com
+/// `to_user_downloads`, a pasta Downloads do usuário. Se o arquivo já
+/// existir, o download ganha `nome (1).ext`; o caminho final vem no resultado.
+#[command]
+pub async fn rust_api_download(
+    app: AppHandle,
+    endpoint: String,
+    params: Option<HashMap<String, String>>,
+    file_name: String,
+    to_user_downloads: Option<bool>,
+    transfer_id: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<DownloadResult> {
+    // Descarta qualquer diretório vindo do frontend: só o nome do arquivo é usado
+    let safe_name = Path::new(&file_name)
+        .file_name()
+        .ok_or_else(|| AppError::validation("Nome de arquivo inválido"))?
+        .to_owned();
+
+    let dir = if to_user_downloads.unwrap_or(false) {
+        app.path()
+            .download_dir()
+            .map_err(|e| AppError::io(format!("Erro ao obter pasta Downloads: {}", e)))?
+    } else {
+        app.path()
+            .app_data_dir()
+            .map_err(|e| AppError::io(format!("Erro ao obter diretório de dados do app: {}", e)))?
+            .join("downloads")
+    };
+
+    let id = transfer_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    transfer::download(&app, &state, &id, endpoint, params, dir.join(safe_name)).await
+}
+
+/// Cancela um upload/download em andamento
+#[command]
+pub async fn cancel_transfer(transfer_id: String, state: State<'_, AppState>) -> AppResult<bool> {
+    Ok(state.transfers.cancel(&transfer_id))
+}
@@ -1,9 +1,10 @@
+use crate::error::{AppError, AppResult};
 use serde::Deserialize;
 use tracing::{info, warn};
 
 /// Obtém a versão atual da aplicação
 #[tauri::command]
-pub async fn get_app_version() -> Result<String, String> {
+pub async fn get_app_version() -> AppResult<String> {
     let version = env!("CARGO_PKG_VERSION");
     info!("Versão atual da aplicação: {}", version);
     Ok(version.to_string())
@@ -19,13 +20,13 @@ struct GitHubRelease {
 
 /// Busca o changelog da release no GitHub
 #[tauri::command]
-pub async fn fetch_changelog(version: String) -> Result<String, String> {
+pub async fn fetch_changelog(version: String) -> AppResult<String> {
     info!("Buscando changelog para versão: {}", version);
 
     let client = reqwest::Client::builder()
         .user_agent("SGP-Desktop-App")
         .build()
-        .map_err(|e| format!("Erro ao criar cliente HTTP: {}", e))?;
+        .map_err(|e| AppError::internal(format!("Erro ao criar cliente HTTP: {}", e)))?;
 
     // Normalizar versão para formato de tag (adicionar 'v' se não tiver)
     let tag = if version.starts_with('v') {
@@ -74,12 +75,20 @@ pub async fn fetch_changelog(version: String) -> Result<String, String> {
                 info!("Changelog obtido via CHANGELOG.md (bruto)");
                 Ok(content)
             }
-            Err(e) => Err(format!("Erro ao ler conteúdo do CHANGELOG.md: {}", e)),
+            Err(e) => Err(AppError::parse(format!(
+                "Erro ao ler conteúdo do CHANGELOG.md: {}",
+                e
+            ))),
         },
-        Ok(response) => Err(format!(
-            "Erro ao buscar CHANGELOG.md (Status: {})",
-            response.status()
-        )),
-        Err(e) => Err(format!("Erro de rede ao buscar CHANGELOG.md: {}", e)),
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            Err(AppError::from_status(status, body)
+                .with_message(format!("Erro ao buscar CHANGELOG.md (Status: {})", status)))
+        }
+        Err(e) => {
+            let message = format!("Erro de rede ao buscar CHANGELOG.md: {}", e);
+            Err(AppError::from(e).with_message(message))
+        }
     }
 }
@@ -0,0 +1,149 @@
+use crate::bridge::auth;
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use crate::vault::Credentials;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{command, State};
+
+/// O que o frontend pode saber sobre o cofre. O token só volta em
+/// [`vault_load`], para o WebView restaurar a sessão em memória.
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultInfo {
+    pub base_url: String,
+    pub has_token: bool,
+    pub saved_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<StoredSession>,
+}
+
+/// Sessão salva no cofre, com o usuário e o papel lidos pelo core no login
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredSession {
+    pub token: String,
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub role: String,
+}
+
+impl From<&Credentials> for VaultInfo {
+    fn from(c: &Credentials) -> Self {
+        Self {
+            base_url: c.base_url.clone(),
+            has_token: c.token.is_some(),
+            saved_at: c.saved_at,
+            session: None,
+        }
+    }
+}
+
+impl StoredSession {
+    /// Só há sessão a restaurar se o token veio junto com a identidade do login
+    fn from_credentials(c: &Credentials) -> Option<Self> {
+        let token = c.token.clone()?;
+        let identity = c.identity.clone()?;
+        Some(Self {
+            token,
+            user_id: identity.user_id,
+            username: identity.username,
+            role: identity.role,
+        })
+    }
+}
+
+fn apply_to_state(state: &AppState, credentials: &Credentials) -> AppResult<()> {
+    auth::apply_connection(
+        state,
+        credentials.base_url.clone(),
+        credentials.token.clone(),
+        credentials.identity.clone(),
+    )?;
+    Ok(())
+}
+
+/// Salva base URL e token cifrados em disco e já os aplica à ponte. A
+/// identidade da sessão só é guardada se `token` e `base_url` forem os que o
+/// core usou no login.
+#[command]
+pub async fn vault_store(
+    base_url: String,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<VaultInfo> {
+    let current_base_url = state
+        .api_base_url
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex no base_url"))?
+        .clone();
+    let current_token = state
+        .auth_token
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex no auth_token"))?
+        .clone();
+    let identity = if token.is_some() && token == current_token && base_url == current_base_url {
+        state.session.identity()
+    } else {
+        None
+    };
+    let credentials = Credentials {
+        base_url,
+        token,
+        identity,
+        saved_at: Utc::now(),
+    };
+    state.vault.store(&credentials)?;
+    apply_to_state(&state, &credentials)?;
+    Ok(VaultInfo::from(&credentials))
+}
+
+/// Carrega as credenciais salvas (ex: na abertura do app) e as aplica à
+/// ponte. Devolve a sessão salva para o WebView voltar logado.
+#[command]
+pub async fn vault_load(state: State<'_, AppState>) -> AppResult<Option<VaultInfo>> {
+    match state.vault.load()? {
+        Some(credentials) => {
+            apply_to_state(&state, &credentials)?;
+            Ok(Some(VaultInfo {
+                session: StoredSession::from_credentials(&credentials),
+                ..VaultInfo::from(&credentials)
+            }))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Substitui o token mantendo a base URL; regrava o cofre com chave nova
+#[command]
+pub async fn vault_rotate_token(token: String, state: State<'_, AppState>) -> AppResult<VaultInfo> {
+    let base_url = match state.vault.load()? {
+        Some(c) => c.base_url,
+        None => state
+            .api_base_url
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no base_url"))?
+            .clone(),
+    };
+    let credentials = Credentials {
+        base_url,
+        token: Some(token),
+        identity: None,
+        saved_at: Utc::now(),
+    };
+    state.vault.store(&credentials)?;
+    apply_to_state(&state, &credentials)?;
+    Ok(VaultInfo::from(&credentials))
+}
+
+/// Logout: apaga o cofre e esquece o token em memória
+#[command]
+pub async fn vault_wipe(state: State<'_, AppState>) -> AppResult<()> {
+    state.vault.wipe()?;
+    let base_url = state
+        .api_base_url
+        .lock()
+        .map_err(|_| AppError::internal("Erro de mutex no base_url"))?
+        .clone();
+    auth::apply_connection(&state, base_url, None, None)?;
+    // Logout: respostas da sessão encerrada não ficam em memória nem em disco
+    state.response_cache.clear()
+}
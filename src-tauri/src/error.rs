@@ -0,0 +1,196 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// Categoria do erro, usada pelo frontend para decidir o que fazer
+/// (redirecionar para o login, tentar de novo, mostrar erro de validação...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// 401/403 - token ausente, expirado ou sem permissão
+    Auth,
+    /// Requisição excedeu o tempo limite
+    Timeout,
+    /// Falha de conexão (DNS, túnel fora do ar, conexão recusada)
+    Network,
+    /// 400/422 - payload rejeitado pela API Python
+    Validation,
+    /// 404
+    NotFound,
+    /// 409
+    Conflict,
+    /// 5xx
+    Server,
+    /// Qualquer outro status HTTP não tratado acima
+    Http,
+    /// Erros de leitura/escrita em disco
+    Io,
+    /// Resposta ou arquivo com formato inesperado
+    Parse,
+    /// Configuração ausente ou inválida (ex: base URL vazia)
+    Config,
+    /// Operação cancelada a pedido do frontend
+    Cancelled,
+    /// Bloqueado por uma regra local (política de endpoints, sandbox de arquivos)
+    Permission,
+    /// Erros internos (mutex envenenado, falha ao iniciar o Chrome...)
+    Internal,
+}
+
+/// Erro serializável retornado por todos os comandos Tauri
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub status: Option<u16>,
+    pub retryable: bool,
+    /// Corpo da resposta de erro da API (JSON quando possível, senão texto)
+    pub body: Option<Value>,
+    pub message: String,
+    /// Preenchido quando a mutação falhou por falta de conexão e foi salva na fila offline
+    pub queue_id: Option<String>,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        let retryable = matches!(kind, ErrorKind::Timeout | ErrorKind::Network);
+        Self {
+            kind,
+            status: None,
+            retryable,
+            body: None,
+            message: message.into(),
+            queue_id: None,
+        }
+    }
+
+    /// Constrói o erro a partir de uma resposta HTTP sem sucesso
+    pub fn from_status(status: u16, body_text: String) -> Self {
+        let kind = match status {
+            401 | 403 => ErrorKind::Auth,
+            400 | 422 => ErrorKind::Validation,
+            404 => ErrorKind::NotFound,
+            408 => ErrorKind::Timeout,
+            409 => ErrorKind::Conflict,
+            500..=599 => ErrorKind::Server,
+            _ => ErrorKind::Http,
+        };
+        let retryable = matches!(status, 408 | 429 | 502 | 503 | 504);
+
+        let body = if body_text.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_str(&body_text).unwrap_or(Value::String(body_text.clone())))
+        };
+
+        Self {
+            kind,
+            status: Some(status),
+            retryable,
+            body,
+            message: format!("{} - {}", status, body_text),
+            queue_id: None,
+        }
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Network, message)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Validation, message)
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Io, message)
+    }
+
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Parse, message)
+    }
+
+    pub fn config(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Config, message)
+    }
+
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Cancelled, message)
+    }
+
+    pub fn permission(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Permission, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Internal, message)
+    }
+
+    /// Indica que a API não foi alcançada (sem rede, timeout ou túnel fora do ar)
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self.kind, ErrorKind::Network | ErrorKind::Timeout)
+            || matches!(self.status, Some(502 | 503 | 504 | 530))
+    }
+
+    /// Substitui a mensagem mantendo kind/status/body
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::new(ErrorKind::Timeout, format!("Tempo limite excedido: {}", e))
+        } else if e.is_decode() {
+            Self::parse(format!("Falha no Parse JSON: {}", e))
+        } else if let Some(status) = e.status() {
+            Self::from_status(status.as_u16(), e.to_string())
+        } else {
+            Self::network(format!("Network Erro: {}", e))
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::parse(format!("Falha no Parse JSON: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_mapping() {
+        let err = AppError::from_status(401, "Token expirado".to_string());
+        assert_eq!(err.kind, ErrorKind::Auth);
+        assert!(!err.retryable);
+        assert_eq!(err.body, Some(Value::String("Token expirado".to_string())));
+
+        let err = AppError::from_status(422, r#"{"detail":"campo obrigatório"}"#.to_string());
+        assert_eq!(err.kind, ErrorKind::Validation);
+        assert_eq!(err.body.unwrap()["detail"], "campo obrigatório");
+
+        let err = AppError::from_status(503, String::new());
+        assert_eq!(err.kind, ErrorKind::Server);
+        assert!(err.retryable);
+        assert!(err.body.is_none());
+    }
+}
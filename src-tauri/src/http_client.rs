@@ -0,0 +1,319 @@
+//! Cliente HTTP da ponte, reconfigurável em tempo de execução.
+//!
+//! A configuração (timeouts por classe de endpoint, proxy, CA extra e pins de
+//! certificado) fica em `<app_data>/http_client.json`. Cada alteração
+//! reconstrói o `reqwest::Client`; requisições em andamento terminam com o
+//! cliente antigo.
+
+use crate::error::{AppError, AppResult};
+use crate::storage;
+use reqwest::tls::TlsInfo;
+use reqwest::{Certificate, Client, Proxy, Response};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Timeout aplicado aos endpoints que começam com `prefix`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointTimeout {
+    pub prefix: String,
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpClientConfig {
+    pub connect_timeout_secs: u64,
+    /// Timeout total padrão de uma requisição
+    pub timeout_secs: u64,
+    /// Classes de endpoint com timeout próprio (ex: relatórios pesados)
+    pub endpoint_timeouts: Vec<EndpointTimeout>,
+    /// Proxy HTTP/HTTPS, ex: `http://proxy.empresa:3128`
+    pub proxy_url: Option<String>,
+    /// Arquivo PEM com certificados raiz extras (servidores internos autoassinados)
+    pub ca_cert_path: Option<String>,
+    /// SHA-256 (hex) dos certificados aceitos. Vazio desativa o pinning.
+    pub pinned_cert_sha256: Vec<String>,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            timeout_secs: 10,
+            endpoint_timeouts: vec![EndpointTimeout {
+                prefix: "/relatorios/".to_string(),
+                timeout_secs: 120,
+            }],
+            proxy_url: None,
+            ca_cert_path: None,
+            pinned_cert_sha256: Vec::new(),
+        }
+    }
+}
+
+/// Blocos `CERTIFICATE` de um bundle PEM
+fn pem_blocks(pem: &str) -> Vec<&str> {
+    const END: &str = "-----END CERTIFICATE-----";
+    pem.split_inclusive(END)
+        .filter(|block| block.contains("-----BEGIN CERTIFICATE-----"))
+        .map(str::trim)
+        .collect()
+}
+
+/// Separa um bundle PEM em certificados individuais
+fn parse_pem_bundle(pem: &str) -> AppResult<Vec<Certificate>> {
+    let certs = pem_blocks(pem)
+        .into_iter()
+        .map(|block| {
+            Certificate::from_pem(block.as_bytes())
+                .map_err(|e| AppError::validation(format!("Certificado PEM inválido: {}", e)))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    if certs.is_empty() {
+        return Err(AppError::validation(
+            "Nenhum certificado encontrado no arquivo PEM",
+        ));
+    }
+    Ok(certs)
+}
+
+fn build_client(config: &HttpClientConfig) -> AppResult<Client> {
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .pool_idle_timeout(Duration::from_secs(300))
+        .pool_max_idle_per_host(10)
+        .tls_info(!config.pinned_cert_sha256.is_empty());
+
+    if let Some(ref proxy_url) = config.proxy_url {
+        let proxy = Proxy::all(proxy_url)
+            .map_err(|e| AppError::validation(format!("Proxy inválido: {}", e)))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ref path) = config.ca_cert_path {
+        let pem = std::fs::read_to_string(path)
+            .map_err(|e| AppError::io(format!("Erro ao ler certificado {}: {}", path, e)))?;
+        for cert in parse_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| AppError::internal(format!("Falha ao construir pool HTTP do Reqwest: {}", e)))
+}
+
+pub struct HttpClientManager {
+    path: Mutex<Option<PathBuf>>,
+    config: Mutex<HttpClientConfig>,
+    client: RwLock<Client>,
+    /// Hosts cujo certificado já foi conferido com os pins antes de receber o token
+    verified_hosts: Mutex<HashSet<String>>,
+}
+
+impl HttpClientManager {
+    pub fn new() -> Self {
+        let config = HttpClientConfig::default();
+        let client = build_client(&config).expect("Falha ao construir pool HTTP do Reqwest");
+        Self {
+            path: Mutex::new(None),
+            config: Mutex::new(config),
+            client: RwLock::new(client),
+            verified_hosts: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Carrega a configuração salva e reconstrói o cliente. Chamado no `setup`.
+    pub fn load(&self, path: PathBuf) -> AppResult<()> {
+        *self
+            .path
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no caminho da config HTTP"))? =
+            Some(path.clone());
+        if let Some(config) = storage::read_json::<HttpClientConfig>(&path)? {
+            self.apply(config)?;
+        }
+        Ok(())
+    }
+
+    pub fn client(&self) -> AppResult<Client> {
+        Ok(self
+            .client
+            .read()
+            .map_err(|_| AppError::internal("Erro de lock no cliente HTTP"))?
+            .clone())
+    }
+
+    pub fn config(&self) -> AppResult<HttpClientConfig> {
+        Ok(self
+            .config
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na config HTTP"))?
+            .clone())
+    }
+
+    fn apply(&self, config: HttpClientConfig) -> AppResult<()> {
+        let client = build_client(&config)?;
+        *self
+            .client
+            .write()
+            .map_err(|_| AppError::internal("Erro de lock no cliente HTTP"))? = client;
+        *self
+            .config
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na config HTTP"))? = config;
+        self.forget_verified_hosts();
+        Ok(())
+    }
+
+    /// Valida, aplica e grava uma nova configuração. Se o cliente não puder ser
+    /// construído (proxy ou PEM inválidos), a configuração atual é mantida.
+    pub fn reconfigure(&self, config: HttpClientConfig) -> AppResult<()> {
+        self.apply(config.clone())?;
+        let path = self
+            .path
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no caminho da config HTTP"))?
+            .clone();
+        if let Some(p) = path {
+            storage::write_json(&p, &config)?;
+        }
+        info!("[HttpClient] Cliente HTTP reconstruído");
+        Ok(())
+    }
+
+    /// Timeout da classe de endpoint mais específica, se houver
+    pub fn timeout_for(&self, endpoint: &str) -> Option<Duration> {
+        let config = self.config.lock().ok()?;
+        config
+            .endpoint_timeouts
+            .iter()
+            .filter(|t| endpoint.starts_with(&t.prefix))
+            .max_by_key(|t| t.prefix.len())
+            .map(|t| Duration::from_secs(t.timeout_secs))
+    }
+
+    fn pins(&self) -> Vec<String> {
+        self.config
+            .lock()
+            .map(|c| {
+                c.pinned_cert_sha256
+                    .iter()
+                    .map(|p| p.trim().replace(':', "").to_ascii_lowercase())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Confere o certificado do servidor com os pins configurados.
+    ///
+    /// Roda na resposta, ou seja, depois que a requisição (com o token) já
+    /// saiu. O que protege o token é [`Self::verify_host`], que faz uma
+    /// chamada sem credenciais antes do primeiro envio a cada host; esta
+    /// conferência pega um certificado trocado no meio da sessão. Nesse caso
+    /// os hosts verificados são esquecidos, e a próxima chamada volta a passar
+    /// por `verify_host` antes de mandar o token de novo.
+    ///
+    /// A falha é `Permission`: não é repetida pelo retry nem tratada como API
+    /// fora do ar (e não vai para a fila offline).
+    pub fn check_pin(&self, response: &Response) -> AppResult<()> {
+        let cert = response
+            .extensions()
+            .get::<TlsInfo>()
+            .and_then(|info| info.peer_certificate());
+        self.check_certificate(cert)
+    }
+
+    /// Confere um certificado (DER) com os pins; `None` se a conexão não usou
+    /// TLS. Usado por quem abre o socket por conta própria (WebSocket).
+    pub fn check_certificate(&self, cert: Option<&[u8]>) -> AppResult<()> {
+        let pins = self.pins();
+        if pins.is_empty() {
+            return Ok(());
+        }
+
+        let Some(der) = cert else {
+            self.forget_verified_hosts();
+            return Err(AppError::permission(
+                "Pinning ativo, mas a conexão não usou TLS",
+            ));
+        };
+
+        let fingerprint: String = Sha256::digest(der)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        if pins.contains(&fingerprint) {
+            Ok(())
+        } else {
+            warn!(
+                "[HttpClient] Certificado não confere com os pins: {}",
+                fingerprint
+            );
+            self.forget_verified_hosts();
+            Err(AppError::permission(format!(
+                "Certificado do servidor não confere com o pin ({})",
+                fingerprint
+            )))
+        }
+    }
+
+    fn forget_verified_hosts(&self) {
+        if let Ok(mut hosts) = self.verified_hosts.lock() {
+            hosts.clear();
+        }
+    }
+
+    /// Conector `native-tls` com as mesmas raízes extras do cliente HTTP.
+    /// Usado por quem abre o socket por conta própria (WebSocket, diagnóstico).
+    pub fn native_tls_connector(&self) -> AppResult<native_tls::TlsConnector> {
+        let mut builder = native_tls::TlsConnector::builder();
+        if let Some(path) = self.config()?.ca_cert_path {
+            let pem = std::fs::read_to_string(&path)
+                .map_err(|e| AppError::io(format!("Erro ao ler certificado {}: {}", path, e)))?;
+            for block in pem_blocks(&pem) {
+                let cert = native_tls::Certificate::from_pem(block.as_bytes()).map_err(|e| {
+                    AppError::validation(format!("Certificado PEM inválido: {}", e))
+                })?;
+                builder.add_root_certificate(cert);
+            }
+        }
+        builder
+            .build()
+            .map_err(|e| AppError::internal(format!("Falha ao montar conector TLS: {}", e)))
+    }
+
+    /// Antes de mandar o token para um host pela primeira vez, faz uma
+    /// requisição sem credenciais e confere o certificado com os pins
+    pub async fn verify_host(&self, base_url: &str) -> AppResult<()> {
+        if self.pins().is_empty() {
+            return Ok(());
+        }
+        let host = reqwest::Url::parse(base_url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| format!("{}:{:?}", h, u.port())))
+            .unwrap_or_else(|| base_url.to_string());
+        if self
+            .verified_hosts
+            .lock()
+            .map(|hosts| hosts.contains(&host))
+            .unwrap_or(false)
+        {
+            return Ok(());
+        }
+
+        let response = self.client()?.head(base_url).send().await?;
+        self.check_pin(&response)?;
+        if let Ok(mut hosts) = self.verified_hosts.lock() {
+            hosts.insert(host);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+//! Tratamento das imagens anexadas antes de irem para o armazenamento.
+//!
+//! Fotos de celular chegam com a rotação só no EXIF, logos vêm em PNG com
+//! fundo transparente e muitos arquivos já são menores que o limite. Aqui a
+//! imagem é girada conforme o EXIF, reduzida só quando passa das dimensões
+//! máximas e regravada em PNG se tiver transparência ou em JPEG com a
+//! qualidade pedida. Se nada precisar mudar, os bytes originais são mantidos
+//! (inclusive WebP e GIF animado).
+//!
+//! Dimensões e EXIF são lidos sem decodificar os pixels; a decodificação só
+//! acontece quando há o que girar, reduzir ou limpar. Não há encoder WebP sem
+//! a libwebp, então um WebP que precise ser regravado vira PNG (com
+//! transparência) ou JPEG, como os demais formatos. GIF animado regravado
+//! fica só com o primeiro quadro.
+
+use crate::error::{AppError, AppResult};
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageFormat, ImageOutputFormat};
+use std::io::Cursor;
+
+pub const DEFAULT_QUALITY: u8 = 85;
+
+#[derive(Debug, Clone)]
+pub struct ProcessOptions {
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    /// Qualidade JPEG, 1-100
+    pub quality: u8,
+    /// Regrava a imagem mesmo sem outra mudança, descartando EXIF/GPS
+    pub strip_metadata: bool,
+}
+
+impl Default for ProcessOptions {
+    fn default() -> Self {
+        Self {
+            max_width: None,
+            max_height: None,
+            quality: DEFAULT_QUALITY,
+            strip_metadata: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProcessedImage {
+    pub data: Vec<u8>,
+    pub mime_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// `false` quando os bytes originais foram mantidos
+    pub reencoded: bool,
+}
+
+fn mime_for(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Png => "image/png",
+        ImageFormat::WebP => "image/webp",
+        ImageFormat::Gif => "image/gif",
+        _ => "image/jpeg",
+    }
+}
+
+/// Valor da tag Orientation (1-8); 1 quando não há EXIF
+pub fn exif_orientation(data: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .filter(|o| (1..=8).contains(o))
+        .unwrap_or(1)
+}
+
+fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+/// Canal alfa realmente usado (PNG RGBA totalmente opaco conta como opaco)
+fn has_transparency(img: &DynamicImage) -> bool {
+    img.color().has_alpha() && img.to_rgba8().pixels().any(|p| p[3] < 255)
+}
+
+pub fn process(data: &[u8], options: &ProcessOptions) -> AppResult<ProcessedImage> {
+    let format = image::guess_format(data)
+        .map_err(|e| AppError::parse(format!("Formato de imagem não reconhecido: {}", e)))?;
+    let (width, height) = image::io::Reader::with_format(Cursor::new(data), format)
+        .into_dimensions()
+        .map_err(|e| AppError::parse(format!("Erro ao ler dimensões da imagem: {}", e)))?;
+
+    let orientation = exif_orientation(data);
+    // Dimensões como a imagem vai aparecer, já girada
+    let (width, height) = if orientation >= 5 {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    let max_width = options.max_width.unwrap_or(u32::MAX);
+    let max_height = options.max_height.unwrap_or(u32::MAX);
+    let needs_resize = width > max_width || height > max_height;
+
+    let keeps_original = matches!(
+        format,
+        ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP | ImageFormat::Gif
+    );
+    if keeps_original && !needs_resize && orientation == 1 && !options.strip_metadata {
+        return Ok(ProcessedImage {
+            data: data.to_vec(),
+            mime_type: mime_for(format),
+            width,
+            height,
+            reencoded: false,
+        });
+    }
+
+    let img = image::load_from_memory_with_format(data, format)
+        .map_err(|e| AppError::parse(format!("Erro ao carregar imagem: {}", e)))?;
+    let mut img = apply_orientation(img, orientation);
+    if needs_resize {
+        img = img.resize(
+            max_width.min(width),
+            max_height.min(height),
+            FilterType::Lanczos3,
+        );
+    }
+
+    let mut buffer = Cursor::new(Vec::new());
+    let mime_type = if has_transparency(&img) {
+        DynamicImage::ImageRgba8(img.to_rgba8())
+            .write_to(&mut buffer, ImageOutputFormat::Png)
+            .map_err(|e| AppError::parse(format!("Erro ao gravar PNG: {}", e)))?;
+        "image/png"
+    } else {
+        let quality = options.quality.clamp(1, 100);
+        DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_to(&mut buffer, ImageOutputFormat::Jpeg(quality))
+            .map_err(|e| AppError::parse(format!("Erro ao gravar JPEG: {}", e)))?;
+        "image/jpeg"
+    };
+
+    let (width, height) = img.dimensions();
+    Ok(ProcessedImage {
+        data: buffer.into_inner(),
+        mime_type,
+        width,
+        height,
+        reencoded: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn encode(img: DynamicImage, format: ImageOutputFormat) -> Vec<u8> {
+        let mut buffer = Cursor::new(Vec::new());
+        img.write_to(&mut buffer, format).unwrap();
+        buffer.into_inner()
+    }
+
+    #[test]
+    fn test_small_image_keeps_original_bytes() {
+        let png = encode(
+            DynamicImage::ImageRgb8(RgbImage::from_pixel(40, 20, Rgb([1, 2, 3]))),
+            ImageOutputFormat::Png,
+        );
+        let options = ProcessOptions {
+            max_width: Some(100),
+            max_height: Some(100),
+            ..ProcessOptions::default()
+        };
+        let result = process(&png, &options).unwrap();
+        assert!(!result.reencoded);
+        assert_eq!(result.data, png);
+        assert_eq!(result.mime_type, "image/png");
+    }
+
+    #[test]
+    fn test_transparent_png_stays_png_when_resized() {
+        let mut logo = RgbaImage::from_pixel(400, 200, Rgba([255, 0, 0, 255]));
+        logo.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+        let png = encode(DynamicImage::ImageRgba8(logo), ImageOutputFormat::Png);
+
+        let options = ProcessOptions {
+            max_width: Some(100),
+            ..ProcessOptions::default()
+        };
+        let result = process(&png, &options).unwrap();
+        assert!(result.reencoded);
+        assert_eq!(result.mime_type, "image/png");
+        assert_eq!((result.width, result.height), (100, 50));
+    }
+
+    #[test]
+    fn test_opaque_image_becomes_jpeg_with_quality() {
+        let png = encode(
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(300, 300, Rgba([9, 9, 9, 255]))),
+            ImageOutputFormat::Png,
+        );
+        let options = ProcessOptions {
+            max_width: Some(150),
+            max_height: Some(150),
+            quality: 40,
+            strip_metadata: false,
+        };
+        let result = process(&png, &options).unwrap();
+        assert_eq!(result.mime_type, "image/jpeg");
+        assert_eq!((result.width, result.height), (150, 150));
+    }
+
+    #[test]
+    fn test_no_exif_means_upright() {
+        let jpeg = encode(
+            DynamicImage::ImageRgb8(RgbImage::new(10, 10)),
+            ImageOutputFormat::Jpeg(90),
+        );
+        assert_eq!(exif_orientation(&jpeg), 1);
+        assert_eq!(
+            apply_orientation(DynamicImage::ImageRgb8(RgbImage::new(30, 10)), 6).dimensions(),
+            (10, 30)
+        );
+    }
+
+    #[test]
+    fn test_gif_is_kept_or_resized() {
+        let gif = encode(
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(200, 100, Rgba([5, 6, 7, 255]))),
+            ImageOutputFormat::Gif,
+        );
+
+        let small = process(&gif, &ProcessOptions::default()).unwrap();
+        assert!(!small.reencoded);
+        assert_eq!(small.mime_type, "image/gif");
+        assert_eq!((small.width, small.height), (200, 100));
+
+        let options = ProcessOptions {
+            max_width: Some(50),
+            ..ProcessOptions::default()
+        };
+        let resized = process(&gif, &options).unwrap();
+        assert!(resized.reencoded);
+        assert_eq!((resized.width, resized.height), (50, 25));
+    }
+
+    #[test]
+    fn test_truncated_image_fails_only_when_decoded() {
+        let png = encode(
+            DynamicImage::ImageRgb8(RgbImage::from_pixel(40, 20, Rgb([1, 2, 3]))),
+            ImageOutputFormat::Png,
+        );
+        // Cabeçalho íntegro, pixels cortados: sem mudança, nada é decodificado
+        let truncated = &png[..png.len() / 2];
+        let kept = process(truncated, &ProcessOptions::default()).unwrap();
+        assert!(!kept.reencoded);
+        assert_eq!((kept.width, kept.height), (40, 20));
+
+        let options = ProcessOptions {
+            strip_metadata: true,
+            ..ProcessOptions::default()
+        };
+        assert!(process(truncated, &options).is_err());
+    }
+}
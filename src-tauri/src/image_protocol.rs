@@ -0,0 +1,333 @@
+//! Protocolo `sgpimg://` para exibir imagens locais direto em `<img>`.
+//!
+//! Em vez de codificar o arquivo em base64 e passá-lo pelo IPC, o WebView
+//! pede `sgpimg://localhost/<referência>?w=256` (no Windows,
+//! `http://sgpimg.localhost/...`) e o core responde com os bytes do
+//! arquivo. O header `Range` é atendido lendo só o trecho pedido, para que
+//! originais grandes não precisem passar inteiros pela memória. A referência é qualquer uma aceita pelo índice de imagens
+//! (`sha256:<hash>`, o hash puro, URL baixada, referência do servidor);
+//! com `w` a resposta é a miniatura mais adequada. TIFF e PDF são servidos
+//! pela prévia gerada em [`crate::thumbnails`]; se ela não puder ser gerada
+//! a resposta é `415` com o motivo, nunca o original. Respostas de referências
+//! por hash nunca mudam e são marcadas como imutáveis.
+
+use crate::error::AppError;
+use crate::image_store;
+use crate::state::AppState;
+use crate::thumbnails;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Runtime, UriSchemeContext, UriSchemeResponder};
+use tracing::warn;
+
+pub const SCHEME: &str = "sgpimg";
+
+/// Maior `w` aceito; acima disso vale o original
+const MAX_WIDTH: u32 = 8192;
+
+fn plain(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(message.as_bytes().to_vec())
+        .unwrap_or_default()
+}
+
+/// TIFF ou PDF sem prévia: o original não abriria em `<img>`, então a
+/// resposta é o erro, e não o arquivo
+fn no_preview(hash: &str, error: &AppError) -> Response<Vec<u8>> {
+    warn!("[ImageProtocol] Sem prévia para {}: {}", hash, error);
+    plain(
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        &format!("Não foi possível gerar a prévia: {}", error.message),
+    )
+}
+
+/// Referência e largura pedida a partir da URI
+fn parse_request(uri: &tauri::http::Uri) -> Option<(String, Option<u32>)> {
+    let raw = uri.path().trim_start_matches('/');
+    let reference = urlencoding::decode(raw).ok()?.into_owned();
+    if reference.is_empty() {
+        return None;
+    }
+    let width = uri.query().and_then(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "w")
+            .and_then(|(_, value)| value.parse::<u32>().ok())
+            .filter(|w| *w > 0 && *w <= MAX_WIDTH)
+    });
+    Some((reference, width))
+}
+
+fn respond<R: Runtime>(app: &AppHandle<R>, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some((reference, width)) = parse_request(request.uri()) else {
+        return plain(StatusCode::BAD_REQUEST, "Referência de imagem ausente");
+    };
+    let state = app.state::<AppState>();
+
+    let (hash, source) = match state.images.resolve_entry(&reference) {
+        Ok(Some(found)) => found,
+        Ok(None) => return plain(StatusCode::NOT_FOUND, "Imagem não encontrada"),
+        Err(e) => {
+            warn!("[ImageProtocol] Erro ao resolver {}: {}", reference, e);
+            return plain(StatusCode::INTERNAL_SERVER_ERROR, &e.message);
+        }
+    };
+
+    let (path, mime_type) = match width {
+        Some(w) => {
+            let variant = state
+                .images
+                .root()
+                .and_then(|root| thumbnails::best_variant(&root, &hash, &source, w));
+            match variant {
+                Ok(v) => (PathBuf::from(v.path), v.mime_type),
+                Err(e) if thumbnails::web_displayable(&source) => {
+                    warn!("[ImageProtocol] Sem miniatura para {}: {}", hash, e);
+                    (source, String::new())
+                }
+                Err(e) => return no_preview(&hash, &e),
+            }
+        }
+        // TIFF e PDF não abrem em <img>: vai a prévia no lugar do original
+        None => match state
+            .images
+            .root()
+            .and_then(|root| thumbnails::display_source(&root, &hash, &source))
+        {
+            Ok(path) => (path, String::new()),
+            Err(e) => return no_preview(&hash, &e),
+        },
+    };
+
+    // Referência por conteúdo: a mesma URL sempre devolve os mesmos bytes
+    let etag = format!("\"{}-{}\"", hash, width.unwrap_or(0));
+    let immutable = image_store::parse_reference(&reference).is_some();
+    let cache_control = if immutable {
+        "public, max-age=31536000, immutable"
+    } else {
+        "no-cache"
+    };
+
+    let not_modified = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == etag);
+    if not_modified {
+        return Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, &etag)
+            .header(header::CACHE_CONTROL, cache_control)
+            .body(Vec::new())
+            .unwrap_or_default();
+    }
+
+    let len = match std::fs::metadata(&path) {
+        Ok(meta) => meta.len(),
+        Err(e) => {
+            warn!("[ImageProtocol] Erro ao ler {:?}: {}", path, e);
+            return plain(StatusCode::NOT_FOUND, "Imagem não encontrada");
+        }
+    };
+    let range = parse_range(
+        request
+            .headers()
+            .get(header::RANGE)
+            .and_then(|v| v.to_str().ok()),
+        len,
+    );
+    let (status, start, end) = match range {
+        ByteRange::Full => (StatusCode::OK, 0, len.saturating_sub(1)),
+        ByteRange::Partial(start, end) => (StatusCode::PARTIAL_CONTENT, start, end),
+        ByteRange::Unsatisfiable => {
+            return Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                .body(Vec::new())
+                .unwrap_or_default();
+        }
+    };
+
+    let data = if len == 0 {
+        Vec::new()
+    } else {
+        match read_range(&path, start, end) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("[ImageProtocol] Erro ao ler {:?}: {}", path, e);
+                return plain(StatusCode::NOT_FOUND, "Imagem não encontrada");
+            }
+        }
+    };
+    let mime_type = if mime_type.is_empty() {
+        infer::get_from_path(&path)
+            .ok()
+            .flatten()
+            .map(|t| t.mime_type())
+            .unwrap_or("application/octet-stream")
+            .to_string()
+    } else {
+        mime_type
+    };
+
+    let mut builder = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, mime_type)
+        .header(header::CONTENT_LENGTH, data.len())
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CACHE_CONTROL, cache_control)
+        .header(header::ETAG, &etag)
+        // Permite desenhar a imagem em <canvas> sem "contaminar" o canvas
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    if status == StatusCode::PARTIAL_CONTENT {
+        builder = builder.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, len),
+        );
+    }
+    builder.body(data).unwrap_or_default()
+}
+
+/// Trecho pedido no header `Range`
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    Full,
+    /// Primeiro e último byte, inclusive
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+/// Interpreta `bytes=início-fim`, `bytes=início-` e `bytes=-sufixo`. Vários
+/// intervalos ou um header malformado valem como o arquivo inteiro, como a
+/// RFC 9110 permite.
+fn parse_range(value: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = value.and_then(|v| v.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((first, last)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (first, last) = (first.trim(), last.trim());
+
+    let (start, end) = if first.is_empty() {
+        match last.parse::<u64>() {
+            Ok(0) => return ByteRange::Unsatisfiable,
+            Ok(suffix) => (len.saturating_sub(suffix), len.saturating_sub(1)),
+            Err(_) => return ByteRange::Full,
+        }
+    } else {
+        let Ok(start) = first.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        let end = if last.is_empty() {
+            len.saturating_sub(1)
+        } else {
+            match last.parse::<u64>() {
+                Ok(end) if end >= start => end.min(len.saturating_sub(1)),
+                _ => return ByteRange::Full,
+            }
+        };
+        (start, end)
+    };
+
+    if len == 0 || start >= len {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(start, end)
+    }
+}
+
+/// Lê só os bytes `start..=end`, sem carregar o resto do arquivo
+fn read_range(path: &Path, start: u64, end: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut data = Vec::with_capacity((end - start + 1) as usize);
+    file.take(end - start + 1).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Handler registrado com `register_asynchronous_uri_scheme_protocol`.
+/// Leitura e geração de miniatura rodam fora da thread do WebView.
+pub fn handle<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        responder.respond(respond(&app, &request));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let hash = "a".repeat(64);
+        let uri: tauri::http::Uri = format!("sgpimg://localhost/sha256%3A{}?w=256", hash)
+            .parse()
+            .unwrap();
+        assert_eq!(
+            parse_request(&uri),
+            Some((format!("sha256:{}", hash), Some(256)))
+        );
+
+        let uri: tauri::http::Uri = "http://sgpimg.localhost/arte.png?w=abc".parse().unwrap();
+        assert_eq!(parse_request(&uri), Some(("arte.png".to_string(), None)));
+
+        let uri: tauri::http::Uri = "sgpimg://localhost/".parse().unwrap();
+        assert_eq!(parse_request(&uri), None);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range(None, 100), ByteRange::Full);
+        assert_eq!(
+            parse_range(Some("bytes=0-9"), 100),
+            ByteRange::Partial(0, 9)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=90-"), 100),
+            ByteRange::Partial(90, 99)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=-10"), 100),
+            ByteRange::Partial(90, 99)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=50-500"), 100),
+            ByteRange::Partial(50, 99)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=100-"), 100),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(parse_range(Some("bytes=-0"), 100), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=0-1,5-6"), 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=9-3"), 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("items=0-1"), 100), ByteRange::Full);
+    }
+
+    #[test]
+    fn test_read_range_reads_only_the_slice() {
+        let path = std::env::temp_dir().join(format!("sgp-range-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, (0u8..100).collect::<Vec<_>>()).unwrap();
+
+        assert_eq!(read_range(&path, 10, 14).unwrap(), vec![10, 11, 12, 13, 14]);
+        assert_eq!(read_range(&path, 98, 99).unwrap(), vec![98, 99]);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
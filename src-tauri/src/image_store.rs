@@ -0,0 +1,1044 @@
+//! Armazenamento de imagens endereçado por conteúdo.
+//!
+//! Cada imagem é gravada uma única vez em
+//! `<app_data>/images/objects/<2 primeiros hex>/<sha256>.<ext>`; a mesma arte
+//! anexada a dez itens de pedido ocupa um arquivo só. Quando o último item
+//! de pedido solta a referência, o arquivo é apagado.
+//!
+//! Um único índice (`images/index.json`, gravado de forma atômica) liga
+//! hashes, URLs de imagens baixadas (`cached_*`), referências do servidor e
+//! nomes de arquivo aos caminhos locais, então qualquer um deles resolve a
+//! imagem com uma consulta em memória. Ele substitui os antigos arquivos
+//! `<url>.cache`, migrados na primeira carga.
+
+use crate::error::{AppError, AppResult};
+use crate::storage;
+use crate::thumbnails;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+/// Prefixo das referências estáveis devolvidas ao frontend
+pub const REFERENCE_PREFIX: &str = "sha256:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobEntry {
+    pub hash: String,
+    pub extension: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+    /// Itens de pedido que usam a imagem (ex: `pedido:12:item:3`)
+    #[serde(default)]
+    pub refs: BTreeSet<String>,
+    /// Quando ficou sem itens (ao soltar o último ou ser escolhida de novo);
+    /// o prazo para virar órfã conta daqui, ou de `created_at`
+    #[serde(default)]
+    pub idle_since: Option<DateTime<Utc>>,
+}
+
+/// Imagem baixada da API e guardada como `cached_*`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedEntry {
+    pub url: String,
+    pub file_name: String,
+    pub hash: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub cached_at: DateTime<Utc>,
+    /// Último acesso via [`ImageStore::resolve`]; ordena a remoção LRU
+    #[serde(default)]
+    pub last_access_at: Option<DateTime<Utc>>,
+}
+
+impl CachedEntry {
+    fn last_used(&self) -> DateTime<Utc> {
+        self.last_access_at.unwrap_or(self.cached_at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageQuota {
+    /// Tamanho máximo somado dos downloads em cache (`cached_*`)
+    pub max_cache_bytes: u64,
+    /// Downloads sem acesso há mais dias que isso são removidos
+    pub max_cache_age_days: u32,
+    /// Idade mínima para uma imagem sem uso ser considerada órfã
+    pub orphan_grace_hours: u32,
+}
+
+impl Default for ImageQuota {
+    fn default() -> Self {
+        Self {
+            max_cache_bytes: 512 * 1024 * 1024,
+            max_cache_age_days: 30,
+            orphan_grace_hours: 7 * 24,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanKind {
+    /// Conteúdo do índice sem nenhum item de pedido
+    Unreferenced,
+    /// Arquivo solto que o índice não conhece (UUID antigo, `cached_*`, miniatura)
+    Untracked,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanImage {
+    pub path: String,
+    pub kind: OrphanKind,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageBucket {
+    pub count: usize,
+    pub bytes: u64,
+}
+
+impl UsageBucket {
+    fn of(sizes: impl Iterator<Item = u64>) -> Self {
+        let (count, bytes) = sizes.fold((0, 0), |(n, total), size| (n + 1, total + size));
+        Self { count, bytes }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageUsage {
+    pub objects: UsageBucket,
+    pub cached: UsageBucket,
+    pub orphans: UsageBucket,
+    pub quota: ImageQuota,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupReport {
+    pub evicted_cached: usize,
+    /// Entradas do índice cujo arquivo não existia mais
+    pub pruned_entries: usize,
+    pub removed_orphans: usize,
+    pub freed_bytes: u64,
+    /// Órfãs encontradas (já removidas se `remove_orphans`)
+    pub orphans: Vec<OrphanImage>,
+}
+
+/// Resultado de [`ImageStore::put`]
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    pub entry: BlobEntry,
+    pub path: PathBuf,
+    /// `true` se o conteúdo já existia e nada foi gravado
+    pub deduplicated: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreIndex {
+    #[serde(default)]
+    blobs: HashMap<String, BlobEntry>,
+    /// URL -> imagem baixada
+    #[serde(default)]
+    cached: HashMap<String, CachedEntry>,
+    /// Referência devolvida pela API no upload -> hash
+    #[serde(default)]
+    server_refs: HashMap<String, String>,
+    /// hash -> URL; derivado de `cached`
+    #[serde(skip)]
+    cached_by_hash: HashMap<String, String>,
+    /// nome do arquivo `cached_*` -> URL; derivado de `cached`
+    #[serde(skip)]
+    cached_by_file: HashMap<String, String>,
+}
+
+impl StoreIndex {
+    fn rebuild_lookups(&mut self) {
+        self.cached_by_hash = self
+            .cached
+            .values()
+            .map(|c| (c.hash.clone(), c.url.clone()))
+            .collect();
+        self.cached_by_file = self
+            .cached
+            .values()
+            .map(|c| (c.file_name.clone(), c.url.clone()))
+            .collect();
+    }
+
+    fn remove_cached(&mut self, url: &str) -> Option<CachedEntry> {
+        let old = self.cached.remove(url)?;
+        self.cached_by_hash.remove(&old.hash);
+        self.cached_by_file.remove(&old.file_name);
+        Some(old)
+    }
+
+    fn insert_cached(&mut self, entry: CachedEntry) {
+        self.remove_cached(&entry.url);
+        self.cached_by_hash
+            .insert(entry.hash.clone(), entry.url.clone());
+        self.cached_by_file
+            .insert(entry.file_name.clone(), entry.url.clone());
+        self.cached.insert(entry.url.clone(), entry);
+    }
+}
+
+#[derive(Default)]
+pub struct ImageStore {
+    /// `<app_data>/images`
+    root: Mutex<Option<PathBuf>>,
+    index: Mutex<StoreIndex>,
+    quota: Mutex<ImageQuota>,
+}
+
+pub fn hash_bytes(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Aceita `sha256:<hex>` ou o hex puro; devolve o hash normalizado
+pub fn parse_reference(reference: &str) -> Option<String> {
+    let hex = reference
+        .trim()
+        .strip_prefix(REFERENCE_PREFIX)
+        .unwrap_or(reference.trim());
+    (hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| hex.to_ascii_lowercase())
+}
+
+pub fn reference_for(hash: &str) -> String {
+    format!("{}{}", REFERENCE_PREFIX, hash)
+}
+
+/// Tipos aceitos como arte: os que o WebView exibe e os de gráfica
+/// (TIFF, BMP, PDF), que ganham prévia em [`crate::thumbnails`]
+pub const SUPPORTED_MIME_TYPES: [&str; 7] = [
+    "image/jpeg",
+    "image/png",
+    "image/webp",
+    "image/gif",
+    "image/tiff",
+    "image/bmp",
+    "application/pdf",
+];
+
+pub fn is_supported_mime(mime_type: &str) -> bool {
+    mime_type == "image/jpg" || SUPPORTED_MIME_TYPES.contains(&mime_type)
+}
+
+pub fn extension_for_mime(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/png" => "png",
+        "image/webp" => "webp",
+        "image/gif" => "gif",
+        "image/tiff" => "tif",
+        "image/bmp" => "bmp",
+        "application/pdf" => "pdf",
+        _ => "jpg",
+    }
+}
+
+/// Arquivos de controle na raiz do armazenamento
+const STORE_FILES: &[&str] = &["index.json", "quota.json"];
+
+fn blob_path(root: &Path, hash: &str, extension: &str) -> PathBuf {
+    root.join("objects")
+        .join(&hash[..2])
+        .join(format!("{}.{}", hash, extension))
+}
+
+/// Nome estável do arquivo de cache de uma URL
+fn cached_file_name(url: &str, extension: &str) -> String {
+    format!("cached_{}.{}", &hash_bytes(url.as_bytes())[..16], extension)
+}
+
+/// Converte os antigos `<url>.cache` (texto com o caminho da imagem) em
+/// entradas do índice e apaga os sidecars
+fn migrate_sidecars(root: &Path, index: &mut StoreIndex) -> usize {
+    let Ok(entries) = fs::read_dir(root) else {
+        return 0;
+    };
+    let mut migrated = 0;
+    for entry in entries.flatten() {
+        let sidecar = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(url) = name.strip_suffix(".cache") else {
+            continue;
+        };
+        let target = fs::read_to_string(&sidecar)
+            .ok()
+            .map(|p| PathBuf::from(p.trim()))
+            .filter(|p| p.starts_with(root) && p.is_file());
+        if let Some(target) = target {
+            if let Ok(data) = fs::read(&target) {
+                let mime_type = infer::get(&data)
+                    .map(|t| t.mime_type())
+                    .unwrap_or("image/jpeg");
+                let file_name = target
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                index.insert_cached(CachedEntry {
+                    url: url.to_string(),
+                    file_name,
+                    hash: hash_bytes(&data),
+                    mime_type: mime_type.to_string(),
+                    size: data.len() as u64,
+                    cached_at: Utc::now(),
+                    last_access_at: None,
+                });
+                migrated += 1;
+            }
+        }
+        let _ = fs::remove_file(&sidecar);
+    }
+    migrated
+}
+
+impl ImageStore {
+    /// Define `<app_data>/images` como raiz e carrega o índice. Chamado no `setup`.
+    pub fn set_dir(&self, root: PathBuf) -> AppResult<()> {
+        fs::create_dir_all(root.join("objects"))
+            .map_err(|e| AppError::io(format!("Erro ao criar diretório de imagens: {}", e)))?;
+        let mut index: StoreIndex =
+            storage::read_json(&root.join("index.json"))?.unwrap_or_default();
+        index.rebuild_lookups();
+
+        let migrated = migrate_sidecars(&root, &mut index);
+        if migrated > 0 {
+            info!(
+                "[ImageStore] {} arquivos .cache migrados para o índice",
+                migrated
+            );
+            self.persist(&root, &index)?;
+        }
+        info!(
+            "[ImageStore] {} imagens e {} downloads no índice",
+            index.blobs.len(),
+            index.cached.len()
+        );
+
+        let quota: ImageQuota = storage::read_json(&root.join("quota.json"))?.unwrap_or_default();
+        *self
+            .quota
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na cota de imagens"))? = quota;
+        *self.lock_index()? = index;
+        *self
+            .root
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no diretório de imagens"))? = Some(root);
+        if let Err(e) = self.enforce_cache_quota() {
+            warn!("[ImageStore] Erro ao aplicar cota do cache: {}", e);
+        }
+        Ok(())
+    }
+
+    /// `<app_data>/images`
+    pub fn root(&self) -> AppResult<PathBuf> {
+        self.root
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no diretório de imagens"))?
+            .clone()
+            .ok_or_else(|| AppError::config("Armazenamento de imagens não inicializado"))
+    }
+
+    fn lock_index(&self) -> AppResult<std::sync::MutexGuard<'_, StoreIndex>> {
+        self.index
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no índice de imagens"))
+    }
+
+    fn persist(&self, root: &Path, index: &StoreIndex) -> AppResult<()> {
+        storage::write_json(&root.join("index.json"), index)
+    }
+
+    /// Grava a imagem se o conteúdo ainda não existir
+    pub fn put(&self, data: &[u8], mime_type: &str) -> AppResult<StoredImage> {
+        let root = self.root()?;
+        let hash = hash_bytes(data);
+        let mut index = self.lock_index()?;
+
+        if let Some(entry) = index.blobs.get_mut(&hash) {
+            let path = blob_path(&root, &hash, &entry.extension);
+            if path.exists() {
+                // Escolhida de novo num formulário: o prazo de órfã recomeça
+                if entry.refs.is_empty() {
+                    entry.idle_since = Some(Utc::now());
+                }
+                let entry = entry.clone();
+                self.persist(&root, &index)?;
+                return Ok(StoredImage {
+                    entry,
+                    path,
+                    deduplicated: true,
+                });
+            }
+            warn!(
+                "[ImageStore] Arquivo de {} sumiu do disco; regravando",
+                hash
+            );
+        }
+
+        let extension = extension_for_mime(mime_type).to_string();
+        let path = blob_path(&root, &hash, &extension);
+        storage::write_atomic(&path, data)?;
+
+        let refs = index
+            .blobs
+            .remove(&hash)
+            .map(|old| old.refs)
+            .unwrap_or_default();
+        let entry = BlobEntry {
+            hash: hash.clone(),
+            extension,
+            mime_type: mime_type.to_string(),
+            size: data.len() as u64,
+            created_at: Utc::now(),
+            refs,
+            idle_since: None,
+        };
+        index.blobs.insert(hash, entry.clone());
+        self.persist(&root, &index)?;
+        Ok(StoredImage {
+            entry,
+            path,
+            deduplicated: false,
+        })
+    }
+
+    pub fn entry(&self, hash: &str) -> AppResult<Option<BlobEntry>> {
+        Ok(self.lock_index()?.blobs.get(hash).cloned())
+    }
+
+    /// Registra que `owner` (item de pedido) usa a imagem. Devolve a contagem.
+    pub fn add_ref(&self, hash: &str, owner: &str) -> AppResult<usize> {
+        let root = self.root()?;
+        let mut index = self.lock_index()?;
+        let entry = index
+            .blobs
+            .get_mut(hash)
+            .ok_or_else(|| AppError::validation(format!("Imagem {} não encontrada", hash)))?;
+        entry.refs.insert(owner.to_string());
+        entry.idle_since = None;
+        let count = entry.refs.len();
+        self.persist(&root, &index)?;
+        Ok(count)
+    }
+
+    /// Remove a referência de `owner` e devolve quantas sobraram. Se `owner`
+    /// não usava a imagem, nada muda. Sem referências o arquivo fica: pode
+    /// estar num formulário ainda não salvo ou na fila offline, e só sai pela
+    /// limpeza de órfãs depois de `orphan_grace_hours`.
+    pub fn release(&self, hash: &str, owner: &str) -> AppResult<usize> {
+        let root = self.root()?;
+        let mut index = self.lock_index()?;
+        let Some(entry) = index.blobs.get_mut(hash) else {
+            return Ok(0);
+        };
+        if !entry.refs.remove(owner) {
+            return Ok(entry.refs.len());
+        }
+        let remaining = entry.refs.len();
+        if remaining == 0 {
+            entry.idle_since = Some(Utc::now());
+            info!("[ImageStore] Imagem {} sem referências", hash);
+        }
+        self.persist(&root, &index)?;
+        Ok(remaining)
+    }
+
+    /// Grava uma imagem baixada de `url` como `cached_*`, substituindo a anterior
+    pub fn put_cached(
+        &self,
+        url: &str,
+        data: &[u8],
+        mime_type: &str,
+    ) -> AppResult<(CachedEntry, PathBuf)> {
+        let root = self.root()?;
+        let file_name = cached_file_name(url, extension_for_mime(mime_type));
+        let path = root.join(&file_name);
+        storage::write_atomic(&path, data)?;
+
+        let entry = CachedEntry {
+            url: url.to_string(),
+            file_name,
+            hash: hash_bytes(data),
+            mime_type: mime_type.to_string(),
+            size: data.len() as u64,
+            cached_at: Utc::now(),
+            last_access_at: None,
+        };
+        let mut index = self.lock_index()?;
+        if let Some(old) = index.cached.get(url) {
+            if old.file_name != entry.file_name {
+                let _ = fs::remove_file(root.join(&old.file_name));
+            }
+        }
+        index.insert_cached(entry.clone());
+        self.persist(&root, &index)?;
+        drop(index);
+        self.enforce_cache_quota()?;
+        Ok((entry, path))
+    }
+
+    /// Liga a referência devolvida pela API no upload ao conteúdo local
+    pub fn link_server_reference(&self, hash: &str, server_reference: &str) -> AppResult<()> {
+        let root = self.root()?;
+        let mut index = self.lock_index()?;
+        if !index.blobs.contains_key(hash) && !index.cached_by_hash.contains_key(hash) {
+            return Err(AppError::validation(format!(
+                "Imagem {} não encontrada",
+                hash
+            )));
+        }
+        index
+            .server_refs
+            .insert(server_reference.to_string(), hash.to_string());
+        self.persist(&root, &index)
+    }
+
+    /// Resolve qualquer referência conhecida (`sha256:<hash>`, URL baixada,
+    /// referência do servidor ou nome de arquivo) para o caminho local
+    pub fn resolve(&self, reference: &str) -> AppResult<Option<PathBuf>> {
+        Ok(self.resolve_entry(reference)?.map(|(_, path)| path))
+    }
+
+    /// Como [`resolve`](Self::resolve), devolvendo também o hash do conteúdo
+    pub fn resolve_entry(&self, reference: &str) -> AppResult<Option<(String, PathBuf)>> {
+        let root = self.root()?;
+        let mut index = self.lock_index()?;
+        let reference = reference.trim();
+
+        let target = if let Some(hash) = parse_reference(reference) {
+            target_for_hash(&index, &root, &hash)
+        } else if index.cached.contains_key(reference) {
+            Some(Target::Cached(reference.to_string()))
+        } else if let Some(hash) = index.server_refs.get(reference) {
+            target_for_hash(&index, &root, hash)
+        } else {
+            // Nome de arquivo (`<hash>.png`, `cached_*.jpg`)
+            let file_name = Path::new(reference)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            match index.cached_by_file.get(&file_name) {
+                Some(url) => Some(Target::Cached(url.clone())),
+                None => Path::new(&file_name)
+                    .file_stem()
+                    .and_then(|stem| parse_reference(&stem.to_string_lossy()))
+                    .and_then(|hash| target_for_hash(&index, &root, &hash)),
+            }
+        };
+
+        let (hash, path) = match target {
+            Some(Target::Blob(hash, path)) => (hash, path),
+            Some(Target::Cached(url)) => {
+                let Some(entry) = index.cached.get_mut(&url) else {
+                    return Ok(None);
+                };
+                // Só em memória; vai para o disco na próxima gravação do índice
+                entry.last_access_at = Some(Utc::now());
+                (entry.hash.clone(), root.join(&entry.file_name))
+            }
+            None => return Ok(None),
+        };
+        Ok(path.is_file().then_some((hash, path)))
+    }
+
+    pub fn quota(&self) -> AppResult<ImageQuota> {
+        Ok(self
+            .quota
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na cota de imagens"))?
+            .clone())
+    }
+
+    /// Grava a nova cota e já aplica o limite do cache
+    pub fn set_quota(&self, quota: ImageQuota) -> AppResult<Vec<CachedEntry>> {
+        let root = self.root()?;
+        storage::write_json(&root.join("quota.json"), &quota)?;
+        *self
+            .quota
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex na cota de imagens"))? = quota;
+        self.enforce_cache_quota()
+    }
+
+    /// Remove downloads sem acesso há mais de `max_cache_age_days` e, se o
+    /// cache ainda passar de `max_cache_bytes`, os menos usados recentemente
+    pub fn enforce_cache_quota(&self) -> AppResult<Vec<CachedEntry>> {
+        let root = self.root()?;
+        let quota = self.quota()?;
+        let mut index = self.lock_index()?;
+
+        let cutoff = Utc::now() - chrono::Duration::days(quota.max_cache_age_days as i64);
+        let mut by_age: Vec<CachedEntry> = index.cached.values().cloned().collect();
+        by_age.sort_by_key(|c| c.last_used());
+
+        let mut total: u64 = by_age.iter().map(|c| c.size).sum();
+        let mut evicted = Vec::new();
+        for entry in by_age {
+            if entry.last_used() >= cutoff && total <= quota.max_cache_bytes {
+                break;
+            }
+            total = total.saturating_sub(entry.size);
+            index.remove_cached(&entry.url);
+            remove_quietly(&root.join(&entry.file_name));
+            if !index.blobs.contains_key(&entry.hash)
+                && !index.cached_by_hash.contains_key(&entry.hash)
+            {
+                thumbnails::remove(&root, &entry.hash);
+            }
+            evicted.push(entry);
+        }
+
+        if !evicted.is_empty() {
+            info!(
+                "[ImageStore] {} downloads removidos do cache ({} bytes)",
+                evicted.len(),
+                evicted.iter().map(|c| c.size).sum::<u64>()
+            );
+            self.persist(&root, &index)?;
+        }
+        Ok(evicted)
+    }
+
+    /// Imagens que nenhum item de pedido usa: conteúdo sem referências
+    /// (inclusive arte salva e nunca anexada a um pedido), arquivos soltos na
+    /// raiz fora do índice (UUIDs antigos, downloads `cached_*` esquecidos) e
+    /// miniaturas de conteúdo que já saiu. Só entram depois de
+    /// `orphan_grace_hours`, e nunca se citados em `pending` (corpos de
+    /// mutações ainda na fila offline).
+    pub fn find_orphans(&self, pending: &[String]) -> AppResult<Vec<OrphanImage>> {
+        let root = self.root()?;
+        let quota = self.quota()?;
+        let index = self.lock_index()?;
+        let cutoff = Utc::now() - chrono::Duration::hours(quota.orphan_grace_hours as i64);
+        let pinned = |name: &str| pending.iter().any(|body| body.contains(name));
+
+        let mut orphans = Vec::new();
+        for blob in index.blobs.values() {
+            let idle_since = blob.idle_since.unwrap_or(blob.created_at);
+            if blob.refs.is_empty() && idle_since < cutoff && !pinned(&blob.hash) {
+                orphans.push(OrphanImage {
+                    path: blob_path(&root, &blob.hash, &blob.extension)
+                        .to_string_lossy()
+                        .to_string(),
+                    kind: OrphanKind::Unreferenced,
+                    size: blob.size,
+                });
+            }
+        }
+
+        let mut loose = Vec::new();
+        collect_files(&root, &mut loose);
+        collect_files(&root.join("thumbs"), &mut loose);
+        for (path, size, modified) in loose {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let tracked = if path.parent() == Some(root.join("thumbs").as_path()) {
+                // Miniatura: `<hash>_<lado>.<ext>`, vale enquanto o conteúdo existir
+                name.split('_')
+                    .next()
+                    .and_then(parse_reference)
+                    .is_some_and(|hash| {
+                        index.blobs.contains_key(&hash) || index.cached_by_hash.contains_key(&hash)
+                    })
+            } else {
+                // Na raiz, fora o índice e a cota, só valem downloads do índice
+                STORE_FILES.contains(&name.as_str()) || index.cached_by_file.contains_key(&name)
+            };
+            if tracked || modified >= cutoff || pinned(&name) {
+                continue;
+            }
+            orphans.push(OrphanImage {
+                path: path.to_string_lossy().to_string(),
+                kind: OrphanKind::Untracked,
+                size,
+            });
+        }
+        Ok(orphans)
+    }
+
+    pub fn usage(&self, pending: &[String]) -> AppResult<ImageUsage> {
+        let orphans = self.find_orphans(pending)?;
+        let quota = self.quota()?;
+        let index = self.lock_index()?;
+        Ok(ImageUsage {
+            objects: UsageBucket::of(index.blobs.values().map(|b| b.size)),
+            cached: UsageBucket::of(index.cached.values().map(|c| c.size)),
+            orphans: UsageBucket::of(orphans.iter().map(|o| o.size)),
+            quota,
+        })
+    }
+
+    /// Aplica a cota do cache, tira do índice entradas cujo arquivo sumiu e,
+    /// com `remove_orphans`, apaga as órfãs (senão só as lista)
+    pub fn cleanup(&self, remove_orphans: bool, pending: &[String]) -> AppResult<CleanupReport> {
+        let evicted = self.enforce_cache_quota()?;
+        let root = self.root()?;
+
+        let pruned = {
+            let mut index = self.lock_index()?;
+            let missing_blobs: Vec<String> = index
+                .blobs
+                .values()
+                .filter(|b| !blob_path(&root, &b.hash, &b.extension).is_file())
+                .map(|b| b.hash.clone())
+                .collect();
+            let missing_cached: Vec<String> = index
+                .cached
+                .values()
+                .filter(|c| !root.join(&c.file_name).is_file())
+                .map(|c| c.url.clone())
+                .collect();
+            for hash in &missing_blobs {
+                index.blobs.remove(hash);
+                index.server_refs.retain(|_, h| h.as_str() != hash.as_str());
+            }
+            for url in &missing_cached {
+                index.remove_cached(url);
+            }
+            let pruned = missing_blobs.len() + missing_cached.len();
+            if pruned > 0 {
+                self.persist(&root, &index)?;
+            }
+            pruned
+        };
+
+        let orphans = self.find_orphans(pending)?;
+        let mut removed_orphans = 0;
+        let mut freed_bytes: u64 = evicted.iter().map(|c| c.size).sum();
+        if remove_orphans && !orphans.is_empty() {
+            let mut index = self.lock_index()?;
+            for orphan in &orphans {
+                if orphan.kind == OrphanKind::Unreferenced {
+                    if let Some(hash) = Path::new(&orphan.path)
+                        .file_stem()
+                        .and_then(|stem| parse_reference(&stem.to_string_lossy()))
+                    {
+                        index.blobs.remove(&hash);
+                        index.server_refs.retain(|_, h| *h != hash);
+                        if !index.cached_by_hash.contains_key(&hash) {
+                            thumbnails::remove(&root, &hash);
+                        }
+                    }
+                }
+                if fs::remove_file(&orphan.path).is_ok() {
+                    removed_orphans += 1;
+                    freed_bytes += orphan.size;
+                }
+            }
+            self.persist(&root, &index)?;
+            info!("[ImageStore] {} imagens órfãs removidas", removed_orphans);
+        }
+
+        Ok(CleanupReport {
+            evicted_cached: evicted.len(),
+            pruned_entries: pruned,
+            removed_orphans,
+            freed_bytes,
+            orphans,
+        })
+    }
+}
+
+enum Target {
+    Blob(String, PathBuf),
+    Cached(String),
+}
+
+fn target_for_hash(index: &StoreIndex, root: &Path, hash: &str) -> Option<Target> {
+    if let Some(blob) = index.blobs.get(hash) {
+        return Some(Target::Blob(
+            blob.hash.clone(),
+            blob_path(root, &blob.hash, &blob.extension),
+        ));
+    }
+    index
+        .cached_by_hash
+        .get(hash)
+        .map(|url| Target::Cached(url.clone()))
+}
+
+fn remove_quietly(path: &Path) {
+    if let Err(e) = fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!("[ImageStore] Erro ao remover {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Arquivos de `dir` (sem descer nos subdiretórios) com tamanho e data de
+/// modificação
+fn collect_files(dir: &Path, out: &mut Vec<(PathBuf, u64, DateTime<Utc>)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_file() {
+            let modified = meta
+                .modified()
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_else(|_| Utc::now());
+            out.push((entry.path(), meta.len(), modified));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> (ImageStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("sgp-image-store-{}", uuid::Uuid::new_v4()));
+        let store = ImageStore::default();
+        store.set_dir(dir.clone()).unwrap();
+        (store, dir)
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        let hash = hash_bytes(b"arte");
+        assert_eq!(parse_reference(&reference_for(&hash)), Some(hash.clone()));
+        assert_eq!(parse_reference(&hash.to_uppercase()), Some(hash.clone()));
+        assert_eq!(parse_reference("sha256:xyz"), None);
+        assert_eq!(
+            parse_reference("550e8400-e29b-41d4-a716-446655440000.jpg"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_by_any_reference() {
+        let (store, dir) = temp_store();
+        let url = "https://api.exemplo.com/imagens/12?size=full";
+        let (cached, cached_path) = store.put_cached(url, b"baixada", "image/png").unwrap();
+        assert!(cached.file_name.starts_with("cached_"));
+        assert_eq!(store.resolve(url).unwrap(), Some(cached_path.clone()));
+        assert_eq!(
+            store.resolve(&reference_for(&cached.hash)).unwrap(),
+            Some(cached_path.clone())
+        );
+        assert_eq!(store.resolve(&cached.file_name).unwrap(), Some(cached_path));
+
+        let stored = store.put(b"local", "image/jpeg").unwrap();
+        store
+            .link_server_reference(&stored.entry.hash, "uploads/arte-12.jpg")
+            .unwrap();
+        assert_eq!(
+            store.resolve("uploads/arte-12.jpg").unwrap(),
+            Some(stored.path.clone())
+        );
+        assert_eq!(
+            store
+                .resolve(&format!("{}.jpg", stored.entry.hash))
+                .unwrap(),
+            Some(stored.path)
+        );
+        assert_eq!(store.resolve("desconhecida").unwrap(), None);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_migrates_cache_sidecars() {
+        let dir = std::env::temp_dir().join(format!("sgp-image-store-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let image = dir.join("cached_0011223344556677.jpg");
+        fs::write(&image, b"antiga").unwrap();
+        fs::write(
+            dir.join("arte-antiga.cache"),
+            image.to_string_lossy().as_bytes(),
+        )
+        .unwrap();
+
+        let store = ImageStore::default();
+        store.set_dir(dir.clone()).unwrap();
+        assert_eq!(store.resolve("arte-antiga").unwrap(), Some(image));
+        assert!(!dir.join("arte-antiga.cache").exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_lru_eviction_keeps_recently_used() {
+        let (store, dir) = temp_store();
+        store.put_cached("a", &[0u8; 10], "image/png").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let (b, b_path) = store.put_cached("b", &[1u8; 10], "image/png").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(store.resolve("a").unwrap().is_some());
+
+        let evicted = store
+            .set_quota(ImageQuota {
+                max_cache_bytes: 15,
+                ..ImageQuota::default()
+            })
+            .unwrap();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].url, b.url);
+        assert!(!b_path.exists());
+        assert!(store.resolve("a").unwrap().is_some());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_orphans_respect_refs_and_pending_uploads() {
+        let (store, dir) = temp_store();
+        store
+            .set_quota(ImageQuota {
+                orphan_grace_hours: 0,
+                ..ImageQuota::default()
+            })
+            .unwrap();
+        let used = store.put(b"usada", "image/png").unwrap();
+        store.add_ref(&used.entry.hash, "pedido:1:item:1").unwrap();
+        let unused = store.put(b"abandonada", "image/png").unwrap();
+        store
+            .add_ref(&unused.entry.hash, "pedido:2:item:1")
+            .unwrap();
+        store
+            .add_ref(&unused.entry.hash, "pedido:3:item:1")
+            .unwrap();
+        store
+            .release(&unused.entry.hash, "pedido:2:item:1")
+            .unwrap();
+        // Simula o item que ainda segura a imagem sumindo sem soltar a referência
+        store
+            .lock_index()
+            .unwrap()
+            .blobs
+            .get_mut(&unused.entry.hash)
+            .unwrap()
+            .refs
+            .clear();
+        let pending = store.put(b"na fila", "image/png").unwrap();
+        store
+            .add_ref(&pending.entry.hash, "pedido:4:item:1")
+            .unwrap();
+        store
+            .lock_index()
+            .unwrap()
+            .blobs
+            .get_mut(&pending.entry.hash)
+            .unwrap()
+            .refs
+            .clear();
+        let abandoned = store.put(b"nunca anexada", "image/png").unwrap();
+        let legacy = dir.join("550e8400-e29b-41d4-a716-446655440000.jpg");
+        fs::write(&legacy, b"antiga").unwrap();
+        let stale_download = dir.join("cached_0011223344556677.jpg");
+        fs::write(&stale_download, b"baixada").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        let queue = vec![format!(r#"{{"imagem":"{}"}}"#, pending.entry.hash)];
+        let orphans = store.find_orphans(&queue).unwrap();
+        assert_eq!(orphans.len(), 4);
+        assert!(orphans.iter().any(|o| Path::new(&o.path) == unused.path));
+        assert!(orphans
+            .iter()
+            .any(|o| o.kind == OrphanKind::Unreferenced && Path::new(&o.path) == abandoned.path));
+        for loose in [&legacy, &stale_download] {
+            assert!(orphans
+                .iter()
+                .any(|o| o.kind == OrphanKind::Untracked && Path::new(&o.path) == loose));
+        }
+
+        let report = store.cleanup(true, &queue).unwrap();
+        assert_eq!(report.removed_orphans, 4);
+        assert!(!unused.path.exists() && !abandoned.path.exists());
+        assert!(!legacy.exists() && !stale_download.exists());
+        assert!(used.path.exists() && pending.path.exists());
+        assert!(dir.join("index.json").exists() && dir.join("quota.json").exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_put_deduplicates_and_release_keeps_file() {
+        let (store, dir) = temp_store();
+        let first = store.put(b"mesma arte", "image/png").unwrap();
+        let second = store.put(b"mesma arte", "image/png").unwrap();
+        assert!(!first.deduplicated);
+        assert!(second.deduplicated);
+        assert_eq!(first.path, second.path);
+
+        let hash = first.entry.hash.clone();
+        assert_eq!(store.add_ref(&hash, "pedido:1:item:1").unwrap(), 1);
+        assert_eq!(store.add_ref(&hash, "pedido:1:item:2").unwrap(), 2);
+        assert_eq!(store.add_ref(&hash, "pedido:1:item:2").unwrap(), 2);
+
+        // Quem nunca segurou a imagem não mexe nas referências
+        assert_eq!(store.release(&hash, "pedido:9:item:9").unwrap(), 2);
+        assert_eq!(store.release(&hash, "pedido:1:item:1").unwrap(), 1);
+        assert!(first.path.exists());
+        assert_eq!(store.release(&hash, "pedido:1:item:2").unwrap(), 0);
+
+        // Sem itens a imagem continua no disco até passar o prazo de órfã
+        let entry = store.entry(&hash).unwrap().unwrap();
+        assert!(entry.refs.is_empty() && entry.idle_since.is_some());
+        assert!(first.path.exists());
+        assert!(store.find_orphans(&[]).unwrap().is_empty());
+
+        store
+            .set_quota(ImageQuota {
+                orphan_grace_hours: 0,
+                ..ImageQuota::default()
+            })
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(store.cleanup(true, &[]).unwrap().removed_orphans, 1);
+        assert!(!first.path.exists());
+        assert!(store.entry(&hash).unwrap().is_none());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_release_of_unselected_art_keeps_it() {
+        let (store, dir) = temp_store();
+        let art = store.put(b"no formulario", "image/png").unwrap();
+
+        assert_eq!(
+            store.release(&art.entry.hash, "pedido:5:item:1").unwrap(),
+            0
+        );
+        assert!(art.path.exists());
+        assert!(store.entry(&art.entry.hash).unwrap().is_some());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_orphans_wait_for_grace_period() {
+        let (store, dir) = temp_store();
+        let abandoned = store.put(b"recem escolhida", "image/png").unwrap();
+        fs::write(
+            dir.join("550e8400-e29b-41d4-a716-446655440000.jpg"),
+            b"antiga",
+        )
+        .unwrap();
+
+        assert!(store.find_orphans(&[]).unwrap().is_empty());
+        assert!(abandoned.path.exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -3,18 +3,61 @@
 mod commands;
 mod state;
 mod pdf_generator;
+mod bridge;
+mod error;
+mod http_client;
+mod image_processing;
+mod image_protocol;
+mod image_store;
+mod pdf_preview;
+mod pedidos;
+mod profiles;
+mod sandbox;
+mod storage;
+mod thumbnails;
+mod vault;
 
 #[cfg(debug_assertions)]
 use commands::devtools::{
     close_devtools, is_devtools_open, open_devtools, test_devtools_system, toggle_devtools,
 };
 use commands::images::{
-    cache_image_from_url, get_local_image_path, load_local_image_as_base64, process_and_save_image,
-    read_image_file, save_image_locally,
+    cache_image_from_url, get_local_image_path, image_add_reference, image_best_variant,
+    image_link_server_reference, image_quota_get, image_quota_set, image_references,
+    image_release_reference, image_storage_cleanup, image_storage_usage,
+    load_local_image_as_base64, pick_image_file, process_and_save_image, read_image_file,
+    revoke_file_grant, save_image_locally,
 };
 use commands::pdf::generate_production_pdf;
 use commands::update::{fetch_changelog, get_app_version};
-use commands::api::{set_api_config, rust_api_get, rust_api_mutate};
+use commands::api::{
+    api_policy_violations, auth_login, cancel_request, clear_api_cache, get_api_policy,
+    get_cache_ttls, get_http_client_config, get_retry_policy, rust_api_get, rust_api_get_cached,
+    rust_api_get_paged, rust_api_mutate, set_api_config, set_cache_ttls, set_http_client_config,
+    set_refresh_config, set_retry_policy,
+};
+use commands::diagnostics::run_network_diagnostics;
+use commands::inspector::{
+    network_inspector_clear, network_inspector_config, network_inspector_configure,
+    network_inspector_entries, network_inspector_export,
+};
+use commands::pedidos::{
+    pedido_change_status, pedido_create, pedido_draft_promote, pedido_draft_save,
+    pedido_draft_update, pedido_get, pedido_update, pedidos_drafts_list, pedidos_list,
+};
+use commands::presence::{presence_configure, presence_status, send_heartbeat};
+use commands::profiles::{
+    profile_activate, profile_delete, profile_health, profile_save, profiles_list,
+};
+use commands::queue::{
+    offline_queue_discard, offline_queue_list, offline_queue_replay, offline_queue_status,
+};
+use commands::realtime::{
+    realtime_connect, realtime_disconnect, realtime_send, realtime_status, realtime_subscribe,
+    realtime_unsubscribe,
+};
+use commands::transfer::{cancel_transfer, rust_api_download, rust_api_upload};
+use commands::vault::{vault_load, vault_rotate_token, vault_store, vault_wipe};
 use tauri::Manager;
 use tracing::{info, warn};
 
@@ -39,6 +82,8 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        // Imagens locais servidas direto para <img>, sem base64 pelo IPC
+        .register_asynchronous_uri_scheme_protocol(image_protocol::SCHEME, image_protocol::handle)
         .invoke_handler(tauri::generate_handler![
             #[cfg(debug_assertions)]
             open_devtools,
@@ -59,11 +104,85 @@ fn main() {
             read_image_file,
             cache_image_from_url,
             process_and_save_image,
+            image_add_reference,
+            image_release_reference,
+            image_references,
+            image_link_server_reference,
+            image_storage_usage,
+            image_storage_cleanup,
+            image_quota_get,
+            image_quota_set,
+            pick_image_file,
+            revoke_file_grant,
+            image_best_variant,
             // Comando de geração de PDF
             generate_production_pdf,
             set_api_config,
+            auth_login,
             rust_api_get,
             rust_api_mutate,
+            set_retry_policy,
+            get_retry_policy,
+            rust_api_get_cached,
+            rust_api_get_paged,
+            cancel_request,
+            get_api_policy,
+            api_policy_violations,
+            set_cache_ttls,
+            get_cache_ttls,
+            clear_api_cache,
+            set_refresh_config,
+            get_http_client_config,
+            set_http_client_config,
+            // Fila offline de mutações
+            offline_queue_status,
+            offline_queue_list,
+            offline_queue_discard,
+            offline_queue_replay,
+            // Cofre de credenciais
+            vault_store,
+            vault_load,
+            vault_rotate_token,
+            vault_wipe,
+            // Perfis de conexão
+            profiles_list,
+            profile_save,
+            profile_activate,
+            profile_delete,
+            profile_health,
+            // Upload/download em streaming
+            rust_api_upload,
+            rust_api_download,
+            cancel_transfer,
+            // Pedidos (cliente tipado)
+            pedidos_list,
+            pedido_get,
+            pedido_create,
+            pedido_update,
+            pedido_change_status,
+            pedido_draft_save,
+            pedido_draft_update,
+            pedidos_drafts_list,
+            pedido_draft_promote,
+            // WebSocket de eventos de pedidos
+            realtime_connect,
+            realtime_disconnect,
+            realtime_status,
+            realtime_subscribe,
+            realtime_unsubscribe,
+            realtime_send,
+            // Presença
+            send_heartbeat,
+            presence_status,
+            presence_configure,
+            // Diagnóstico de rede
+            run_network_diagnostics,
+            // Inspetor de rede
+            network_inspector_config,
+            network_inspector_configure,
+            network_inspector_entries,
+            network_inspector_clear,
+            network_inspector_export,
         ])
         .setup(|app| {
             let version = env!("CARGO_PKG_VERSION");
@@ -82,8 +201,59 @@ fn main() {
                 info!("Título da janela definido: {}", title);
             }
 
+            // Fila offline e cache de respostas vivem no diretório de dados do app
+            match app.path().app_data_dir() {
+                Ok(dir) => {
+                    let state = app.state::<state::AppState>();
+                    if let Err(e) = state.http.load(dir.join("http_client.json")) {
+                        warn!("Erro ao carregar configuração HTTP: {}", e);
+                    }
+                    if let Err(e) = state.policy.load(dir.join("api_policy.json")) {
+                        warn!("Erro ao carregar política de endpoints: {}", e);
+                    }
+                    if let Err(e) = state.offline_queue.load(dir.join("offline_queue.json")) {
+                        warn!("Erro ao carregar fila offline: {}", e);
+                    }
+                    if let Err(e) = state.response_cache.set_dir(dir.join("http_cache")) {
+                        warn!("Erro ao configurar cache de respostas: {}", e);
+                    }
+                    if let Err(e) = state.images.set_dir(dir.join("images")) {
+                        warn!("Erro ao carregar armazenamento de imagens: {}", e);
+                    }
+                    if let Err(e) = state.vault.set_path(dir.join("vault.json")) {
+                        warn!("Erro ao configurar cofre de credenciais: {}", e);
+                    }
+                    if let Err(e) = state.profiles.load(dir.join("profiles.json")) {
+                        warn!("Erro ao carregar perfis de conexão: {}", e);
+                    }
+                    if let Ok(Some(profile)) = state.profiles.active() {
+                        if let Err(e) = commands::profiles::apply_profile(&state, &profile) {
+                            warn!("Erro ao aplicar perfil ativo: {}", e);
+                        }
+                    }
+                }
+                Err(e) => warn!("Erro ao obter diretório de dados do app: {}", e),
+            }
+            app.state::<state::AppState>()
+                .session
+                .attach(app.handle().clone());
+            app.state::<state::AppState>()
+                .inspector
+                .attach(app.handle().clone());
+            app.state::<state::AppState>()
+                .policy
+                .attach(app.handle().clone());
+            let realtime = &app.state::<state::AppState>().realtime;
+            realtime.attach(app.handle().clone());
+            realtime.start();
+            app.state::<state::AppState>()
+                .presence
+                .attach(app.handle().clone());
+            bridge::presence::spawn_heartbeat_task(app.handle().clone());
+            bridge::queue::spawn_replay_task(app.handle().clone());
+
             info!("Janela principal pronta: {:?}", app.get_webview_window("main").is_some());
-            info!("Backend Rust apenas inicializa a interface. Toda comunicação de rede acontece no frontend.");
+            info!("Ponte de rede do core pronta; chamadas à API passam por `rust_api_*`.");
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,154 @@
+//! Rasterização da primeira página de PDFs enviados como arte.
+//!
+//! Usa o mesmo Chrome headless do gerador de PDF: o arquivo é aberto no
+//! visualizador embutido, sem barra de ferramentas, e a janela é capturada
+//! como PNG. O visualizador de PDF só existe no modo headless novo do
+//! Chrome, por isso ele é ligado por argumento em vez de `headless: true`.
+//!
+//! O PDF vem de fora (cliente, designer), então o Chrome roda com o sandbox
+//! ligado. Não há evento de "página desenhada" no visualizador: a captura
+//! vale quando duas seguidas saem iguais e com conteúdo, o que dispensa um
+//! tempo fixo de espera.
+
+use crate::error::{AppError, AppResult};
+use headless_chrome::protocol::cdp::Page::CaptureScreenshotFormatOption;
+use headless_chrome::{Browser, LaunchOptions};
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Janela na proporção do A4, ~150 dpi na largura
+const WINDOW_SIZE: (u32, u32) = (1240, 1754);
+
+/// Tempo máximo para o visualizador desenhar a página
+const RENDER_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Intervalo entre capturas enquanto a página é desenhada
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Parâmetros do visualizador: só a primeira página, inteira na janela
+const VIEWER_FRAGMENT: &str = "#page=1&toolbar=0&navpanes=0&scrollbar=0&view=Fit";
+
+/// Verifica a assinatura `%PDF-` no início do arquivo
+pub fn is_pdf(path: &Path) -> bool {
+    use std::io::Read;
+    let mut magic = [0u8; 5];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && &magic == b"%PDF-"
+}
+
+fn file_url(path: &Path) -> String {
+    let raw = path.to_string_lossy().replace('\\', "/");
+    // canonicalize no Windows devolve \\?\C:\...
+    let raw = raw.trim_start_matches("//?/").trim_start_matches('/');
+    let encoded: Vec<String> = raw
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).replace("%3A", ":"))
+        .collect();
+    format!("file:///{}{}", encoded.join("/"), VIEWER_FRAGMENT)
+}
+
+fn chrome_error(e: impl std::fmt::Display) -> AppError {
+    AppError::internal(format!("Erro ao renderizar PDF: {}", e))
+}
+
+/// Indica se a captura já mostra a página: enquanto o visualizador carrega,
+/// a janela é só o fundo, de uma cor
+fn has_content(png: &[u8]) -> bool {
+    let Ok(img) = image::load_from_memory_with_format(png, image::ImageFormat::Png) else {
+        return false;
+    };
+    let img = img.to_rgb8();
+    let Some(first) = img.pixels().next().copied() else {
+        return false;
+    };
+    img.pixels().any(|pixel| *pixel != first)
+}
+
+/// Renderiza a primeira página de `pdf` e devolve o PNG
+pub fn render_first_page(pdf: &Path) -> AppResult<Vec<u8>> {
+    let pdf = pdf
+        .canonicalize()
+        .map_err(|e| AppError::io(format!("PDF não encontrado: {}", e)))?;
+
+    let browser = Browser::new(LaunchOptions {
+        headless: false,
+        sandbox: true,
+        window_size: Some(WINDOW_SIZE),
+        args: vec![
+            OsStr::new("--headless=new"),
+            OsStr::new("--hide-scrollbars"),
+        ],
+        ..Default::default()
+    })
+    .map_err(chrome_error)?;
+
+    let tab = browser.new_tab().map_err(chrome_error)?;
+    tab.navigate_to(&file_url(&pdf)).map_err(chrome_error)?;
+    tab.wait_until_navigated().map_err(chrome_error)?;
+    tab.wait_for_element_with_custom_timeout("embed", RENDER_TIMEOUT)
+        .map_err(chrome_error)?;
+
+    let deadline = Instant::now() + RENDER_TIMEOUT;
+    let mut previous: Option<Vec<u8>> = None;
+    while Instant::now() < deadline {
+        let capture = tab
+            .capture_screenshot(CaptureScreenshotFormatOption::Png, None, None, true)
+            .map_err(chrome_error)?;
+        if previous.as_deref() == Some(capture.as_slice()) && has_content(&capture) {
+            return Ok(capture);
+        }
+        previous = Some(capture);
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    Err(AppError::internal(format!(
+        "O visualizador não desenhou o PDF em {}s",
+        RENDER_TIMEOUT.as_secs()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_url() {
+        assert_eq!(
+            file_url(Path::new("/tmp/arte final.pdf")),
+            format!("file:///tmp/arte%20final.pdf{}", VIEWER_FRAGMENT)
+        );
+        assert_eq!(
+            file_url(Path::new(r"\\?\C:\Artes\lona.pdf")),
+            format!("file:///C:/Artes/lona.pdf{}", VIEWER_FRAGMENT)
+        );
+    }
+
+    #[test]
+    fn test_render_first_page_of_real_pdf() {
+        let html = r#"
+            <!DOCTYPE html>
+            <html>
+            <head><style>@page { size: A4; margin: 0; } body { margin: 0; }</style></head>
+            <body><div style="width: 50%; height: 50vh; background: #000"></div></body>
+            </html>
+        "#;
+        let pdf = crate::pdf_generator::PdfGenerator::new()
+            .unwrap()
+            .generate_from_html(html)
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("sgp-preview-{}.pdf", uuid::Uuid::new_v4()));
+        std::fs::write(&path, &pdf).unwrap();
+        assert!(is_pdf(&path));
+
+        let png = render_first_page(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let img = image::load_from_memory(&png).unwrap().to_rgb8();
+        assert_eq!(img.dimensions(), WINDOW_SIZE);
+        // O retângulo preto da página aparece na captura
+        assert!(img.pixels().any(|p| p.0.iter().all(|c| *c < 32)));
+        assert!(has_content(&png));
+    }
+}
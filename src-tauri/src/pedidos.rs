@@ -0,0 +1,528 @@
+//! Cliente tipado da API de pedidos (`/pedidos`).
+//!
+//! Os comandos em `commands/pedidos.rs` recebem e devolvem estas structs em
+//! vez de endpoints em texto e `serde_json::Value`. Payloads são validados
+//! aqui antes de sair; respostas fora do contrato viram erro `parse` em vez de
+//! chegar quebradas ao frontend. Campos de item que a API acrescentar e que
+//! ainda não foram mapeados são preservados em `extra`.
+
+use crate::bridge::{self, inflight, ApiRequest};
+use crate::error::{AppError, AppResult};
+use crate::state::AppState;
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+const BASE: &str = "/pedidos/";
+const MAX_PAGE_SIZE: u32 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PedidoStatus {
+    Pendente,
+    EmProducao,
+    Pronto,
+    Entregue,
+    Cancelado,
+}
+
+impl PedidoStatus {
+    fn as_api(self) -> &'static str {
+        match self {
+            Self::Pendente => "pendente",
+            Self::EmProducao => "em_producao",
+            Self::Pronto => "pronto",
+            Self::Entregue => "entregue",
+            Self::Cancelado => "cancelado",
+        }
+    }
+}
+
+/// A API devolve valores decimais como texto, mas versões antigas mandam número
+fn lenient_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(s)) => Some(s),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        Some(Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Acabamento {
+    pub overloque: Option<bool>,
+    pub elastico: Option<bool>,
+    pub ilhos: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PedidoItem {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub tipo_producao: Option<String>,
+    pub descricao: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub largura: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub altura: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub metro_quadrado: Option<String>,
+    pub vendedor: Option<String>,
+    pub designer: Option<String>,
+    pub tecido: Option<String>,
+    pub tipo_acabamento: Option<String>,
+    pub acabamento: Option<Acabamento>,
+    pub observacao: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub valor_unitario: Option<String>,
+    pub imagem: Option<String>,
+    pub legenda_imagem: Option<String>,
+    pub data_impressao: Option<String>,
+    pub machine_id: Option<i64>,
+    /// Campos específicos de cada tipo de produção (totem, lona, adesivo...)
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pedido {
+    pub id: i64,
+    pub numero: Option<String>,
+    pub data_entrada: Option<String>,
+    pub data_entrega: Option<String>,
+    pub observacao: Option<String>,
+    pub prioridade: Option<String>,
+    pub status: PedidoStatus,
+    pub cliente: String,
+    pub telefone_cliente: Option<String>,
+    pub cidade_cliente: Option<String>,
+    pub estado_cliente: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub valor_total: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub valor_frete: Option<String>,
+    #[serde(default, deserialize_with = "lenient_string")]
+    pub valor_itens: Option<String>,
+    pub tipo_pagamento: Option<String>,
+    pub obs_pagamento: Option<String>,
+    pub forma_envio: Option<String>,
+    pub forma_envio_id: Option<i64>,
+    pub financeiro: Option<bool>,
+    pub conferencia: Option<bool>,
+    pub sublimacao: Option<bool>,
+    pub costura: Option<bool>,
+    pub expedicao: Option<bool>,
+    pub sublimacao_maquina: Option<String>,
+    pub sublimacao_data_impressao: Option<String>,
+    pub financeiro_liberado_em: Option<String>,
+    pub data_criacao: Option<String>,
+    pub ultima_atualizacao: Option<String>,
+    #[serde(default)]
+    pub items: Vec<PedidoItem>,
+}
+
+/// Corpo de criação/edição. Na edição, campos `None` não são enviados.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PedidoPayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numero: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cliente: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telefone_cliente: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cidade_cliente: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estado_cliente: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_entrada: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_entrega: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observacao: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prioridade: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<PedidoStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valor_frete: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valor_itens: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valor_total: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tipo_pagamento: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub obs_pagamento: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forma_envio: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forma_envio_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<PedidoItem>>,
+}
+
+/// Mudança de status e das etapas de produção (PATCH parcial)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusChange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<PedidoStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pronto: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conferencia: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sublimacao: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub costura: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expedicao: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sublimacao_maquina: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sublimacao_data_impressao: Option<String>,
+    /// Liberação financeira; só aceita em sessões de administrador
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub financeiro: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PedidoFiltro {
+    pub status: Option<PedidoStatus>,
+    pub cliente: Option<String>,
+    /// `YYYY-MM-DD`
+    pub data_inicio: Option<String>,
+    /// `YYYY-MM-DD`
+    pub data_fim: Option<String>,
+    pub tipo_producao: Option<String>,
+    pub is_pronto: Option<bool>,
+    /// Qual data o intervalo filtra (entrada, entrega...), repassado à API
+    pub date_mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Paginacao {
+    /// Começa em 1
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Default for Paginacao {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            page_size: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PedidosPage {
+    pub pedidos: Vec<Pedido>,
+    pub page: u32,
+    pub page_size: u32,
+    pub has_next: bool,
+}
+
+fn validate_date(field: &str, value: &Option<String>) -> AppResult<()> {
+    if let Some(date) = value {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            AppError::validation(format!("{} inválida (use AAAA-MM-DD): {}", field, date))
+        })?;
+    }
+    Ok(())
+}
+
+fn validate_id(id: i64) -> AppResult<()> {
+    if id <= 0 {
+        return Err(AppError::validation(format!(
+            "ID de pedido inválido: {}",
+            id
+        )));
+    }
+    Ok(())
+}
+
+impl PedidoFiltro {
+    fn to_params(&self, pagina: Paginacao) -> AppResult<HashMap<String, String>> {
+        validate_date("data_inicio", &self.data_inicio)?;
+        validate_date("data_fim", &self.data_fim)?;
+        if pagina.page == 0 || pagina.page_size == 0 || pagina.page_size > MAX_PAGE_SIZE {
+            return Err(AppError::validation(format!(
+                "Paginação inválida (página a partir de 1, tamanho entre 1 e {})",
+                MAX_PAGE_SIZE
+            )));
+        }
+
+        let mut params = HashMap::new();
+        let skip = (pagina.page - 1) as u64 * pagina.page_size as u64;
+        params.insert("skip".to_string(), skip.to_string());
+        // Um item a mais indica se existe próxima página
+        params.insert("limit".to_string(), (pagina.page_size + 1).to_string());
+        if let Some(status) = self.status {
+            params.insert("status".to_string(), status.as_api().to_string());
+        }
+        let optional = [
+            ("cliente", &self.cliente),
+            ("data_inicio", &self.data_inicio),
+            ("data_fim", &self.data_fim),
+            ("tipo_producao", &self.tipo_producao),
+            ("date_mode", &self.date_mode),
+        ];
+        for (name, value) in optional {
+            if let Some(v) = value.as_ref().filter(|v| !v.trim().is_empty()) {
+                params.insert(name.to_string(), v.clone());
+            }
+        }
+        if let Some(pronto) = self.is_pronto {
+            params.insert("is_pronto".to_string(), pronto.to_string());
+        }
+        Ok(params)
+    }
+}
+
+impl PedidoPayload {
+    fn validate_common(&self) -> AppResult<()> {
+        validate_date("data_entrada", &self.data_entrada)?;
+        validate_date("data_entrega", &self.data_entrega)
+    }
+
+    /// Pedido completo: cliente, data de entrega e ao menos um item
+    fn validate_for_create(&self) -> AppResult<()> {
+        self.validate_common()?;
+        if self.cliente.as_deref().is_none_or(|c| c.trim().is_empty()) {
+            return Err(AppError::validation("Pedido precisa de cliente"));
+        }
+        if self.data_entrega.is_none() {
+            return Err(AppError::validation("Pedido precisa de data de entrega"));
+        }
+        if self.items.as_ref().is_none_or(|items| items.is_empty()) {
+            return Err(AppError::validation("Pedido precisa de ao menos um item"));
+        }
+        Ok(())
+    }
+}
+
+fn decode<T: DeserializeOwned>(endpoint: &str, value: Value) -> AppResult<T> {
+    serde_json::from_value(value)
+        .map_err(|e| AppError::parse(format!("Resposta inesperada de {}: {}", endpoint, e)))
+}
+
+fn to_body<T: Serialize>(payload: &T) -> AppResult<Value> {
+    Ok(serde_json::to_value(payload)?)
+}
+
+async fn get<T: DeserializeOwned>(
+    state: &AppState,
+    endpoint: String,
+    params: Option<HashMap<String, String>>,
+) -> AppResult<T> {
+    let request = ApiRequest::get(endpoint, params);
+    let response = inflight::coalesced_get(state, &request, false).await?;
+    decode(&request.endpoint, response.data)
+}
+
+async fn mutate<T: DeserializeOwned>(
+    state: &AppState,
+    method: &str,
+    endpoint: String,
+    body: Option<Value>,
+    idempotency_key: Option<String>,
+) -> AppResult<T> {
+    let request = ApiRequest::mutation(method, endpoint, body, idempotency_key)?;
+    let value = bridge::execute(state, &request).await?;
+    state.response_cache.invalidate_resource(&request.endpoint);
+    decode(&request.endpoint, value)
+}
+
+pub async fn list(
+    state: &AppState,
+    filtro: &PedidoFiltro,
+    pagina: Paginacao,
+) -> AppResult<PedidosPage> {
+    let params = filtro.to_params(pagina)?;
+    let mut pedidos: Vec<Pedido> = get(state, BASE.to_string(), Some(params)).await?;
+    let has_next = pedidos.len() > pagina.page_size as usize;
+    pedidos.truncate(pagina.page_size as usize);
+    Ok(PedidosPage {
+        pedidos,
+        page: pagina.page,
+        page_size: pagina.page_size,
+        has_next,
+    })
+}
+
+pub async fn get_by_id(state: &AppState, id: i64) -> AppResult<Pedido> {
+    validate_id(id)?;
+    get(state, format!("/pedidos/{}", id), None).await
+}
+
+pub async fn create(
+    state: &AppState,
+    payload: &PedidoPayload,
+    idempotency_key: Option<String>,
+) -> AppResult<Pedido> {
+    payload.validate_for_create()?;
+    mutate(
+        state,
+        "POST",
+        BASE.to_string(),
+        Some(to_body(payload)?),
+        idempotency_key,
+    )
+    .await
+}
+
+pub async fn update(state: &AppState, id: i64, payload: &PedidoPayload) -> AppResult<Pedido> {
+    validate_id(id)?;
+    payload.validate_common()?;
+    if payload.items.as_ref().is_some_and(|items| items.is_empty()) {
+        return Err(AppError::validation(
+            "Pedido não pode ficar sem itens; use o cancelamento",
+        ));
+    }
+    mutate(
+        state,
+        "PATCH",
+        format!("/pedidos/{}", id),
+        Some(to_body(payload)?),
+        None,
+    )
+    .await
+}
+
+pub async fn change_status(state: &AppState, id: i64, change: &StatusChange) -> AppResult<Pedido> {
+    validate_id(id)?;
+    if change.financeiro.is_some() && state.session.role().as_deref() != Some("admin") {
+        return Err(AppError::permission(
+            "Somente administradores alteram o status financeiro",
+        ));
+    }
+    let body = to_body(change)?;
+    if body.as_object().is_some_and(|o| o.is_empty()) {
+        return Err(AppError::validation(
+            "Nenhuma alteração de status informada",
+        ));
+    }
+    mutate(state, "PATCH", format!("/pedidos/{}", id), Some(body), None).await
+}
+
+/// Rascunhos não passam pelas validações de pedido completo
+fn draft_body(payload: &PedidoPayload) -> AppResult<Value> {
+    payload.validate_common()?;
+    let mut body = to_body(payload)?;
+    if let Some(object) = body.as_object_mut() {
+        object.insert("rascunho".to_string(), Value::Bool(true));
+    }
+    Ok(body)
+}
+
+pub async fn save_draft(
+    state: &AppState,
+    payload: &PedidoPayload,
+    idempotency_key: Option<String>,
+) -> AppResult<Pedido> {
+    let body = draft_body(payload)?;
+    mutate(
+        state,
+        "POST",
+        "/pedidos/rascunho".to_string(),
+        Some(body),
+        idempotency_key,
+    )
+    .await
+}
+
+pub async fn update_draft(state: &AppState, id: i64, payload: &PedidoPayload) -> AppResult<Pedido> {
+    validate_id(id)?;
+    let body = draft_body(payload)?;
+    mutate(state, "PATCH", format!("/pedidos/{}", id), Some(body), None).await
+}
+
+pub async fn list_drafts(state: &AppState) -> AppResult<Vec<Pedido>> {
+    get(state, "/pedidos/rascunhos".to_string(), None).await
+}
+
+/// Transforma o rascunho em pedido de produção (a API aplica as validações completas)
+pub async fn promote_draft(state: &AppState, id: i64) -> AppResult<Pedido> {
+    validate_id(id)?;
+    mutate(
+        state,
+        "POST",
+        format!("/pedidos/{}/promover", id),
+        None,
+        None,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_pedido() {
+        let pedido: Pedido = decode(
+            "/pedidos/1",
+            json!({
+                "id": 1,
+                "status": "em_producao",
+                "cliente": "Loja Centro",
+                "valor_total": 150.5,
+                "items": [{
+                    "tipo_producao": "totem",
+                    "valor_unitario": "75.25",
+                    "quantidade_totem": "2"
+                }]
+            }),
+        )
+        .unwrap();
+        assert_eq!(pedido.status, PedidoStatus::EmProducao);
+        assert_eq!(pedido.valor_total.as_deref(), Some("150.5"));
+        assert_eq!(pedido.items[0].extra["quantidade_totem"], "2");
+
+        let invalid: AppResult<Pedido> = decode("/pedidos/1", json!({ "id": 1, "status": "?" }));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_filter_params() {
+        let filtro = PedidoFiltro {
+            status: Some(PedidoStatus::Pronto),
+            cliente: Some(" ".to_string()),
+            data_inicio: Some("2024-03-01".to_string()),
+            ..Default::default()
+        };
+        let params = filtro
+            .to_params(Paginacao {
+                page: 3,
+                page_size: 20,
+            })
+            .unwrap();
+        assert_eq!(params["skip"], "40");
+        assert_eq!(params["limit"], "21");
+        assert_eq!(params["status"], "pronto");
+        assert!(!params.contains_key("cliente"));
+
+        let bad_date = PedidoFiltro {
+            data_fim: Some("01/03/2024".to_string()),
+            ..Default::default()
+        };
+        assert!(bad_date.to_params(Paginacao::default()).is_err());
+    }
+
+    #[test]
+    fn test_create_validation() {
+        let mut payload = PedidoPayload {
+            cliente: Some("Loja Centro".to_string()),
+            data_entrega: Some("2024-03-10".to_string()),
+            ..Default::default()
+        };
+        assert!(payload.validate_for_create().is_err());
+        payload.items = Some(vec![PedidoItem::default()]);
+        assert!(payload.validate_for_create().is_ok());
+    }
+}
@@ -0,0 +1,153 @@
+//! Perfis de conexão nomeados (produção, túnel de staging, instância local...).
+//!
+//! Os perfis ficam cifrados em `<app_data>/profiles.json` (via [`vault::seal`])
+//! porque guardam o token de cada servidor.
+
+use crate::error::{AppError, AppResult};
+use crate::vault;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiProfile {
+    pub id: String,
+    pub name: String,
+    pub base_url: String,
+    pub token: Option<String>,
+    /// Timeout por requisição; `None` usa o padrão do cliente HTTP
+    pub timeout_secs: Option<u64>,
+    /// Headers extras enviados em toda chamada (ex: bypass do ngrok)
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// Visão do perfil entregue ao frontend, sem o token
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSummary {
+    pub id: String,
+    pub name: String,
+    pub base_url: String,
+    pub has_token: bool,
+    pub timeout_secs: Option<u64>,
+    pub headers: HashMap<String, String>,
+    pub active: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfilesFile {
+    active_id: Option<String>,
+    profiles: Vec<ApiProfile>,
+}
+
+#[derive(Default)]
+pub struct ProfileStore {
+    path: Mutex<Option<PathBuf>>,
+    data: Mutex<ProfilesFile>,
+}
+
+impl ProfileStore {
+    /// Carrega os perfis gravados. Chamado no `setup`.
+    pub fn load(&self, path: PathBuf) -> AppResult<()> {
+        let file: ProfilesFile = vault::unseal(&path)?.unwrap_or_default();
+        *self.lock_data()? = file;
+        *self
+            .path
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no caminho dos perfis"))? = Some(path);
+        Ok(())
+    }
+
+    fn lock_data(&self) -> AppResult<std::sync::MutexGuard<'_, ProfilesFile>> {
+        self.data
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex nos perfis"))
+    }
+
+    fn persist(&self, data: &ProfilesFile) -> AppResult<()> {
+        let path = self
+            .path
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no caminho dos perfis"))?
+            .clone()
+            .ok_or_else(|| AppError::config("Perfis ainda não inicializados"))?;
+        vault::seal(&path, data)
+    }
+
+    pub fn list(&self) -> AppResult<Vec<ProfileSummary>> {
+        let data = self.lock_data()?;
+        Ok(data
+            .profiles
+            .iter()
+            .map(|p| ProfileSummary {
+                id: p.id.clone(),
+                name: p.name.clone(),
+                base_url: p.base_url.clone(),
+                has_token: p.token.is_some(),
+                timeout_secs: p.timeout_secs,
+                headers: p.headers.clone(),
+                active: data.active_id.as_deref() == Some(p.id.as_str()),
+            })
+            .collect())
+    }
+
+    pub fn get(&self, id: &str) -> AppResult<ApiProfile> {
+        self.lock_data()?
+            .profiles
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::validation(format!("Perfil não encontrado: {}", id)))
+    }
+
+    pub fn active(&self) -> AppResult<Option<ApiProfile>> {
+        let data = self.lock_data()?;
+        Ok(data
+            .active_id
+            .as_ref()
+            .and_then(|id| data.profiles.iter().find(|p| &p.id == id))
+            .cloned())
+    }
+
+    /// Insere ou substitui (pelo `id`) um perfil
+    pub fn save(&self, profile: ApiProfile) -> AppResult<()> {
+        if profile.name.trim().is_empty() || profile.base_url.trim().is_empty() {
+            return Err(AppError::validation("Perfil precisa de nome e URL base"));
+        }
+
+        let mut data = self.lock_data()?;
+        match data.profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => *existing = profile,
+            None => data.profiles.push(profile),
+        }
+        self.persist(&data)
+    }
+
+    pub fn set_active(&self, id: &str) -> AppResult<ApiProfile> {
+        let mut data = self.lock_data()?;
+        let profile = data
+            .profiles
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::validation(format!("Perfil não encontrado: {}", id)))?;
+        data.active_id = Some(id.to_string());
+        self.persist(&data)?;
+        Ok(profile)
+    }
+
+    pub fn delete(&self, id: &str) -> AppResult<bool> {
+        let mut data = self.lock_data()?;
+        let before = data.profiles.len();
+        data.profiles.retain(|p| p.id != id);
+        let removed = data.profiles.len() != before;
+        if data.active_id.as_deref() == Some(id) {
+            data.active_id = None;
+        }
+        if removed {
+            self.persist(&data)?;
+        }
+        Ok(removed)
+    }
+}
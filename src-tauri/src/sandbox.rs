@@ -0,0 +1,157 @@
+//! Controle de quais arquivos locais o WebView pode ler.
+//!
+//! Só são liberados arquivos dentro do diretório de imagens do app ou
+//! arquivos que o próprio usuário escolheu no diálogo nativo, identificados
+//! por um token emitido no momento da escolha. Os caminhos são
+//! canonicalizados (o que resolve links simbólicos) antes da comparação, e
+//! caminhos com `..` são recusados de saída.
+
+use crate::error::{AppError, AppResult};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Arquivos escolhidos pelo usuário no diálogo, por token
+#[derive(Default)]
+pub struct FileGrants {
+    grants: Mutex<HashMap<String, PathBuf>>,
+}
+
+impl FileGrants {
+    /// Registra um arquivo escolhido pelo usuário e devolve o token de acesso
+    pub fn grant(&self, path: &Path) -> AppResult<String> {
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| AppError::io(format!("Arquivo não encontrado: {}", e)))?;
+        if !canonical.is_file() {
+            return Err(AppError::validation("Só arquivos podem ser liberados"));
+        }
+        let token = uuid::Uuid::new_v4().to_string();
+        self.grants
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex nas permissões de arquivo"))?
+            .insert(token.clone(), canonical);
+        Ok(token)
+    }
+
+    pub fn revoke(&self, token: &str) -> bool {
+        self.grants
+            .lock()
+            .map(|mut grants| grants.remove(token).is_some())
+            .unwrap_or(false)
+    }
+
+    fn granted(&self, token: &str) -> Option<PathBuf> {
+        self.grants.lock().ok()?.get(token).cloned()
+    }
+}
+
+fn denied(requested: &str) -> AppError {
+    warn!("[Sandbox] Acesso negado a {}", requested);
+    AppError::permission("Acesso a este arquivo não é permitido")
+}
+
+/// Valida `requested` e devolve o caminho canônico a ser lido. Aceita
+/// arquivos dentro de `root` ou o arquivo liberado por `grant_token`.
+pub fn authorize(
+    root: &Path,
+    requested: &str,
+    grants: &FileGrants,
+    grant_token: Option<&str>,
+) -> AppResult<PathBuf> {
+    let path = Path::new(requested);
+    if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+        return Err(denied(requested));
+    }
+
+    let canonical = path.canonicalize().map_err(|_| denied(requested))?;
+    if !canonical.is_file() {
+        return Err(denied(requested));
+    }
+
+    // `root` também é canonicalizado: em alguns sistemas o app data já passa
+    // por um link (ex: /var -> /private/var no macOS)
+    if let Ok(root) = root.canonicalize() {
+        if canonical.starts_with(&root) {
+            return Ok(canonical);
+        }
+    }
+
+    match grant_token.and_then(|token| grants.granted(token)) {
+        Some(granted) if granted == canonical => Ok(canonical),
+        _ => Err(denied(requested)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use std::fs;
+
+    fn temp_dirs() -> (PathBuf, PathBuf) {
+        let base = std::env::temp_dir().join(format!("sgp-sandbox-{}", uuid::Uuid::new_v4()));
+        let root = base.join("images");
+        let outside = base.join("fora");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        (root, outside)
+    }
+
+    #[test]
+    fn test_allows_images_dir_and_rejects_outside() {
+        let (root, outside) = temp_dirs();
+        let grants = FileGrants::default();
+        let inside = root.join("arte.png");
+        let secret = outside.join("segredo.txt");
+        fs::write(&inside, b"png").unwrap();
+        fs::write(&secret, b"senha").unwrap();
+
+        assert!(authorize(&root, inside.to_str().unwrap(), &grants, None).is_ok());
+
+        let err = authorize(&root, secret.to_str().unwrap(), &grants, None).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Permission);
+
+        let escape = format!("{}/../fora/segredo.txt", root.display());
+        let err = authorize(&root, &escape, &grants, None).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Permission);
+
+        let _ = fs::remove_dir_all(root.parent().unwrap());
+    }
+
+    #[test]
+    fn test_grant_token_only_opens_its_file() {
+        let (root, outside) = temp_dirs();
+        let grants = FileGrants::default();
+        let picked = outside.join("escolhida.jpg");
+        let other = outside.join("outra.jpg");
+        fs::write(&picked, b"jpg").unwrap();
+        fs::write(&other, b"jpg").unwrap();
+
+        let token = grants.grant(&picked).unwrap();
+        assert!(authorize(&root, picked.to_str().unwrap(), &grants, Some(&token)).is_ok());
+        assert!(authorize(&root, other.to_str().unwrap(), &grants, Some(&token)).is_err());
+
+        assert!(grants.revoke(&token));
+        assert!(authorize(&root, picked.to_str().unwrap(), &grants, Some(&token)).is_err());
+
+        let _ = fs::remove_dir_all(root.parent().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_symlink_escape() {
+        let (root, outside) = temp_dirs();
+        let grants = FileGrants::default();
+        let secret = outside.join("segredo.txt");
+        fs::write(&secret, b"senha").unwrap();
+        let link = root.join("atalho.png");
+        std::os::unix::fs::symlink(&secret, &link).unwrap();
+
+        let err = authorize(&root, link.to_str().unwrap(), &grants, None).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Permission);
+
+        let _ = fs::remove_dir_all(root.parent().unwrap());
+    }
+}
@@ -1,26 +1,69 @@
-use reqwest::Client;
+use crate::bridge::auth::SessionManager;
+use crate::bridge::cache::ResponseCache;
+use crate::bridge::inflight::InflightRegistry;
+use crate::bridge::inspector::NetworkInspector;
+use crate::bridge::policy::PolicyEngine;
+use crate::bridge::presence::PresenceMonitor;
+use crate::bridge::queue::OfflineQueue;
+use crate::bridge::realtime::RealtimeHub;
+use crate::bridge::retry::RetryPolicy;
+use crate::bridge::transfer::TransferRegistry;
+use crate::http_client::HttpClientManager;
+use crate::image_store::ImageStore;
+use crate::profiles::ProfileStore;
+use crate::sandbox::FileGrants;
+use crate::vault::CredentialVault;
+use std::collections::HashMap;
 use std::sync::Mutex;
 use std::time::Duration;
 
 pub struct AppState {
-    pub client: Client,
+    pub http: HttpClientManager,
     pub api_base_url: Mutex<String>,
     pub auth_token: Mutex<Option<String>>,
+    /// Headers extras do perfil ativo
+    pub extra_headers: Mutex<HashMap<String, String>>,
+    /// Timeout por requisição do perfil ativo
+    pub request_timeout: Mutex<Option<Duration>>,
+    pub retry_policy: Mutex<RetryPolicy>,
+    pub offline_queue: OfflineQueue,
+    pub response_cache: ResponseCache,
+    pub vault: CredentialVault,
+    pub session: SessionManager,
+    pub profiles: ProfileStore,
+    pub transfers: TransferRegistry,
+    pub inflight: InflightRegistry,
+    pub inspector: NetworkInspector,
+    pub policy: PolicyEngine,
+    pub realtime: RealtimeHub,
+    pub presence: PresenceMonitor,
+    pub images: ImageStore,
+    /// Arquivos fora do app liberados pelo usuário no diálogo
+    pub file_grants: FileGrants,
 }
 
 impl AppState {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .pool_idle_timeout(Duration::from_secs(300))
-            .pool_max_idle_per_host(10)
-            .build()
-            .expect("Falha ao construir pool HTTP do Reqwest");
-
         Self {
-            client,
+            http: HttpClientManager::new(),
             api_base_url: Mutex::new(String::new()),
             auth_token: Mutex::new(None),
+            extra_headers: Mutex::new(HashMap::new()),
+            request_timeout: Mutex::new(None),
+            retry_policy: Mutex::new(RetryPolicy::default()),
+            offline_queue: OfflineQueue::default(),
+            response_cache: ResponseCache::default(),
+            vault: CredentialVault::default(),
+            session: SessionManager::default(),
+            profiles: ProfileStore::default(),
+            transfers: TransferRegistry::default(),
+            inflight: InflightRegistry::default(),
+            inspector: NetworkInspector::default(),
+            policy: PolicyEngine::default(),
+            realtime: RealtimeHub::default(),
+            presence: PresenceMonitor::default(),
+            images: ImageStore::default(),
+            file_grants: FileGrants::default(),
         }
     }
 }
@@ -0,0 +1,103 @@
+//! Utilitários de persistência em disco usados pelos subsistemas do core
+//! (fila offline, cache, perfis...). Toda escrita é atômica: grava num
+//! arquivo temporário ao lado do destino e renomeia por cima.
+
+use crate::error::{AppError, AppResult};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Sequência dos temporários deste processo
+static TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// `<nome>.<pid>.<seq>.tmp` ao lado do destino: duas escritas simultâneas no
+/// mesmo arquivo (ou duas instâncias do app) nunca dividem o temporário
+fn tmp_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        "{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        TMP_SEQ.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+pub fn write_atomic(path: &Path, data: &[u8]) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io(format!("Erro ao criar diretório {:?}: {}", parent, e)))?;
+    }
+
+    let tmp_path = tmp_path_for(path);
+    let result = fs::write(&tmp_path, data)
+        .map_err(|e| AppError::io(format!("Erro ao gravar {:?}: {}", tmp_path, e)))
+        .and_then(|_| {
+            fs::rename(&tmp_path, path)
+                .map_err(|e| AppError::io(format!("Erro ao substituir {:?}: {}", path, e)))
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> AppResult<()> {
+    let data = serde_json::to_vec_pretty(value)?;
+    write_atomic(path, &data)
+}
+
+/// Lê um JSON do disco; arquivo inexistente retorna `None`
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> AppResult<Option<T>> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AppError::io(format!("Erro ao ler {:?}: {}", path, e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tmp_path_is_unique_per_write() {
+        let path = Path::new("/tmp/sgp/index.json");
+        let a = tmp_path_for(path);
+        let b = tmp_path_for(path);
+        assert_ne!(a, b);
+        assert_eq!(a.parent(), path.parent());
+        assert!(a
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("index.json."));
+    }
+
+    #[test]
+    fn test_concurrent_writes_leave_a_complete_file() {
+        let dir = std::env::temp_dir().join(format!("sgp-storage-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("data.json");
+
+        let writers: Vec<_> = (0..8u8)
+            .map(|n| {
+                let path = path.clone();
+                std::thread::spawn(move || write_atomic(&path, &[n; 4096]))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        let data = fs::read(&path).unwrap();
+        assert_eq!(data.len(), 4096);
+        assert!(data.iter().all(|b| *b == data[0]));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
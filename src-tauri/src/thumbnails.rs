@@ -0,0 +1,398 @@
+//! Miniaturas das imagens de pedido.
+//!
+//! Listas e o painel do designer só precisam de uma prévia pequena, mas
+//! carregavam a arte inteira como data URL. Para cada conteúdo (por hash)
+//! são geradas variantes com o maior lado em 128 e 512 px, gravadas em
+//! `<app_data>/images/thumbs/<hash>_<lado>.<ext>`: JPEG para imagens opacas
+//! e PNG quando há transparência. [`best_variant`] escolhe a menor variante
+//! que ainda cobre o tamanho pedido.
+//!
+//! TIFF e PDF não são exibidos pelo WebView: para eles é gerada também uma
+//! prévia (`<hash>_preview.<ext>`, maior lado até 2048 px; no PDF, a
+//! primeira página) que faz o papel do original na exibição. O arquivo
+//! original nunca é alterado.
+//!
+//! A decodificação tem limites explícitos ([`MAX_DECODE_SIDE`],
+//! [`MAX_DECODE_ALLOC`]): uma arte de impressão acima deles não ganha prévia
+//! e o erro diz isso, em vez de derrubar o app por falta de memória. JPEG é
+//! reduzido já na decodificação; os demais formatos são reduzidos logo em
+//! seguida, antes de qualquer cópia.
+
+use crate::error::{AppError, AppResult};
+use crate::pdf_preview;
+use crate::storage;
+use image::codecs::jpeg::JpegDecoder;
+use image::error::ImageError;
+use image::imageops::FilterType;
+use image::io::{Limits, Reader};
+use image::{DynamicImage, GenericImageView, ImageDecoder, ImageFormat, ImageOutputFormat};
+use serde::Serialize;
+use std::fs;
+use std::io::{BufReader, Cursor};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Maior lado de cada variante, em ordem crescente
+pub const SIZES: [u32; 2] = [128, 512];
+
+const JPEG_QUALITY: u8 = 80;
+
+/// Maior lado da prévia de formatos que o WebView não exibe
+const PREVIEW_SIZE: u32 = 2048;
+
+/// Maior largura ou altura que o decodificador aceita
+pub const MAX_DECODE_SIDE: u32 = 30_000;
+
+/// Memória que o decodificador pode alocar para uma imagem
+pub const MAX_DECODE_ALLOC: u64 = 1024 * 1024 * 1024;
+
+/// Extensões que o WebView exibe direto em `<img>`
+const WEB_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "webp", "gif", "bmp"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageVariant {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: String,
+    /// Lado da variante (`128`, `512`) ou `None` para o original
+    pub variant: Option<u32>,
+    /// Preenchido só quando o chamador pede a imagem embutida
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_url: Option<String>,
+}
+
+fn thumbs_dir(root: &Path) -> PathBuf {
+    root.join("thumbs")
+}
+
+/// Caminho da variante (`128`, `512`, `preview`) já gerada, se existir
+fn existing(root: &Path, hash: &str, label: impl std::fmt::Display) -> Option<PathBuf> {
+    ["jpg", "png"]
+        .iter()
+        .map(|ext| thumbs_dir(root).join(format!("{}_{}.{}", hash, label, ext)))
+        .find(|path| path.is_file())
+}
+
+/// Indica se o WebView exibe o arquivo direto, sem prévia
+pub fn web_displayable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| WEB_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+fn decode_limits() -> Limits {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DECODE_SIDE);
+    limits.max_image_height = Some(MAX_DECODE_SIDE);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    limits
+}
+
+fn decode_error(source: &Path, e: ImageError) -> AppError {
+    match e {
+        ImageError::Limits(_) => AppError::validation(format!(
+            "Imagem grande demais para gerar prévia ({}): {}",
+            source.display(),
+            e
+        )),
+        e => AppError::parse(format!("Erro ao carregar imagem: {}", e)),
+    }
+}
+
+/// Decodifica `source` com [`decode_limits`], reduzido para caber em
+/// `max_side`. No JPEG a redução acontece na própria decodificação.
+fn decode_scaled(source: &Path, max_side: u32) -> AppResult<DynamicImage> {
+    let reader = Reader::open(source)?
+        .with_guessed_format()
+        .map_err(|e| AppError::io(format!("Erro ao abrir imagem: {}", e)))?;
+
+    let img = if reader.format() == Some(ImageFormat::Jpeg) {
+        let file = BufReader::new(fs::File::open(source)?);
+        let mut decoder = JpegDecoder::new(file).map_err(|e| decode_error(source, e))?;
+        decoder
+            .set_limits(decode_limits())
+            .map_err(|e| decode_error(source, e))?;
+        let side = max_side.min(u16::MAX as u32) as u16;
+        decoder
+            .scale(side, side)
+            .map_err(|e| decode_error(source, e))?;
+        DynamicImage::from_decoder(decoder).map_err(|e| decode_error(source, e))?
+    } else {
+        let mut reader = reader;
+        reader.limits(decode_limits());
+        reader.decode().map_err(|e| decode_error(source, e))?
+    };
+
+    let (width, height) = img.dimensions();
+    Ok(if width.max(height) > max_side {
+        img.resize(max_side, max_side, FilterType::Triangle)
+    } else {
+        img
+    })
+}
+
+/// Decodifica o original reduzido para `max_side`; PDFs são rasterizados
+/// pela primeira página
+fn load_raster(source: &Path, max_side: u32) -> AppResult<DynamicImage> {
+    if !pdf_preview::is_pdf(source) {
+        return decode_scaled(source, max_side);
+    }
+    let page = pdf_preview::render_first_page(source)?;
+    let mut reader = Reader::with_format(Cursor::new(page), ImageFormat::Png);
+    reader.limits(decode_limits());
+    let img = reader.decode().map_err(|e| decode_error(source, e))?;
+    let (width, height) = img.dimensions();
+    Ok(if width.max(height) > max_side {
+        img.resize(max_side, max_side, FilterType::Triangle)
+    } else {
+        img
+    })
+}
+
+/// Arquivo que o WebView consegue exibir para `source`: o próprio original
+/// ou, para TIFF e PDF, a prévia, gerada na primeira vez que é pedida
+pub fn display_source(root: &Path, hash: &str, source: &Path) -> AppResult<PathBuf> {
+    if web_displayable(source) {
+        return Ok(source.to_path_buf());
+    }
+    if let Some(path) = existing(root, hash, "preview") {
+        return Ok(path);
+    }
+
+    let img = load_raster(source, PREVIEW_SIZE)?;
+    let (data, ext) = encode(&img)?;
+    let path = thumbs_dir(root).join(format!("{}_preview.{}", hash, ext));
+    storage::write_atomic(&path, &data)?;
+    debug!(
+        "[Thumbnails] Prévia de {} gerada em {}",
+        source.display(),
+        path.display()
+    );
+    Ok(path)
+}
+
+fn encode(img: &DynamicImage) -> AppResult<(Vec<u8>, &'static str)> {
+    let mut buffer = Cursor::new(Vec::new());
+    let ext = if img.color().has_alpha() {
+        img.write_to(&mut buffer, ImageOutputFormat::Png)
+            .map_err(|e| AppError::parse(format!("Erro ao gerar miniatura: {}", e)))?;
+        "png"
+    } else {
+        DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_to(&mut buffer, ImageOutputFormat::Jpeg(JPEG_QUALITY))
+            .map_err(|e| AppError::parse(format!("Erro ao gerar miniatura: {}", e)))?;
+        "jpg"
+    };
+    Ok((buffer.into_inner(), ext))
+}
+
+/// Gera as variantes que faltam para `hash` a partir do arquivo original.
+/// Imagens menores que uma variante não ganham essa variante.
+pub fn generate(root: &Path, hash: &str, source: &Path) -> AppResult<Vec<PathBuf>> {
+    let missing: Vec<u32> = SIZES
+        .iter()
+        .copied()
+        .filter(|size| existing(root, hash, *size).is_none())
+        .collect();
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let display = display_source(root, hash, source)?;
+    let (width, height) = image::image_dimensions(&display)
+        .map_err(|e| AppError::parse(format!("Erro ao ler dimensões da imagem: {}", e)))?;
+    let longest = width.max(height);
+    let largest = missing.iter().copied().max().unwrap_or(0);
+    let img = decode_scaled(&display, largest)?;
+
+    let mut written = Vec::new();
+    for size in missing {
+        if longest <= size {
+            continue;
+        }
+        let thumb = img.resize(size, size, FilterType::Triangle);
+        let (data, ext) = encode(&thumb)?;
+        let path = thumbs_dir(root).join(format!("{}_{}.{}", hash, size, ext));
+        storage::write_atomic(&path, &data)?;
+        debug!(
+            "[Thumbnails] {} gerada ({} bytes)",
+            path.display(),
+            data.len()
+        );
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Gera as miniaturas em segundo plano, sem segurar quem salvou a imagem
+pub fn generate_in_background(root: PathBuf, hash: String, source: PathBuf) {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = generate(&root, &hash, &source) {
+            warn!("[Thumbnails] Falha ao gerar miniaturas de {}: {}", hash, e);
+        }
+    });
+}
+
+/// Apaga todas as variantes de um hash, inclusive a prévia
+pub fn remove(root: &Path, hash: &str) {
+    let labels = SIZES
+        .iter()
+        .map(|size| size.to_string())
+        .chain(std::iter::once("preview".to_string()));
+    for label in labels {
+        while let Some(path) = existing(root, hash, &label) {
+            if fs::remove_file(&path).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+fn variant_info(path: PathBuf, variant: Option<u32>) -> AppResult<ImageVariant> {
+    let (width, height) = image::image_dimensions(&path)
+        .map_err(|e| AppError::parse(format!("Erro ao ler dimensões da imagem: {}", e)))?;
+    let mime_type = match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "image/jpeg",
+    };
+    Ok(ImageVariant {
+        path: path.to_string_lossy().to_string(),
+        width,
+        height,
+        mime_type: mime_type.to_string(),
+        variant,
+        data_url: None,
+    })
+}
+
+/// Menor variante cujo maior lado cobre `requested`; o original (ou a
+/// prévia, para TIFF e PDF) se nenhuma cobrir ou se o próprio original já
+/// for pequeno. Gera a variante na hora se ela ainda não existir.
+pub fn best_variant(
+    root: &Path,
+    hash: &str,
+    source: &Path,
+    requested: u32,
+) -> AppResult<ImageVariant> {
+    let display = display_source(root, hash, source)?;
+    let source = display.as_path();
+    let (width, height) = image::image_dimensions(source)
+        .map_err(|e| AppError::parse(format!("Erro ao ler dimensões da imagem: {}", e)))?;
+    let longest = width.max(height);
+
+    let Some(size) = SIZES
+        .iter()
+        .copied()
+        .find(|size| *size >= requested && *size < longest)
+    else {
+        return variant_info(source.to_path_buf(), None);
+    };
+
+    if existing(root, hash, size).is_none() {
+        generate(root, hash, source)?;
+    }
+    match existing(root, hash, size) {
+        Some(path) => variant_info(path, Some(size)),
+        None => variant_info(source.to_path_buf(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn temp_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("sgp-thumbs-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_best_variant_picks_smallest_covering_size() {
+        let root = temp_root();
+        let source = root.join("arte.png");
+        RgbImage::from_pixel(1000, 600, Rgb([200, 10, 10]))
+            .save(&source)
+            .unwrap();
+
+        let small = best_variant(&root, "abc", &source, 100).unwrap();
+        assert_eq!(small.variant, Some(128));
+        assert_eq!(small.width, 128);
+        assert_eq!(small.mime_type, "image/jpeg");
+
+        let medium = best_variant(&root, "abc", &source, 300).unwrap();
+        assert_eq!(medium.variant, Some(512));
+
+        let original = best_variant(&root, "abc", &source, 800).unwrap();
+        assert_eq!(original.variant, None);
+        assert_eq!(original.width, 1000);
+
+        remove(&root, "abc");
+        assert!(existing(&root, "abc", 128).is_none());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_transparent_images_keep_png_and_small_images_skip_variants() {
+        let root = temp_root();
+        let source = root.join("logo.png");
+        RgbaImage::from_pixel(300, 300, Rgba([0, 0, 0, 0]))
+            .save(&source)
+            .unwrap();
+
+        let written = generate(&root, "logo", &source).unwrap();
+        assert_eq!(written.len(), 1);
+        assert!(written[0].to_string_lossy().ends_with("logo_128.png"));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_tiff_gets_displayable_preview_and_keeps_original() {
+        let root = temp_root();
+        let source = root.join("lona.tif");
+        RgbImage::from_pixel(600, 300, Rgb([10, 120, 10]))
+            .save(&source)
+            .unwrap();
+        let original = fs::read(&source).unwrap();
+
+        let full = best_variant(&root, "lona", &source, 1000).unwrap();
+        assert_eq!(full.variant, None);
+        assert!(full.path.ends_with("lona_preview.jpg"));
+        assert_eq!((full.width, full.height), (600, 300));
+
+        let small = best_variant(&root, "lona", &source, 100).unwrap();
+        assert_eq!(small.variant, Some(128));
+        assert_eq!(fs::read(&source).unwrap(), original);
+
+        remove(&root, "lona");
+        assert!(existing(&root, "lona", "preview").is_none());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_decode_is_limited_and_scaled() {
+        let root = temp_root();
+        let photo = root.join("foto.jpg");
+        RgbImage::from_pixel(2000, 1000, Rgb([90, 90, 200]))
+            .save(&photo)
+            .unwrap();
+        let img = decode_scaled(&photo, 256).unwrap();
+        assert_eq!(img.dimensions(), (256, 128));
+
+        // Acima do limite de lado: erro claro em vez de prévia (ou do original)
+        let strip = root.join("faixa.tif");
+        RgbImage::from_pixel(MAX_DECODE_SIDE + 1, 2, Rgb([0, 0, 0]))
+            .save(&strip)
+            .unwrap();
+        let err = display_source(&root, "faixa", &strip).unwrap_err();
+        assert!(err.message.contains("grande demais"), "{}", err.message);
+        assert!(existing(&root, "faixa", "preview").is_none());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,236 @@
+//! Cofre de credenciais da API gravado em `<app_data>/vault.json`.
+//!
+//! O token e a base URL são cifrados com ChaCha20-Poly1305. A chave é derivada
+//! (HKDF-SHA256) do identificador da máquina, do usuário do sistema e de um
+//! salt aleatório salvo junto do arquivo, então copiar o cofre para outro
+//! computador ou outro usuário não revela o token.
+
+use crate::bridge::auth::SessionIdentity;
+use crate::error::{AppError, AppResult};
+use crate::storage;
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, Utc};
+use hkdf::Hkdf;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+const VAULT_VERSION: u32 = 1;
+const KEY_INFO: &[u8] = b"sgp-v4 credential vault v1";
+
+/// Conteúdo em claro do cofre. Nunca sai do processo Rust.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub base_url: String,
+    pub token: Option<String>,
+    /// Usuário e papel obtidos pelo core no login com este token
+    #[serde(default)]
+    pub identity: Option<SessionIdentity>,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Formato gravado em disco
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedVault {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Default)]
+pub struct CredentialVault {
+    path: Mutex<Option<PathBuf>>,
+}
+
+/// Identificador estável da máquina + usuário atual
+fn machine_fingerprint() -> Vec<u8> {
+    let machine_id = machine_uid::get().unwrap_or_else(|e| {
+        warn!("[Vault] Não foi possível obter o id da máquina: {}", e);
+        "unknown-machine".to_string()
+    });
+    let user = std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_default();
+    format!("{}:{}", machine_id, user).into_bytes()
+}
+
+fn derive_key(salt: &[u8]) -> AppResult<Key> {
+    let hk = Hkdf::<Sha256>::new(Some(salt), &machine_fingerprint());
+    let mut key = [0u8; 32];
+    hk.expand(KEY_INFO, &mut key)
+        .map_err(|_| AppError::internal("Falha ao derivar chave do cofre"))?;
+    Ok(Key::from(key))
+}
+
+fn decode(field: &str, value: &str) -> AppResult<Vec<u8>> {
+    general_purpose::STANDARD
+        .decode(value)
+        .map_err(|e| AppError::parse(format!("Cofre corrompido ({}): {}", field, e)))
+}
+
+/// Cifra `value` e grava em `path`. Usado pelo cofre e por qualquer outro
+/// arquivo do core que guarde segredos (ex: perfis de conexão).
+pub fn seal<T: Serialize + ?Sized>(path: &Path, value: &T) -> AppResult<()> {
+    let salt: [u8; 16] = rand_bytes();
+    let cipher = ChaCha20Poly1305::new(&derive_key(&salt)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let plaintext = serde_json::to_vec(value)?;
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_ref())
+        .map_err(|_| AppError::internal("Falha ao cifrar dados do cofre"))?;
+
+    let sealed = SealedVault {
+        version: VAULT_VERSION,
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    };
+    storage::write_json(path, &sealed)
+}
+
+/// Lê e decifra um arquivo gravado por [`seal`]; `None` se não existir
+pub fn unseal<T: DeserializeOwned>(path: &Path) -> AppResult<Option<T>> {
+    let sealed: SealedVault = match storage::read_json(path)? {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+    if sealed.version != VAULT_VERSION {
+        return Err(AppError::parse(format!(
+            "Versão do cofre não suportada: {}",
+            sealed.version
+        )));
+    }
+
+    let salt = decode("salt", &sealed.salt)?;
+    let nonce = decode("nonce", &sealed.nonce)?;
+    let ciphertext = decode("ciphertext", &sealed.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(AppError::parse("Cofre corrompido (nonce)"));
+    }
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(&salt)?);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| {
+            AppError::parse("Não foi possível decifrar o cofre (outra máquina ou usuário?)")
+        })?;
+
+    Ok(Some(serde_json::from_slice(&plaintext)?))
+}
+
+impl CredentialVault {
+    pub fn set_path(&self, path: PathBuf) -> AppResult<()> {
+        *self
+            .path
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no caminho do cofre"))? = Some(path);
+        Ok(())
+    }
+
+    fn path(&self) -> AppResult<PathBuf> {
+        self.path
+            .lock()
+            .map_err(|_| AppError::internal("Erro de mutex no caminho do cofre"))?
+            .clone()
+            .ok_or_else(|| AppError::config("Cofre de credenciais ainda não inicializado"))
+    }
+
+    /// Cifra e grava as credenciais. Cada gravação usa salt e nonce novos,
+    /// o que também rotaciona a chave derivada.
+    pub fn store(&self, credentials: &Credentials) -> AppResult<()> {
+        seal(&self.path()?, credentials)?;
+        info!("[Vault] Credenciais gravadas");
+        Ok(())
+    }
+
+    /// Lê e decifra o cofre; `None` se ainda não houver credenciais salvas
+    pub fn load(&self) -> AppResult<Option<Credentials>> {
+        unseal(&self.path()?)
+    }
+
+    /// Apaga o cofre do disco (logout)
+    pub fn wipe(&self) -> AppResult<()> {
+        let path = self.path()?;
+        match std::fs::remove_file(&path) {
+            Ok(()) => {
+                info!("[Vault] Credenciais removidas");
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::io(format!("Erro ao remover cofre: {}", e))),
+        }
+    }
+}
+
+fn rand_bytes<const N: usize>() -> [u8; N] {
+    use chacha20poly1305::aead::rand_core::RngCore;
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("sgp-vault-{}", uuid::Uuid::new_v4()))
+            .join("vault.json")
+    }
+
+    #[test]
+    fn test_seal_unseal_round_trip() {
+        let path = scratch_path();
+        let credentials = Credentials {
+            base_url: "http://192.168.0.10:8000".to_string(),
+            token: Some("segredo".to_string()),
+            identity: Some(SessionIdentity {
+                user_id: Some(3),
+                username: Some("ana".to_string()),
+                role: "admin".to_string(),
+            }),
+            saved_at: Utc::now(),
+        };
+        seal(&path, &credentials).unwrap();
+
+        // O token não aparece em claro no arquivo
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("segredo"));
+
+        let loaded: Credentials = unseal(&path).unwrap().unwrap();
+        assert_eq!(loaded.base_url, credentials.base_url);
+        assert_eq!(loaded.token, credentials.token);
+        assert_eq!(loaded.identity, credentials.identity);
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_unseal_rejects_tampered_ciphertext() {
+        let path = scratch_path();
+        seal(&path, &"valor").unwrap();
+
+        let mut sealed: SealedVault = storage::read_json(&path).unwrap().unwrap();
+        let mut bytes = general_purpose::STANDARD
+            .decode(&sealed.ciphertext)
+            .unwrap();
+        bytes[0] ^= 0xff;
+        sealed.ciphertext = general_purpose::STANDARD.encode(bytes);
+        storage::write_json(&path, &sealed).unwrap();
+
+        assert!(unseal::<String>(&path).is_err());
+        assert!(unseal::<String>(&path.with_file_name("ausente.json"))
+            .unwrap()
+            .is_none());
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -13,6 +13,7 @@ import { isTauri } from './utils/isTauri';
 import { AlertProvider } from './contexts/AlertContext';
 import { ConfirmProvider } from './contexts/ConfirmContext';
 import { useRealtimeNotifications } from './hooks/useRealtimeNotifications';
+import { useSessionExpired } from './hooks/useSessionExpired';
 import { ChangelogModal } from './components/ChangelogModal';
 import { logger } from './utils/logger';
 import { UpdateBanner } from './components/UpdateBanner';
@@ -30,6 +31,12 @@ function PrivateRoute({ children }: { children: React.ReactNode }) {
   return isAuthenticated ? <>{children}</> : <Navigate to="/login" />;
 }
 
+/** Escuta o aviso de sessão expirada do core; precisa ficar dentro do router */
+function SessionExpiredRedirect() {
+  useSessionExpired();
+  return null;
+}
+
 function LoadingFallback() {
   return (
     <div className="min-h-screen flex items-center justify-center bg-slate-50">
@@ -339,6 +346,7 @@ function App() {
             ) : (
               <HashRouter>
                 <div className="flex flex-col h-screen overflow-hidden">
+                  <SessionExpiredRedirect />
                   <UpdateBanner />
                   <CommandPalette />
                   <div className="flex-1 flex flex-col relative">
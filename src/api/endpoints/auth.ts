@@ -1,6 +1,8 @@
 import { apiClient, setAuthToken } from '../client';
 import { LoginRequest, LoginResponse } from '../types';
 import { useAuthStore } from '../../store/authStore';
+import { hybridClient } from '../../services/hybridClient';
+import { isTauri } from '../../utils/isTauri';
 
 const requireSessionToken = (): void => {
     const token = useAuthStore.getState().sessionToken;
@@ -12,6 +14,15 @@ const requireSessionToken = (): void => {
 
 export const authApi = {
     login: async (request: LoginRequest): Promise<LoginResponse> => {
+        if (isTauri()) {
+            // O Rust faz o login e adota o token e o papel da resposta
+            const data = await hybridClient.login<LoginResponse>(request.username, request.password);
+            if (data.session_token) {
+                setAuthToken(data.session_token);
+            }
+            return data;
+        }
+
         // Permitir passar headers específicos na chamada
         const response = await apiClient.post<LoginResponse>('/auth/login', request, {
             headers: {
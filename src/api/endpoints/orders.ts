@@ -27,6 +27,8 @@ import { ordersSocket } from '../../lib/realtimeOrders';
 import { useAuthStore } from '../../store/authStore';
 import { setAuthToken } from '../client';
 import { toCurrencyString } from '../utils';
+import { hybridClient } from '../../services/hybridClient';
+import { isTauri } from '../../utils/isTauri';
 
 // Cache constants
 const ORDER_BY_ID_CACHE_TTL_MS = 2_000;
@@ -105,6 +107,22 @@ interface FetchOrdersParams {
 
 const inFlightRequests = new Map<string, Promise<PaginatedOrders>>();
 
+/**
+ * Com `requestId` a leitura passa pela ponte nativa, onde uma nova chamada com o
+ * mesmo id cancela a anterior (a resposta obsoleta falha com `cancelled`).
+ */
+const getPedidosPage = async (params: FetchOrdersParams, requestId?: string): Promise<ApiPedido[]> => {
+    if (!requestId) {
+        const response = await apiClient.get<ApiPedido[]>('/pedidos/', { params });
+        return response.data ?? [];
+    }
+    const query: Record<string, string> = {};
+    Object.entries(params).forEach(([key, value]) => {
+        if (value !== undefined && value !== '') query[key] = String(value);
+    });
+    return (await hybridClient.get<ApiPedido[]>('/pedidos/', query, requestId)) ?? [];
+};
+
 const fetchOrdersPaginated = async (
     page: number = 1,
     pageSize: number = DEFAULT_PAGE_SIZE,
@@ -114,7 +132,8 @@ const fetchOrdersPaginated = async (
     data_fim?: string,
     tipo_producao?: string,
     is_pronto?: boolean,
-    date_mode?: string
+    date_mode?: string,
+    requestId?: string
 ): Promise<PaginatedOrders> => {
     requireSessionToken();
 
@@ -146,8 +165,10 @@ const fetchOrdersPaginated = async (
         params.date_mode = date_mode;
     }
 
+    // Com requestId a ponte já junta GETs idênticos; reaproveitar aqui devolveria
+    // uma promessa que uma chamada mais nova pode ter cancelado
     const cacheKey = JSON.stringify(params);
-    const existing = inFlightRequests.get(cacheKey);
+    const existing = requestId ? undefined : inFlightRequests.get(cacheKey);
     if (existing) {
         logger.debug('[fetchOrdersPaginated] Reaproveitando requisição idêntica em andamento:', cacheKey);
         return existing;
@@ -155,8 +176,7 @@ const fetchOrdersPaginated = async (
 
     const promise = (async () => {
         try {
-            const response = await apiClient.get<ApiPedido[]>('/pedidos/', { params });
-            const allData = (response.data ?? []).map(mapPedidoFromApi);
+            const allData = (await getPedidosPage(params, requestId)).map(mapPedidoFromApi);
 
             // Verifica se há mais páginas
             const hasNextPage = allData.length > pageSize;
@@ -181,11 +201,11 @@ const fetchOrdersPaginated = async (
                 total_pages: totalPages,
             };
         } finally {
-            inFlightRequests.delete(cacheKey);
+            if (!requestId) inFlightRequests.delete(cacheKey);
         }
     })();
 
-    inFlightRequests.set(cacheKey, promise);
+    if (!requestId) inFlightRequests.set(cacheKey, promise);
     return promise;
 };
 
@@ -206,9 +226,10 @@ export const ordersApi = {
         data_fim?: string,
         tipo_producao?: string,
         is_pronto?: boolean,
-        date_mode?: string
+        date_mode?: string,
+        requestId?: string
     ): Promise<PaginatedOrders> => {
-        return await fetchOrdersPaginated(page, pageSize, status, cliente, data_inicio, data_fim, tipo_producao, is_pronto, date_mode);
+        return await fetchOrdersPaginated(page, pageSize, status, cliente, data_inicio, data_fim, tipo_producao, is_pronto, date_mode, requestId);
     },
 
     getOrderById: async (orderId: number): Promise<OrderWithItems> => {
@@ -235,16 +256,48 @@ export const ordersApi = {
         startDate: string,
         endDate?: string
     ): Promise<OrderWithItems[]> => {
+        const orders: OrderWithItems[] = [];
+        await ordersApi.streamOrders(
+            (page) => orders.push(...page),
+            { data_inicio: startDate, data_fim: endDate || startDate },
+            { maxItems: 1000 } // Limite razoável para relatórios
+        );
+        return orders;
+    },
+
+    /**
+     * Percorre `/pedidos/` página a página (skip/limit), chamando `onPage` a cada lote.
+     * Reusar o `streamId` interrompe a leitura anterior. Fora do Tauri busca tudo de uma vez.
+     */
+    streamOrders: async (
+        onPage: (orders: OrderWithItems[]) => void,
+        filters: Omit<FetchOrdersParams, 'skip' | 'limit'> = {},
+        options: { maxItems?: number; streamId?: string } = {}
+    ): Promise<void> => {
         requireSessionToken();
 
-        const params: FetchOrdersParams = {
-            data_inicio: startDate,
-            data_fim: endDate || startDate,
-            limit: 1000, // Limite razoável para relatórios
-        };
+        if (!isTauri()) {
+            const params: FetchOrdersParams = { ...filters, limit: options.maxItems ?? 1000 };
+            const response = await apiClient.get<ApiPedido[]>('/pedidos/', { params });
+            onPage((response.data ?? []).map(mapPedidoFromApi));
+            return;
+        }
 
-        const response = await apiClient.get<ApiPedido[]>('/pedidos/', { params });
-        return (response.data ?? []).map(mapPedidoFromApi);
+        const params: Record<string, string> = {};
+        Object.entries(filters).forEach(([key, value]) => {
+            if (value !== undefined && value !== '') params[key] = String(value);
+        });
+        await hybridClient.streamPages<ApiPedido>(
+            {
+                endpoint: '/pedidos/',
+                params,
+                page_size: 100,
+                max_items: options.maxItems,
+                strategy: { mode: 'offset', offset_param: 'skip' },
+            },
+            (pedidos) => onPage(pedidos.map(mapPedidoFromApi)),
+            options.streamId
+        );
     },
 
     createOrder: async (request: CreateOrderRequest): Promise<OrderWithItems> => {
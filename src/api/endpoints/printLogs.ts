@@ -1,4 +1,6 @@
 import { apiClient } from '../client';
+import { hybridClient } from '../../services/hybridClient';
+import { isTauri } from '../../utils/isTauri';
 import { PrintLog, PrintLogCreate, PrintLogStatus, PrinterStats } from '@/types';
 
 export const printLogsApi = {
@@ -63,6 +65,60 @@ export const printLogsApi = {
         return response.data;
     },
 
+    /**
+     * Carrega o histórico página a página, chamando `onPage` a cada lote.
+     * Com `printerId` lê só os logs da máquina; `maxItems` para a leitura ao
+     * atingir essa quantidade. Reusar o `streamId` interrompe a leitura anterior.
+     * Fora do Tauri cai no `getAllLogs`/`getPrinterLogs` de uma vez só.
+     */
+    async streamAllLogs(
+        onPage: (logs: PrintLog[]) => void,
+        options: {
+            printerId?: number;
+            maxItems?: number;
+            statusFilter?: PrintLogStatus;
+            dataInicio?: string;
+            dataFim?: string;
+            streamId?: string;
+        } = {}
+    ): Promise<void> {
+        if (!isTauri()) {
+            const limit = options.maxItems ?? 1000;
+            onPage(
+                options.printerId === undefined
+                    ? await this.getAllLogs(limit, 0, options.statusFilter, options.dataInicio, options.dataFim)
+                    : await this.getPrinterLogs(
+                        options.printerId,
+                        limit,
+                        0,
+                        options.statusFilter,
+                        options.dataInicio,
+                        options.dataFim
+                    )
+            );
+            return;
+        }
+
+        const params: Record<string, string> = {};
+        if (options.statusFilter) params.status_filter = options.statusFilter;
+        if (options.dataInicio) params.data_inicio = options.dataInicio;
+        if (options.dataFim) params.data_fim = options.dataFim;
+
+        const endpoint =
+            options.printerId === undefined ? '/print-logs/' : `/print-logs/printers/${options.printerId}`;
+        await hybridClient.streamPages<PrintLog>(
+            {
+                endpoint,
+                params,
+                page_size: 100,
+                max_items: options.maxItems,
+                strategy: { mode: 'offset' },
+            },
+            (logs) => onPage(logs),
+            options.streamId
+        );
+    },
+
     async createPrintLog(logData: PrintLogCreate): Promise<PrintLog> {
         const response = await apiClient.post<PrintLog>('/print-logs/', logData);
         return response.data;
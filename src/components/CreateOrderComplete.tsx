@@ -39,7 +39,7 @@ import { FormMochilinhaProducao } from '@/components/FormMochilinhaProducao';
 import { FormMesaBabado } from '@/components/FormMesaBabado';
 import { CurrencyInput } from '@/components/ui/currency-input';
 import { useOrderStore } from '@/store/orderStore';
-import { uploadImageToServer, needsUpload } from '@/utils/imageUploader';
+import { uploadImageToServer, needsUpload, syncOrderImageReferences } from '@/utils/imageUploader';
 import { canonicalizeFromItemRequest } from '@/mappers/productionItems';
 import { parseMonetary, formatMonetary } from '@/utils/currency';
 import { normalizeItemFieldsByTipo } from '@/utils/order-item-display';
@@ -2672,6 +2672,8 @@ export default function CreateOrderComplete({ mode }: CreateOrderCompleteProps)
             : `Pedido ${orderIdentifier} atualizado com sucesso.`,
         });
 
+        await syncOrderImageReferences(currentOrder, finalOrder);
+
         setShowResumoModal(false);
         setCurrentOrder(finalOrder);
         populateFormFromOrder(finalOrder);
@@ -2729,6 +2731,7 @@ export default function CreateOrderComplete({ mode }: CreateOrderCompleteProps)
 
       const createdOrder = await api.createOrder(createOrderRequest);
       addOrderInStore(createdOrder);
+      await syncOrderImageReferences(null, createdOrder);
       const orderIdentifier = createdOrder.numero ?? createdOrder.id.toString();
 
       // Imagens já foram enviadas antes de criar o pedido, então estão prontas
@@ -10,7 +10,11 @@ import { Textarea } from '@/components/ui/textarea';
 import { MedidasCalculator } from '@/components/MedidasCalculator';
 import SelectVendedor from '@/components/SelectVendedor';
 import SelectDesigner from '@/components/SelectDesigner';
-import { processAndSaveImage } from '@/utils/localImageManager';
+import {
+  ARTWORK_ACCEPT,
+  isArtworkFile,
+  saveArtworkLocally,
+} from '@/utils/localImageManager';
 import { getImagePreviewUrl } from '@/utils/imagePreview';
 import { isTauri } from '@/utils/isTauri';
 import { useToast } from '@/hooks/use-toast';
@@ -292,7 +296,7 @@ export function FormLonaProducao({
           <div className="relative flex-1 min-h-[320px]">
             <Input
               type="file"
-              accept="image/*"
+              accept={ARTWORK_ACCEPT}
               onChange={async (event) => {
                 const file = event.target.files?.[0];
                 if (!file) return;
@@ -302,13 +306,9 @@ export function FormLonaProducao({
                   try {
                     setImageLoading(true);
 
-                    // Converter File para Uint8Array
-                    const arrayBuffer = await file.arrayBuffer();
-                    const imageData = new Uint8Array(arrayBuffer);
-
-                    // Processar e salvar localmente
-                    const metadata = await processAndSaveImage(
-                      imageData,
+                    // TIFF, BMP e PDF são guardados como vieram; os demais são processados
+                    const metadata = await saveArtworkLocally(
+                      file,
                       5000, // maxWidth
                       400, // maxHeight
                       85    // quality
@@ -371,20 +371,16 @@ export function FormLonaProducao({
                 e.preventDefault();
                 e.stopPropagation();
                 const file = e.dataTransfer.files?.[0];
-                if (!file || !file.type.startsWith('image/')) return;
+                if (!file || !isArtworkFile(file)) return;
 
                 // Se estiver em Tauri, salvar localmente
                 if (isTauri()) {
                   try {
                     setImageLoading(true);
 
-                    // Converter File para Uint8Array
-                    const arrayBuffer = await file.arrayBuffer();
-                    const imageData = new Uint8Array(arrayBuffer);
-
-                    // Processar e salvar localmente
-                    const metadata = await processAndSaveImage(
-                      imageData,
+                    // TIFF, BMP e PDF são guardados como vieram; os demais são processados
+                    const metadata = await saveArtworkLocally(
+                      file,
                       5000, // maxWidth
                       400, // maxHeight
                       85    // quality
@@ -94,8 +94,9 @@ import { Checkbox } from '@/components/ui/checkbox';
 import { Skeleton } from '@/components/ui/skeleton';
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from '@/components/ui/tooltip';
 import { isTauri } from '@/utils/isTauri';
+import { hybridClient, NativeApiError } from '@/services/hybridClient';
 import { generateMultipleOrdersPdfBlob } from '@/utils/printOrderServiceForm';
-import { loadAuthenticatedImage } from '@/utils/imageLoader';
+import { loadImagePreviewSrc } from '@/utils/imageLoader';
 import { isValidImagePath } from '@/utils/path';
 
 import { cn } from '@/lib/utils';
@@ -114,6 +115,9 @@ const TIPOS_PRODUCAO_DEFAULT = [
   { value: 'mesa_babado', label: 'Mesa de Babado' },
 ];
 
+// Id das leituras da lista na ponte nativa: um novo carregamento cancela o anterior
+const ORDERS_LIST_REQUEST_ID = 'orders-list';
+
 // Helper puro para calcular urgência do pedido
 const calcOrderUrgency = (dataEntrega: string | null | undefined) => {
   if (!dataEntrega) return { type: 'no-date', days: null };
@@ -427,6 +431,7 @@ export default function OrderList() {
     } else if (orders.length === 0) {
       setLoading(true);
     }
+    let superseded = false;
     try {
       const currentPage = page;
       const currentPageSize = rowsPerPage;
@@ -441,7 +446,11 @@ export default function OrderList() {
           undefined, // status - todos
           activeSearchTerm || undefined, // cliente
           dateFrom || undefined, // data_inicio
-          dateTo || undefined // data_fim
+          dateTo || undefined, // data_fim
+          undefined, // tipo_producao
+          undefined, // is_pronto
+          undefined, // date_mode
+          ORDERS_LIST_REQUEST_ID
         );
         logger.debug(
           '[OrderList] Pedidos recebidos:',
@@ -468,11 +477,13 @@ export default function OrderList() {
             undefined, // data_inicio
             undefined, // data_fim
             undefined, // tipo_producao
-            false // is_pronto
+            false, // is_pronto
+            undefined, // date_mode
+            ORDERS_LIST_REQUEST_ID
           );
           all = paginated.orders;
         } else {
-          all = await api.getPendingOrdersLight();
+          all = await api.getPendingOrdersLight(ORDERS_LIST_REQUEST_ID);
         }
         logger.debug('[OrderList] carregamento de pedidos pendentes concluído:', {
           ordersLength: all.length,
@@ -491,7 +502,11 @@ export default function OrderList() {
             undefined, // status
             activeSearchTerm || undefined, // cliente
             dateFrom || undefined, // data_inicio
-            dateTo || undefined // data_fim
+            dateTo || undefined, // data_fim
+            undefined, // tipo_producao
+            undefined, // is_pronto
+            undefined, // date_mode
+            ORDERS_LIST_REQUEST_ID
           );
           setOrders(paginatedData.orders);
           setTotalPages(Math.ceil(paginatedData.orders.length / currentPageSize) || 1);
@@ -507,7 +522,7 @@ export default function OrderList() {
             page_size: currentPageSize,
           };
 
-          const paginatedData = await api.getOrdersWithFiltersForTable(filters);
+          const paginatedData = await api.getOrdersWithFiltersForTable(filters, ORDERS_LIST_REQUEST_ID);
           setOrders(paginatedData.orders);
           setTotalPages(paginatedData.total_pages);
           setTotalOrders(paginatedData.total);
@@ -524,17 +539,28 @@ export default function OrderList() {
               undefined, // data_inicio
               undefined, // data_fim
               undefined, // tipo_producao
-              true // is_pronto
+              true, // is_pronto
+              undefined, // date_mode
+              ORDERS_LIST_REQUEST_ID
             );
             all = paginated.orders;
           } else {
-            all = await api.getReadyOrdersLight();
+            all = await api.getReadyOrdersLight(ORDERS_LIST_REQUEST_ID);
           }
           setOrders(all);
           setTotalPages(Math.ceil(all.length / currentPageSize) || 1);
           setTotalOrders(all.length);
         } else {
-          const paginatedData = await api.getReadyOrdersPaginated(currentPage + 1, currentPageSize);
+          const paginatedData = await api.getReadyOrdersPaginated(
+            currentPage + 1,
+            currentPageSize,
+            undefined,
+            undefined,
+            undefined,
+            undefined,
+            undefined,
+            ORDERS_LIST_REQUEST_ID
+          );
           setOrders(paginatedData.orders);
           setTotalPages(paginatedData.total_pages);
           setTotalOrders(paginatedData.total);
@@ -557,6 +583,12 @@ export default function OrderList() {
         logger.error('[OrderList] Erro ao carregar logs para badges:', logErr);
       });
     } catch (error) {
+      // Substituída por um carregamento mais novo: a lista fica com o resultado dele
+      if (error instanceof NativeApiError && error.kind === 'cancelled') {
+        superseded = true;
+        logger.debug('[OrderList] Carregamento anterior cancelado');
+        return;
+      }
       const message = extractErrorMessage(error);
       if (isSessionError(message)) {
         toast({
@@ -576,7 +608,7 @@ export default function OrderList() {
         logger.error('Error loading orders:', error);
       }
     } finally {
-      setLoading(false);
+      if (!superseded) setLoading(false);
     }
   }, [
     dateFrom,
@@ -618,6 +650,13 @@ export default function OrderList() {
     loadOrders();
   }, [loadOrders]);
 
+  // Ao sair da lista, a leitura em andamento não precisa terminar
+  useEffect(() => {
+    return () => {
+      hybridClient.cancelRequest(ORDERS_LIST_REQUEST_ID).catch(() => undefined);
+    };
+  }, []);
+
   // Recarregar pedidos quando o modal de visualização for fechado
   useEffect(() => {
     if (!useModalStore.getState().viewModalOpen) {
@@ -740,7 +779,7 @@ export default function OrderList() {
         // Imagem do item (se existir)
         if (item.imagem && isValidImagePath(item.imagem)) {
           try {
-            const imageUrl = await loadAuthenticatedImage(item.imagem);
+            const imageUrl = await loadImagePreviewSrc(item.imagem, 1024);
             const img = document.createElement('img');
             img.src = imageUrl;
             img.style.maxWidth = '100%';
@@ -9,7 +9,7 @@ import { api } from '../services/api';
 import { getItemDisplayEntries, FIELD_ALLOWED_TYPES } from '@/utils/order-item-display';
 import { logger } from '@/utils/logger';
 import { isValidImagePath } from '@/utils/path';
-import { loadAuthenticatedImage } from '@/utils/imageLoader';
+import { loadImagePreviewSrc } from '@/utils/imageLoader';
 import { OrderPrintManager } from './OrderPrintManager';
 import { FormProducaoFields } from './FormProducaoFields';
 import { useToast } from '@/hooks/use-toast';
@@ -89,7 +89,8 @@ export const OrderViewModal: React.FC<OrderViewModalProps> = ({
 
       try {
         logger.debug(`[OrderViewModal] 🔄 Carregando imagem do item ${itemKey}:`, imagePath);
-        const blobUrl = await loadAuthenticatedImage(imagePath);
+        // Cartão do item: miniatura de 512 px quando a imagem já está no disco
+        const blobUrl = await loadImagePreviewSrc(imagePath, 512);
 
         // Atualizar estado com a URL da imagem
         setItemImageUrls(prev => {
@@ -160,7 +161,7 @@ export const OrderViewModal: React.FC<OrderViewModalProps> = ({
   };
 
   // Função para lidar com clique na imagem
-  const handleImageClick = async (imageUrl: string, caption?: string, itemId?: string | number) => {
+  const handleImageClick = async (imageUrl: string, caption?: string) => {
     if (!imageUrl) {
       return;
     }
@@ -182,36 +183,10 @@ export const OrderViewModal: React.FC<OrderViewModalProps> = ({
       setImageError(false);
       setSelectedImage(null);
 
-      // Tentar encontrar a blob URL nos itens já carregados
-      let blobUrl: string | undefined;
-
-      // Se temos o itemId, tentar buscar diretamente pelo itemKey
-      if (itemId !== undefined) {
-        const itemKey = String(itemId);
-        blobUrl = itemImageUrls.get(itemKey);
-        if (blobUrl) {
-          logger.debug('[OrderViewModal] ✅ Usando blob URL do cache para modal (por itemId):', { itemKey, imageUrl });
-        }
-      }
-
-      // Se não encontrou pelo itemId, procurar pelo caminho da imagem
-      if (!blobUrl) {
-        for (const [itemKey, url] of itemImageUrls.entries()) {
-          const item = order?.items?.find(i => String(i.id ?? i.item_name) === itemKey);
-          if (item?.imagem === imageUrl) {
-            blobUrl = url;
-            logger.debug('[OrderViewModal] ✅ Usando blob URL do cache para modal (por caminho):', { itemKey, imageUrl });
-            break;
-          }
-        }
-      }
-
-      // Se não encontrou, carregar a imagem
-      if (!blobUrl) {
-        logger.debug('[OrderViewModal] 🔄 Carregando imagem para modal:', imageUrl);
-        blobUrl = await loadAuthenticatedImage(imageUrl);
-        logger.debug('[OrderViewModal] ✅ Imagem carregada para modal:', blobUrl);
-      }
+      // Não reaproveita a URL do card: ela aponta para a miniatura de 512 px
+      logger.debug('[OrderViewModal] 🔄 Carregando imagem para modal:', imageUrl);
+      const blobUrl = await loadImagePreviewSrc(imageUrl);
+      logger.debug('[OrderViewModal] ✅ Imagem carregada para modal:', blobUrl);
 
       // Verificar se a blob URL é válida
       if (!blobUrl) {
@@ -1325,7 +1300,7 @@ export const OrderViewModal: React.FC<OrderViewModalProps> = ({
                   <Button
                     variant="outline"
                     size="sm"
-                    onClick={() => handleImageClick(item.imagem!, legendaImagem, item.id ?? item.item_name)}
+                    onClick={() => handleImageClick(item.imagem!, legendaImagem)}
                     className="w-full"
                   >
                     Abrir imagem em destaque
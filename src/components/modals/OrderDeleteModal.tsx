@@ -5,6 +5,7 @@ import { useOrderStore } from '@/store/orderStore';
 import { useToast } from '@/hooks/use-toast';
 import { api } from '@/services/api';
 import { logger } from '@/utils/logger';
+import { syncOrderImageReferences } from '@/utils/imageUploader';
 
 export function OrderDeleteModal() {
     const { deleteDialogOpen, orderToDelete, closeDeleteModal } = useModalStore();
@@ -14,8 +15,12 @@ export function OrderDeleteModal() {
     const handleDeleteConfirm = async () => {
         if (orderToDelete) {
             try {
+                const deleted = useOrderStore
+                    .getState()
+                    .orders.find((order) => order.id === orderToDelete);
                 await api.deleteOrder(orderToDelete);
                 removeOrder(orderToDelete);
+                await syncOrderImageReferences(deleted ?? null, null);
                 toast({
                     title: 'Pedido excluído',
                     description: 'O pedido foi excluído com sucesso!',
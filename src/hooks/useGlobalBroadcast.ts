@@ -1,6 +1,7 @@
 import { useState, useEffect, useCallback, useRef } from 'react';
-// import { invoke } from '@tauri-apps/api/core';
-// import { listen } from '@tauri-apps/api/event';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { isTauri } from '@/utils/isTauri';
 
 // Tipos para o sistema de broadcast global
 export interface OrderNotification {
@@ -21,6 +22,13 @@ export interface BroadcastStatus {
   clientId: string;
 }
 
+// Estado de presença mantido pelo core (bridge/presence.rs)
+interface NativePresenceStatus {
+  state: 'unknown' | 'online' | 'degraded' | 'offline' | 'unsupported';
+  client_id: string;
+  last_success_at: string | null;
+}
+
 export interface UseGlobalBroadcastReturn {
   status: BroadcastStatus;
   subscribe: (clientId: string) => Promise<void>;
@@ -49,7 +57,8 @@ export function useGlobalBroadcast(): UseGlobalBroadcastReturn {
 
       setStatus(prev => ({
         ...prev,
-        isConnected: false, // Mock: sempre desconectado
+        // No desktop o heartbeat vai pelo core; no navegador segue desconectado
+        isConnected: isTauri(),
         clientId: id,
       }));
 
@@ -78,8 +87,15 @@ export function useGlobalBroadcast(): UseGlobalBroadcastReturn {
   const sendHeartbeat = useCallback(async () => {
     try {
       if (status.clientId) {
-        // MOCK: Sem backend Rust
-        // await invoke('send_heartbeat', { clientId: status.clientId });
+        if (isTauri()) {
+          const presence = await invoke<NativePresenceStatus>('send_heartbeat', { clientId: status.clientId });
+          setStatus(prev => ({
+            ...prev,
+            isConnected: presence.state !== 'offline',
+            lastHeartbeat: presence.last_success_at ? new Date(presence.last_success_at) : prev.lastHeartbeat,
+          }));
+          return;
+        }
         setStatus(prev => ({
           ...prev,
           lastHeartbeat: new Date(),
@@ -121,9 +137,41 @@ export function useGlobalBroadcast(): UseGlobalBroadcastReturn {
     }
   }, []);
 
-  // Heartbeat automático a cada 30 segundos
+  // No desktop o core já envia o heartbeat periódico; aqui só acompanhamos o estado
+  useEffect(() => {
+    if (!isTauri()) return;
+
+    const applyPresence = (presence: NativePresenceStatus) => {
+      setStatus(prev => ({
+        ...prev,
+        isConnected: presence.state !== 'offline',
+        lastHeartbeat: presence.last_success_at ? new Date(presence.last_success_at) : prev.lastHeartbeat,
+      }));
+    };
+
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+
+    invoke<NativePresenceStatus>('presence_status').then(applyPresence).catch(() => {});
+    listen<NativePresenceStatus>('presence-status', (event) => applyPresence(event.payload))
+      .then((fn) => {
+        if (cancelled) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
+      })
+      .catch(() => {});
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+
+  // Heartbeat automático a cada 30 segundos (navegador)
   useEffect(() => {
-    if (!status.isConnected) return;
+    if (isTauri() || !status.isConnected) return;
 
     const heartbeatInterval = setInterval(() => {
       sendHeartbeat();
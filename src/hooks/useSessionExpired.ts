@@ -0,0 +1,50 @@
+import { useEffect } from 'react';
+import { useNavigate } from 'react-router-dom';
+import { listen } from '@tauri-apps/api/event';
+import { useAuthStore } from '../store/authStore';
+import { toast } from './use-toast';
+import { isTauri } from '../utils/isTauri';
+import { logger } from '../utils/logger';
+
+/** Evento emitido pelo core (`bridge::auth::EVENT_SESSION_EXPIRED`) */
+export const SESSION_EXPIRED_EVENT = 'session-expired';
+
+/**
+ * Quando a ponte recebe 401 e não consegue renovar o token, o core avisa uma
+ * única vez: encerra a sessão e leva para o login, de qualquer tela.
+ */
+export function useSessionExpired() {
+  const navigate = useNavigate();
+
+  useEffect(() => {
+    if (!isTauri()) return;
+
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+
+    listen<{ message?: string }>(SESSION_EXPIRED_EVENT, (event) => {
+      logger.warn('[Auth] Sessão expirada pelo core:', event.payload?.message);
+      if (!useAuthStore.getState().isAuthenticated) return;
+      useAuthStore.getState().logout();
+      toast({
+        title: 'Sessão expirada',
+        description: 'Faça login novamente para continuar.',
+        variant: 'destructive',
+      });
+      navigate('/login', { replace: true });
+    })
+      .then((fn) => {
+        if (cancelled) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
+      })
+      .catch((err) => logger.error('[Auth] Erro ao escutar sessão expirada:', err));
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [navigate]);
+}
@@ -1,4 +1,7 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { getApiUrl } from '@/api/client';
+import { isTauri } from '@/utils/isTauri';
 import { useAuthStore } from '@/store/authStore';
 import { logger } from '@/utils/logger';
 
@@ -16,6 +19,15 @@ export interface OrderEventMessage {
   [key: string]: unknown;
 }
 
+/** Espelha `RealtimeStatus` (src-tauri/src/bridge/realtime.rs) */
+interface NativeRealtimeStatus {
+  connected: boolean;
+  reconnect_attempts: number;
+  last_event_at: string | null;
+  last_error: string | null;
+  halted: boolean;
+}
+
 type MessageListener = (message: OrderEventMessage) => void;
 type StatusListener = (status: OrdersSocketStatus) => void;
 
@@ -39,6 +51,8 @@ class OrdersWebSocketManager {
   private isConnecting = false; // Flag para evitar múltiplas conexões simultâneas
   private lastReconnectAttempt = 0; // Timestamp da última tentativa de reconexão
   private minReconnectInterval = 2000; // Mínimo de 2 segundos entre tentativas
+  // No Tauri a conexão é mantida pelo core Rust; aqui só ouvimos os eventos
+  private nativeUnlisten: UnlistenFn[] | null = null;
 
   constructor() {
     if (typeof window !== 'undefined') {
@@ -101,6 +115,12 @@ class OrdersWebSocketManager {
   subscribe(listener: MessageListener): () => void {
     this.listeners.add(listener);
     this.shouldStayConnected = true;
+    if (isTauri()) {
+      this.startNative();
+      return () => {
+        this.listeners.delete(listener);
+      };
+    }
     this.ensureConnection();
     return () => {
       this.listeners.delete(listener);
@@ -120,11 +140,19 @@ class OrdersWebSocketManager {
 
   connect(): void {
     this.shouldStayConnected = true;
+    if (isTauri()) {
+      this.startNative();
+      return;
+    }
     this.ensureConnection(true);
   }
 
   disconnect(): void {
     this.shouldStayConnected = false;
+    if (isTauri()) {
+      invoke('realtime_disconnect').catch((error) => logger.warn('Falha ao desconectar WebSocket nativo:', error));
+      return;
+    }
     this.clearReconnectTimer();
     this.stopPing();
     if (this.socket && this.socket.readyState === WebSocket.OPEN) {
@@ -565,6 +593,52 @@ class OrdersWebSocketManager {
     this.disconnect();
   }
 
+  /**
+   * Assina todos os tipos de mensagem desta janela no core e repassa os eventos
+   * aos listeners. A assinatura no Rust é idempotente, então reloads não duplicam.
+   */
+  private startNative(): void {
+    if (this.nativeUnlisten) {
+      invoke('realtime_connect').catch(() => undefined);
+      return;
+    }
+    const unlisten: UnlistenFn[] = [];
+    this.nativeUnlisten = unlisten;
+
+    listen<OrderEventMessage>('realtime-event', (event) => {
+      this.updateStatus({ lastEventAt: Date.now() });
+      this.listeners.forEach((listener) => {
+        try {
+          listener(event.payload);
+        } catch (error) {
+          logger.error('❌ Erro ao processar listener do WebSocket:', error);
+        }
+      });
+    }).then((fn) => unlisten.push(fn));
+
+    listen<NativeRealtimeStatus>('realtime-status', (event) => {
+      const native = event.payload;
+      this.updateStatus({
+        isConnected: native.connected,
+        reconnectAttempts: native.reconnect_attempts,
+        lastEventAt: native.last_event_at ? Date.parse(native.last_event_at) : this.status.lastEventAt,
+        lastError: native.last_error ?? undefined,
+      });
+    }).then((fn) => unlisten.push(fn));
+
+    invoke('realtime_subscribe', { topics: ['*'] })
+      .then(() => invoke('realtime_connect'))
+      .then(() => invoke<NativeRealtimeStatus>('realtime_status'))
+      .then((native) =>
+        this.updateStatus({
+          isConnected: native.connected,
+          reconnectAttempts: native.reconnect_attempts,
+          lastError: native.last_error ?? undefined,
+        })
+      )
+      .catch((error) => logger.error('❌ Falha ao iniciar WebSocket nativo:', error));
+  }
+
   private updateStatus(patch: Partial<OrdersSocketStatus>): void {
     this.status = {
       ...this.status,
@@ -585,6 +659,16 @@ class OrdersWebSocketManager {
    * @returns true se a mensagem foi enviada, false caso contrário
    */
   sendMessage(message: OrderEventMessage): boolean {
+    if (isTauri()) {
+      if (!this.status.isConnected) {
+        return false;
+      }
+      invoke('realtime_send', { message }).catch((error) =>
+        logger.error('❌ Erro ao enviar mensagem pelo WebSocket nativo:', error)
+      );
+      return true;
+    }
+
     if (!this.socket || this.socket.readyState !== WebSocket.OPEN) {
       if (import.meta.env.DEV) {
         logger.warn('⚠️ WebSocket não conectado, não foi possível enviar mensagem:', message);
@@ -1,4 +1,4 @@
-import { useState, useEffect, useMemo } from 'react';
+import { useState, useEffect, useMemo, useRef } from 'react';
 import { Card, CardContent } from '@/components/ui/card';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
 import { Input } from '@/components/ui/input';
@@ -32,12 +32,16 @@ import {
 } from 'recharts';
 import Papa from 'papaparse';
 import { api } from '@/services/api';
+import { hybridClient } from '@/services/hybridClient';
 import { PrintLog, PrintLogStatus, OrderWithItems } from '@/types';
 import { MachineEntity } from '@/api/types';
 import { useLazyImage } from '@/hooks/useLazyImage';
 import { OrderViewModal } from '@/components/OrderViewModal';
 import { useToast } from '@/hooks/use-toast';
 
+/** Reusar o id faz uma nova leitura interromper a anterior no core */
+const PRINT_LOGS_STREAM_ID = 'print-logs-page';
+
 export default function PrintLogsPage() {
     const [machines, setMachines] = useState<MachineEntity[]>([]);
     const [selectedMachine, setSelectedMachine] = useState<number | null>(null);
@@ -60,9 +64,15 @@ export default function PrintLogsPage() {
     const [loadingOrder, setLoadingOrder] = useState(false);
 
     const { toast } = useToast();
+    // Só a leitura mais recente pode atualizar a tela
+    const loadGeneration = useRef(0);
 
     useEffect(() => {
         loadMachines();
+        return () => {
+            loadGeneration.current += 1;
+            hybridClient.cancelRequest(PRINT_LOGS_STREAM_ID).catch(() => undefined);
+        };
     }, []);
 
     useEffect(() => {
@@ -95,20 +105,31 @@ export default function PrintLogsPage() {
     };
 
     const loadLogs = async () => {
+        const generation = ++loadGeneration.current;
+        const isCurrent = () => generation === loadGeneration.current;
         try {
             if (!loading) setLoading(true);
-            let data: PrintLog[];
-            const limit = parseInt(logLimit);
-            if (selectedMachine === null) {
-                data = await api.getAllLogs(limit, 0, undefined, startDate, endDate);
-            } else {
-                data = await api.getPrinterLogs(selectedMachine, limit, 0, undefined, startDate, endDate);
-            }
-            setLogs(data);
+            const received: PrintLog[] = [];
+            // As páginas aparecem conforme chegam, sem esperar o histórico inteiro
+            await api.streamAllLogs(
+                (page) => {
+                    if (!isCurrent()) return;
+                    received.push(...page);
+                    setLogs([...received]);
+                },
+                {
+                    printerId: selectedMachine ?? undefined,
+                    maxItems: parseInt(logLimit),
+                    dataInicio: startDate,
+                    dataFim: endDate,
+                    streamId: PRINT_LOGS_STREAM_ID,
+                }
+            );
+            if (isCurrent() && received.length === 0) setLogs([]);
         } catch {
           // noop
         } finally {
-            setLoading(false);
+            if (isCurrent()) setLoading(false);
         }
     };
 
@@ -52,11 +52,13 @@ export default function RelatoriosEnvios() {
       dataCriacaoInicio.setDate(dataCriacaoInicio.getDate() - 90);
       const dataCriacaoInicioStr = dataCriacaoInicio.toISOString().split('T')[0];
 
-      // A API também tem um limite padrão de 20 itens. Vamos aumentar para 1000.
-      const pedidosResponse = await api.getRelatorioEnviosPedidos(
-        dataCriacaoInicioStr,
-        undefined, // Sem limite final de criação para não perder nada
-        { pageSize: 1000 }
+      // Lê todos os pedidos criados desde então, página a página
+      // (sem limite final de criação para não perder nada)
+      const pedidosResponse: OrderWithItems[] = [];
+      await api.streamOrders(
+        (page) => pedidosResponse.push(...page),
+        { data_inicio: dataCriacaoInicioStr },
+        { streamId: 'relatorio-envios' }
       );
 
       // Filtro Rigoroso no Frontend: Apenas pedidos onde a data_entrega está no intervalo solicitado
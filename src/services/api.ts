@@ -20,18 +20,18 @@ export const api = {
   getOrders: ordersApi.getOrders,
   getOrdersPaginated: ordersApi.getOrdersPaginated,
   getOrdersPaginatedForTable: ordersApi.getOrdersPaginated, // Alias for now, as implementation is identical in logic
-  getPendingOrdersLight: async () => {
+  getPendingOrdersLight: async (requestId?: string) => {
     // Busca até 500 pedidos pendentes reais do backend
-    const paginated = await ordersApi.getOrdersPaginated(1, 500, undefined, undefined, undefined, undefined, undefined, false);
+    const paginated = await ordersApi.getOrdersPaginated(1, 500, undefined, undefined, undefined, undefined, undefined, false, undefined, requestId);
     return paginated.orders;
   },
   getPendingOrdersPaginated: async (page?: number, pageSize?: number) =>
     ordersApi.getOrdersPaginated(page, pageSize, OrderStatus.Pendente),
-  getReadyOrdersPaginated: async (page?: number, pageSize?: number, status?: OrderStatus, cliente?: string, date_from?: string, date_to?: string, tipo_producao?: string) =>
-    ordersApi.getOrdersPaginated(page, pageSize, status, cliente, date_from, date_to, tipo_producao, true),
-  getReadyOrdersLight: async () => {
+  getReadyOrdersPaginated: async (page?: number, pageSize?: number, status?: OrderStatus, cliente?: string, date_from?: string, date_to?: string, tipo_producao?: string, requestId?: string) =>
+    ordersApi.getOrdersPaginated(page, pageSize, status, cliente, date_from, date_to, tipo_producao, true, undefined, requestId),
+  getReadyOrdersLight: async (requestId?: string) => {
     // Busca até 500 pedidos prontos limpos do backend (is_pronto = true)
-    const paginated = await ordersApi.getOrdersPaginated(1, 500, undefined, undefined, undefined, undefined, undefined, true);
+    const paginated = await ordersApi.getOrdersPaginated(1, 500, undefined, undefined, undefined, undefined, undefined, true, undefined, requestId);
     return paginated.orders;
   },
   getOrderById: ordersApi.getOrderById,
@@ -49,7 +49,7 @@ export const api = {
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
   getOrdersWithFilters: async (filters: any) => ordersApi.getOrdersPaginated(filters.page, filters.page_size, filters.status, filters.cliente, filters.date_from, filters.date_to, filters.tipo_producao),
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
-  getOrdersWithFiltersForTable: async (filters: any) => ordersApi.getOrdersPaginated(filters.page, filters.page_size, filters.status, filters.cliente, filters.date_from, filters.date_to, filters.tipo_producao),
+  getOrdersWithFiltersForTable: async (filters: any, requestId?: string) => ordersApi.getOrdersPaginated(filters.page, filters.page_size, filters.status, filters.cliente, filters.date_from, filters.date_to, filters.tipo_producao, undefined, undefined, requestId),
   getDashboardSummary: ordersApi.getDashboardSummary,
   getTotalOrdersCount: ordersApi.getTotalOrdersCount,
   getOrderHistory: async (_?: number) => [],
@@ -60,6 +60,7 @@ export const api = {
   getRelatorioEnviosPedidos: async (start: string, end?: string, options?: any) => {
     return ordersApi.getOrdersPaginated(options?.page, options?.pageSize, options?.status, options?.cliente, start, end).then(r => r.orders);
   },
+  streamOrders: ordersApi.streamOrders,
 
   // Customers
   getClientes: customersApi.getClientes,
@@ -97,6 +98,7 @@ export const api = {
   // Print Logs
   getPrinterLogs: printLogsApi.getPrinterLogs,
   getAllLogs: printLogsApi.getAllLogs,
+  streamAllLogs: (...args: Parameters<typeof printLogsApi.streamAllLogs>) => printLogsApi.streamAllLogs(...args),
   createPrintLog: printLogsApi.createPrintLog,
   getPrinterStats: printLogsApi.getPrinterStats,
 
@@ -1,24 +1,110 @@
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { logger } from '../utils/logger';
 import { isTauri } from '../utils/isTauri';
 import { apiClient } from '../api/client';
 
-const formatNativeError = (err: unknown): string =>
-    typeof err === 'string' ? err : JSON.stringify(err);
+/** Espelha `AppError` (src-tauri/src/error.rs) retornado pelos comandos Rust */
+export type NativeErrorKind =
+    | 'auth'
+    | 'timeout'
+    | 'network'
+    | 'validation'
+    | 'not_found'
+    | 'conflict'
+    | 'server'
+    | 'http'
+    | 'io'
+    | 'parse'
+    | 'config'
+    | 'cancelled'
+    | 'permission'
+    | 'internal';
+
+interface NativeErrorPayload {
+    kind: NativeErrorKind;
+    status: number | null;
+    retryable: boolean;
+    body: unknown;
+    message: string;
+    queue_id: string | null;
+}
+
+export class NativeApiError extends Error {
+    kind: NativeErrorKind;
+    status: number | null;
+    retryable: boolean;
+    body: unknown;
+    /** Id na fila offline quando a mutação foi guardada para reenvio */
+    queueId: string | null;
+
+    constructor(payload: NativeErrorPayload) {
+        super(payload.message);
+        this.name = 'NativeApiError';
+        this.kind = payload.kind;
+        this.status = payload.status;
+        this.retryable = payload.retryable;
+        this.body = payload.body;
+        this.queueId = payload.queue_id ?? null;
+    }
+}
+
+const isNativeErrorPayload = (err: unknown): err is NativeErrorPayload =>
+    typeof err === 'object' && err !== null && 'kind' in err && 'message' in err;
+
+const toNativeError = (err: unknown): Error => {
+    if (isNativeErrorPayload(err)) return new NativeApiError(err);
+    return new Error(typeof err === 'string' ? err : JSON.stringify(err));
+};
+
+/** Espelha `PagedQuery` (src-tauri/src/bridge/paging.rs) */
+export interface PagedQuery {
+    endpoint: string;
+    params?: Record<string, string>;
+    page_size?: number;
+    max_items?: number;
+    strategy?:
+        | { mode: 'offset'; offset_param?: string; limit_param?: string }
+        | { mode: 'cursor'; cursor_param?: string; limit_param?: string; next_cursor_field?: string };
+    items_field?: string;
+}
+
+export type PageEvent<T> =
+    | { event: 'page'; index: number; items: T[]; delivered: number }
+    | { event: 'done'; pages: number; items: number; truncated: boolean };
+
+/** Espelha `StoredSession` (src-tauri/src/commands/vault.rs) */
+export interface StoredSession {
+    token: string;
+    user_id: number | null;
+    username: string | null;
+    role: string;
+}
+
+/** Espelha `VaultInfo`; `session` só vem em `loadStoredCredentials` */
+export interface StoredCredentials {
+    base_url: string;
+    has_token: boolean;
+    saved_at: string;
+    session?: StoredSession;
+}
 
 export const hybridClient = {
-    get: async <T>(endpoint: string, params?: Record<string, unknown>): Promise<T> => {
+    /**
+     * `requestId` permite cancelar a chamada via `cancelRequest`; reutilizar o mesmo id
+     * cancela a chamada anterior, então respostas obsoletas nunca chegam.
+     */
+    get: async <T>(endpoint: string, params?: Record<string, unknown>, requestId?: string): Promise<T> => {
         if (!isTauri()) {
             const resp = await apiClient.get<T>(endpoint, { params });
             return resp.data as unknown as T;
         }
 
         try {
-            return await invoke<T>('rust_api_get', { endpoint, params });
+            return await invoke<T>('rust_api_get', { endpoint, params, requestId });
         } catch (err: unknown) {
-            const errorMsg = formatNativeError(err);
-            logger.error(`[Hybrid Client GET] Erro nativo em ${endpoint}:`, errorMsg);
-            throw new Error(errorMsg);
+            const error = toNativeError(err);
+            logger.error(`[Hybrid Client GET] Erro nativo em ${endpoint}:`, error.message);
+            throw error;
         }
     },
 
@@ -30,9 +116,9 @@ export const hybridClient = {
         try {
             return await invoke<T>('rust_api_mutate', { method: 'POST', endpoint, body });
         } catch (err: unknown) {
-            const errorMsg = formatNativeError(err);
-            logger.error(`[Hybrid Client POST] Erro nativo em ${endpoint}:`, errorMsg);
-            throw new Error(errorMsg);
+            const error = toNativeError(err);
+            logger.error(`[Hybrid Client POST] Erro nativo em ${endpoint}:`, error.message);
+            throw error;
         }
     },
 
@@ -44,9 +130,9 @@ export const hybridClient = {
         try {
             return await invoke<T>('rust_api_mutate', { method: 'PATCH', endpoint, body });
         } catch (err: unknown) {
-            const errorMsg = formatNativeError(err);
-            logger.error(`[Hybrid Client PATCH] Erro nativo em ${endpoint}:`, errorMsg);
-            throw new Error(errorMsg);
+            const error = toNativeError(err);
+            logger.error(`[Hybrid Client PATCH] Erro nativo em ${endpoint}:`, error.message);
+            throw error;
         }
     },
 
@@ -58,9 +144,73 @@ export const hybridClient = {
         try {
             return await invoke<T>('rust_api_mutate', { method: 'DELETE', endpoint, body });
         } catch (err: unknown) {
-            const errorMsg = formatNativeError(err);
-            logger.error(`[Hybrid Client DELETE] Erro nativo em ${endpoint}:`, errorMsg);
-            throw new Error(errorMsg);
+            const error = toNativeError(err);
+            logger.error(`[Hybrid Client DELETE] Erro nativo em ${endpoint}:`, error.message);
+            throw error;
+        }
+    },
+
+    /**
+     * Lê um endpoint paginado entregando cada página em `onPage` conforme chega.
+     * Passe `streamId` para poder interromper com `cancelRequest(streamId)`.
+     */
+    streamPages: async <T>(
+        query: PagedQuery,
+        onPage: (items: T[], delivered: number) => void,
+        streamId?: string
+    ): Promise<{ pages: number; items: number; truncated: boolean }> => {
+        const channel = new Channel<PageEvent<T>>();
+        let summary = { pages: 0, items: 0, truncated: false };
+        channel.onmessage = (message) => {
+            if (message.event === 'page') {
+                onPage(message.items, message.delivered);
+            } else {
+                summary = { pages: message.pages, items: message.items, truncated: message.truncated };
+            }
+        };
+        try {
+            await invoke('rust_api_get_paged', { query, streamId, onPage: channel });
+        } catch (err: unknown) {
+            throw toNativeError(err);
+        }
+        return summary;
+    },
+
+    /** Cancela a chamada nativa com esse id; retorna `false` se ela já terminou */
+    cancelRequest: async (requestId: string): Promise<boolean> => {
+        if (!isTauri()) return false;
+        return await invoke<boolean>('cancel_request', { requestId });
+    },
+
+    /** Grava base URL e token cifrados no cofre do Rust (sobrevive a reinícios) */
+    persistCredentials: async (baseUrl: string, token: string | null): Promise<void> => {
+        if (!isTauri()) return;
+        await invoke('vault_store', { baseUrl, token });
+    },
+
+    /**
+     * Restaura as credenciais do cofre na ponte. Se o token veio de um login,
+     * devolve a sessão (token, usuário e papel) para o WebView voltar logado.
+     */
+    loadStoredCredentials: async (): Promise<StoredCredentials | null> => {
+        if (!isTauri()) return null;
+        return await invoke<StoredCredentials | null>('vault_load');
+    },
+
+    wipeCredentials: async (): Promise<void> => {
+        if (!isTauri()) return;
+        await invoke('vault_wipe');
+    },
+
+    /**
+     * Faz o login pelo Rust, que guarda o token e o papel lidos da resposta da API.
+     * O papel usado pela política de endpoints nunca vem do WebView.
+     */
+    login: async <T>(username: string, password: string): Promise<T> => {
+        try {
+            return await invoke<T>('auth_login', { username, password });
+        } catch (err: unknown) {
+            throw toNativeError(err);
         }
     },
 
@@ -1,5 +1,9 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
+import { getApiUrl } from '../api/client';
+import { hybridClient } from '../services/hybridClient';
+import { isTauri } from '../utils/isTauri';
+import { logger } from '../utils/logger';
 
 const DEFAULT_SESSION_TTL_MS = 1000 * 60 * 60 * 8; // 8 horas
 
@@ -32,6 +36,54 @@ const createInitialState = (): Omit<AuthState, 'login' | 'logout'> => ({
   sessionExpiresAt: null,
 });
 
+/**
+ * No desktop o token fica só em memória no WebView; entre execuções ele vive
+ * cifrado no cofre do Rust, que o restaura para a ponte ao abrir o app.
+ */
+const persistToVault = (sessionToken: string) => {
+  if (!isTauri()) return;
+  hybridClient.persistCredentials(getApiUrl(), sessionToken).catch((err) => {
+    logger.warn('[Auth] Falha ao gravar sessão no cofre:', err);
+  });
+};
+
+const wipeVault = () => {
+  if (!isTauri()) return;
+  hybridClient.wipeCredentials().catch((err) => {
+    logger.warn('[Auth] Falha ao apagar sessão do cofre:', err);
+  });
+};
+
+/**
+ * Na abertura do desktop, o cofre devolve a sessão salva no login: o token
+ * volta para a memória e usuário e papel vêm do que o core leu da API.
+ * Cofre vazio ou ilegível (outra máquina) pede um novo login.
+ */
+const restoreFromVault = () => {
+  hybridClient
+    .loadStoredCredentials()
+    .then((credentials) => {
+      const session = credentials?.session;
+      if (!session) {
+        useAuthStore.setState(createInitialState());
+        return;
+      }
+      const current = useAuthStore.getState();
+      useAuthStore.setState({
+        isAuthenticated: true,
+        sessionToken: session.token,
+        userId: session.user_id ?? current.userId,
+        username: session.username ?? current.username,
+        isAdmin: session.role === 'admin',
+        setor: current.setor || 'geral',
+      });
+    })
+    .catch((err) => {
+      logger.warn('[Auth] Falha ao restaurar sessão do cofre:', err);
+      useAuthStore.setState(createInitialState());
+    });
+};
+
 export const useAuthStore = create<AuthState>()(
   persist(
     (set) => ({
@@ -50,8 +102,12 @@ export const useAuthStore = create<AuthState>()(
           sessionToken,
           sessionExpiresAt: Date.now() + ttlMs,
         });
+        persistToVault(sessionToken);
+      },
+      logout: () => {
+        set(createInitialState());
+        wipeVault();
       },
-      logout: () => set(createInitialState()),
     }),
     {
       name: 'auth-storage',
@@ -60,8 +116,19 @@ export const useAuthStore = create<AuthState>()(
           return;
         }
         queueMicrotask(() => {
-          const { sessionToken, sessionExpiresAt, logout } = state;
-          if (!sessionToken || !sessionExpiresAt || sessionExpiresAt <= Date.now()) {
+          const { isAuthenticated, sessionToken, sessionExpiresAt, logout } = state;
+          if (!isAuthenticated) {
+            return;
+          }
+          if (!sessionExpiresAt || sessionExpiresAt <= Date.now()) {
+            logout();
+            return;
+          }
+          if (isTauri()) {
+            restoreFromVault();
+            return;
+          }
+          if (!sessionToken) {
             logout();
           }
         });
@@ -72,7 +139,8 @@ export const useAuthStore = create<AuthState>()(
         username: state.username,
         isAdmin: state.isAdmin,
         setor: state.setor,
-        sessionToken: state.sessionToken,
+        // No desktop o token não vai para o localStorage (ver persistToVault)
+        sessionToken: isTauri() ? null : state.sessionToken,
         sessionExpiresAt: state.sessionExpiresAt,
       }),
     }
@@ -1,6 +1,12 @@
 import { describe, it, expect, beforeEach, vi, afterEach } from 'vitest';
+import { invoke } from '@tauri-apps/api/core';
 import { useAuthStore } from '../store/authStore';
 
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn().mockResolvedValue(null),
+  Channel: class {},
+}));
+
 const resetStore = () => {
   useAuthStore.setState({
     isAuthenticated: false,
@@ -67,5 +73,83 @@ describe('Auth Store', () => {
     expect(state.sessionToken).toBe(null);
     expect(state.sessionExpiresAt).toBe(null);
   });
-});
 
+  describe('no desktop', () => {
+    const win = window as unknown as Record<string, unknown>;
+
+    beforeEach(() => {
+      win.__TAURI_INTERNALS__ = {};
+      vi.mocked(invoke).mockClear();
+    });
+
+    afterEach(() => {
+      delete win.__TAURI_INTERNALS__;
+    });
+
+    it('keeps the token out of localStorage and stores it in the vault', () => {
+      const { login } = useAuthStore.getState();
+      login({ userId: 1, username: 'testuser', sessionToken: 'token123' });
+
+      expect(useAuthStore.getState().sessionToken).toBe('token123');
+      const persisted = JSON.parse(localStorage.getItem('auth-storage') ?? '{}');
+      expect(persisted.state.isAuthenticated).toBe(true);
+      expect(persisted.state.sessionToken).toBe(null);
+      expect(invoke).toHaveBeenCalledWith('vault_store', expect.objectContaining({ token: 'token123' }));
+    });
+
+    it('wipes the vault on logout', () => {
+      const { login, logout } = useAuthStore.getState();
+      login({ userId: 1, username: 'testuser', sessionToken: 'token123' });
+      logout();
+
+      expect(invoke).toHaveBeenCalledWith('vault_wipe');
+    });
+
+    const persistLoggedIn = () => {
+      localStorage.setItem(
+        'auth-storage',
+        JSON.stringify({
+          state: {
+            isAuthenticated: true,
+            userId: 1,
+            username: 'testuser',
+            isAdmin: false,
+            setor: 'geral',
+            sessionToken: null,
+            sessionExpiresAt: Date.now() + 60_000,
+          },
+          version: 0,
+        })
+      );
+    };
+
+    it('restores the session from the vault on launch', async () => {
+      persistLoggedIn();
+      vi.mocked(invoke).mockResolvedValueOnce({
+        base_url: 'http://api',
+        has_token: true,
+        saved_at: '2026-01-01T00:00:00Z',
+        session: { token: 'vault-token', user_id: 1, username: 'testuser', role: 'admin' },
+      });
+
+      await useAuthStore.persist.rehydrate();
+
+      await vi.waitFor(() => expect(useAuthStore.getState().sessionToken).toBe('vault-token'));
+      const state = useAuthStore.getState();
+      expect(state.isAuthenticated).toBe(true);
+      expect(state.username).toBe('testuser');
+      expect(state.isAdmin).toBe(true);
+      expect(invoke).toHaveBeenCalledWith('vault_load');
+    });
+
+    it('asks for a new login when the vault is empty', async () => {
+      persistLoggedIn();
+      vi.mocked(invoke).mockResolvedValueOnce(null);
+
+      await useAuthStore.persist.rehydrate();
+
+      await vi.waitFor(() => expect(useAuthStore.getState().isAuthenticated).toBe(false));
+      expect(useAuthStore.getState().sessionToken).toBe(null);
+    });
+  });
+});
@@ -0,0 +1,64 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { renderHook, waitFor } from '@testing-library/react';
+import { listen } from '@tauri-apps/api/event';
+import { useSessionExpired, SESSION_EXPIRED_EVENT } from '@/hooks/useSessionExpired';
+import { useAuthStore } from '@/store/authStore';
+
+const navigate = vi.fn();
+
+vi.mock('react-router-dom', async (importOriginal) => ({
+  ...(await importOriginal<typeof import('react-router-dom')>()),
+  useNavigate: () => navigate,
+}));
+
+vi.mock('@/utils/isTauri', () => ({
+  isTauri: () => true,
+}));
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(() => Promise.resolve(null)),
+}));
+
+vi.mock('@tauri-apps/api/event', () => ({
+  listen: vi.fn(() => Promise.resolve(() => {})),
+}));
+
+type Handler = (event: { payload: { message?: string } }) => void;
+
+describe('useSessionExpired', () => {
+  beforeEach(() => {
+    navigate.mockClear();
+    vi.mocked(listen).mockClear();
+    useAuthStore.setState({
+      isAuthenticated: true,
+      username: 'testuser',
+      userId: 1,
+      sessionToken: 'token',
+    });
+  });
+
+  it('encerra a sessão e leva ao login quando o core avisa', async () => {
+    renderHook(() => useSessionExpired());
+
+    await waitFor(() => expect(listen).toHaveBeenCalled());
+    const [event, handler] = vi.mocked(listen).mock.calls[0] as unknown as [string, Handler];
+    expect(event).toBe(SESSION_EXPIRED_EVENT);
+
+    handler({ payload: { message: '401' } });
+
+    expect(useAuthStore.getState().isAuthenticated).toBe(false);
+    expect(useAuthStore.getState().sessionToken).toBe(null);
+    expect(navigate).toHaveBeenCalledWith('/login', { replace: true });
+  });
+
+  it('ignora o aviso quando já não há sessão', async () => {
+    useAuthStore.setState({ isAuthenticated: false, sessionToken: null });
+    renderHook(() => useSessionExpired());
+
+    await waitFor(() => expect(listen).toHaveBeenCalled());
+    const [, handler] = vi.mocked(listen).mock.calls[0] as unknown as [string, Handler];
+    handler({ payload: {} });
+
+    expect(navigate).not.toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,91 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { invoke } from '@tauri-apps/api/core';
+import { syncOrderImageReferences } from '@/utils/imageUploader';
+
+vi.mock('@/utils/isTauri', () => ({
+  isTauri: () => true,
+}));
+
+vi.mock('@/api/client', () => ({
+  apiClient: { post: vi.fn() },
+}));
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(() => Promise.resolve(1)),
+  convertFileSrc: vi.fn((path: string) => path),
+}));
+
+const invokeMock = vi.mocked(invoke);
+
+const calls = (command: string) =>
+  invokeMock.mock.calls
+    .filter(([name]) => name === command)
+    .map(([, args]) => args);
+
+describe('syncOrderImageReferences', () => {
+  beforeEach(() => {
+    invokeMock.mockClear();
+  });
+
+  it('registra as imagens de um pedido novo', async () => {
+    await syncOrderImageReferences(null, {
+      id: 7,
+      items: [
+        { id: 1, imagem: 'pedidos/7/arte.png' },
+        { id: 2, imagem: '' },
+      ],
+    });
+
+    expect(calls('image_add_reference')).toEqual([
+      { reference: 'pedidos/7/arte.png', orderItem: 'pedido:7:item:1' },
+    ]);
+    expect(calls('image_release_reference')).toEqual([]);
+  });
+
+  it('solta a imagem trocada ou de item removido e registra a nova', async () => {
+    await syncOrderImageReferences(
+      {
+        id: 7,
+        items: [
+          { id: 1, imagem: 'pedidos/7/antiga.png' },
+          { id: 2, imagem: 'pedidos/7/removido.png' },
+          { id: 3, imagem: 'pedidos/7/igual.png' },
+        ],
+      },
+      {
+        id: 7,
+        items: [
+          { id: 1, imagem: 'pedidos/7/nova.png' },
+          { id: 3, imagem: 'pedidos/7/igual.png' },
+        ],
+      }
+    );
+
+    expect(calls('image_release_reference')).toEqual([
+      { reference: 'pedidos/7/antiga.png', orderItem: 'pedido:7:item:1' },
+      { reference: 'pedidos/7/removido.png', orderItem: 'pedido:7:item:2' },
+    ]);
+    expect(calls('image_add_reference')).toEqual([
+      { reference: 'pedidos/7/nova.png', orderItem: 'pedido:7:item:1' },
+    ]);
+  });
+
+  it('solta todas as imagens ao excluir e ignora as que não estão no disco', async () => {
+    invokeMock.mockRejectedValueOnce({ kind: 'validation', message: 'Imagem não encontrada' });
+
+    await expect(
+      syncOrderImageReferences(
+        {
+          id: 9,
+          items: [
+            { id: 1, imagem: 'pedidos/9/so-no-servidor.png' },
+            { id: 2, imagem: 'pedidos/9/local.png' },
+          ],
+        },
+        null
+      )
+    ).resolves.toBeUndefined();
+
+    expect(calls('image_release_reference')).toHaveLength(2);
+  });
+});
@@ -1,5 +1,10 @@
 import { apiClient, getApiUrl } from '../api/client';
-import { getLocalImagePath, loadLocalImageAsBase64, cacheImageFromUrl } from './localImageManager';
+import {
+  getLocalImagePath,
+  loadLocalImageAsBase64,
+  cacheImageFromUrl,
+  localImageSrc,
+} from './localImageManager';
 import { isTauri } from './isTauri';
 
 /**
@@ -206,6 +211,25 @@ export async function loadAuthenticatedImage(imagePath: string): Promise<string>
   return downloadPromise;
 }
 
+/**
+ * URL para exibir a imagem em <img>. No Tauri, imagens com cópia local vêm
+ * pelo protocolo sgpimg:// (com `width`, a miniatura mais adequada), sem
+ * base64 pelo IPC; as demais são baixadas por loadAuthenticatedImage, que
+ * guarda a cópia local para a próxima vez.
+ *
+ * @param imagePath - Referência do servidor, URL, caminho local ou base64
+ * @param width - Maior lado em que a imagem será exibida, em px
+ */
+export async function loadImagePreviewSrc(imagePath: string, width?: number): Promise<string> {
+  if (isTauri() && !imagePath.startsWith('data:image/')) {
+    const localPath = await getLocalImagePath(normalizeImageUrl(imagePath)).catch(() => null);
+    if (localPath) {
+      return localImageSrc(localPath, width);
+    }
+  }
+  return loadAuthenticatedImage(imagePath);
+}
+
 /**
  * Implementação interna do carregamento. Chamada sempre com URL já normalizada
  * e garantidamente não duplicada (garantia do in-flight map acima).
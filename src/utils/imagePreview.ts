@@ -1,4 +1,4 @@
-import { loadLocalImageAsBase64 } from './localImageManager';
+import { loadLocalImageAsBase64, localImageSrc } from './localImageManager';
 import { loadAuthenticatedImage } from './imageLoader';
 import { isTauri } from './isTauri';
 
@@ -45,6 +45,10 @@ export async function getImagePreviewUrl(
      !imageReference.startsWith('images/') &&
      (imageReference.includes('/') || imageReference.includes('\\')))
   )) {
+    // TIFF e PDF não abrem em <img>: sgpimg:// serve a prévia gerada no core
+    if (/\.(tiff?|pdf)$/i.test(imageReference)) {
+      return localImageSrc(imageReference, 1024);
+    }
     try {
       const base64 = await loadLocalImageAsBase64(imageReference);
       return base64;
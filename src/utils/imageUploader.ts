@@ -1,6 +1,13 @@
-import { readImageFile } from './localImageManager';
+import {
+  addImageReference,
+  linkServerReference,
+  readImageFile,
+  releaseImageReference,
+} from './localImageManager';
 import { apiClient } from '../api/client';
 import { isTauri } from './isTauri';
+import { logger } from './logger';
+import type { OrderWithItems } from '../types';
 
 /**
  * Resultado de um upload de imagem
@@ -68,10 +75,20 @@ export async function uploadImageToServer(
     // O router de pedidos tem prefixo /pedidos, então o endpoint é /pedidos/order-items/upload-image
     // NÃO definir Content-Type manualmente - axios/navegador define automaticamente com boundary
     const response = await apiClient.post('/pedidos/order-items/upload-image', formData);
+    const serverReference: string | null =
+      response.data.server_reference || response.data.image_reference || response.data.path || response.data.url;
+
+    // O item salvo guarda a referência do servidor; ligá-la ao arquivo local
+    // permite registrar o item como dono da imagem depois de salvar o pedido
+    if (serverReference) {
+      await linkServerReference(localPath, serverReference).catch((error) =>
+        logger.warn('[uploadImageToServer] Não foi possível ligar a referência do servidor:', error)
+      );
+    }
 
     return {
       success: true,
-      server_reference: response.data.server_reference || response.data.image_reference || response.data.path || response.data.url,
+      server_reference: serverReference,
     };
   } catch (error: unknown) {
     // eslint-disable-next-line @typescript-eslint/no-explicit-any
@@ -131,3 +148,61 @@ export function needsUpload(imageReference: string | null | undefined): boolean
   return imageReference.includes('/') || imageReference.includes('\\');
 }
 
+
+type OrderImages = Pick<OrderWithItems, 'id'> & {
+  items?: Array<{ id?: number; imagem?: string | null }>;
+};
+
+/** Imagem de cada item do pedido, pela chave `pedido:<id>:item:<id>` */
+function imagesByItem(order: OrderImages | null): Map<string, string> {
+  const images = new Map<string, string>();
+  if (!order) {
+    return images;
+  }
+  for (const item of order.items ?? []) {
+    if (item.id && item.imagem) {
+      images.set(`pedido:${order.id}:item:${item.id}`, item.imagem);
+    }
+  }
+  return images;
+}
+
+/**
+ * Atualiza quem usa cada imagem local depois de salvar ou excluir um pedido.
+ * Itens novos ou com imagem trocada registram a imagem; itens removidos ou
+ * com imagem trocada soltam a anterior, que vira órfã quando ninguém mais a usa.
+ *
+ * Imagens que só existem no servidor não estão no armazenamento local; essas
+ * falhas são esperadas e apenas registradas no log.
+ *
+ * @param previous - Pedido como estava antes (null ao criar)
+ * @param saved - Pedido como ficou (null ao excluir)
+ */
+export async function syncOrderImageReferences(
+  previous: OrderImages | null,
+  saved: OrderImages | null
+): Promise<void> {
+  if (!isTauri()) {
+    return;
+  }
+
+  const before = imagesByItem(previous);
+  const after = imagesByItem(saved);
+  const tasks: Promise<number>[] = [];
+  before.forEach((image, orderItem) => {
+    if (after.get(orderItem) !== image) {
+      tasks.push(releaseImageReference(image, orderItem));
+    }
+  });
+  after.forEach((image, orderItem) => {
+    if (before.get(orderItem) !== image) {
+      tasks.push(addImageReference(image, orderItem));
+    }
+  });
+
+  const results = await Promise.allSettled(tasks);
+  const failed = results.filter((result) => result.status === 'rejected').length;
+  if (failed > 0) {
+    logger.debug(`[syncOrderImageReferences] ${failed} imagem(ns) sem cópia local`);
+  }
+}
@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core';
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
 
 /**
  * Metadados de uma imagem salva localmente
@@ -10,13 +10,18 @@ export interface LocalImageMetadata {
   mime_type: string;
   uploaded: boolean;
   server_reference: string | null;
+  /** SHA-256 do conteúdo */
+  content_hash?: string | null;
+  /** Referência estável (`sha256:<hash>`), aceita por getLocalImagePath */
+  reference?: string | null;
 }
 
 /**
  * Salva uma imagem localmente antes de qualquer upload
- * Converte File para bytes e envia para Rust
+ * Converte File para bytes e envia para Rust. O arquivo é guardado sem
+ * conversão; TIFF e PDF ganham prévia para exibição (veja localImageSrc)
  * 
- * @param file - Arquivo de imagem selecionado pelo usuário
+ * @param file - Imagem (JPEG, PNG, WebP, GIF, TIFF, BMP) ou PDF selecionado pelo usuário
  * @returns Metadados da imagem salva localmente
  */
 export async function saveImageLocally(file: File): Promise<LocalImageMetadata> {
@@ -26,7 +31,7 @@ export async function saveImageLocally(file: File): Promise<LocalImageMetadata>
   
   const metadata = await invoke<LocalImageMetadata>('save_image_locally', {
     imageData: bytes,
-    mimeType: file.type || 'image/jpeg',
+    mimeType: file.type || 'application/octet-stream',
   });
   
   return metadata;
@@ -35,7 +40,7 @@ export async function saveImageLocally(file: File): Promise<LocalImageMetadata>
 /**
  * Obtém caminho local de uma imagem (cache ou caminho direto)
  * 
- * @param imageReference - Referência da imagem (caminho local ou referência do servidor)
+ * @param imageReference - Caminho local, `sha256:<hash>`, URL baixada ou referência do servidor
  * @returns Caminho local da imagem se encontrada, null caso contrário
  */
 export async function getLocalImagePath(
@@ -52,16 +57,19 @@ export async function getLocalImagePath(
 
 /**
  * Carrega imagem local como base64 (apenas para preview/impressão)
- * NÃO usar para armazenar em estado
+ * NÃO usar para armazenar em estado. Para exibir em <img>, prefira localImageSrc
  * 
  * @param localPath - Caminho local da imagem
+ * @param grantToken - Token de pickImageFile, para arquivos fora do diretório de imagens
  * @returns Data URL base64 da imagem
  */
 export async function loadLocalImageAsBase64(
-  localPath: string
+  localPath: string,
+  grantToken?: string
 ): Promise<string> {
   return await invoke<string>('load_local_image_as_base64', {
     localPath,
+    grantToken: grantToken ?? null,
   });
 }
 
@@ -70,11 +78,16 @@ export async function loadLocalImageAsBase64(
  * Útil para upload para servidor
  * 
  * @param localPath - Caminho local da imagem
+ * @param grantToken - Token de pickImageFile, para arquivos fora do diretório de imagens
  * @returns Array de bytes da imagem
  */
-export async function readImageFile(localPath: string): Promise<Uint8Array> {
+export async function readImageFile(
+  localPath: string,
+  grantToken?: string
+): Promise<Uint8Array> {
   const bytes = await invoke<number[]>('read_image_file', {
     localPath,
+    grantToken: grantToken ?? null,
   });
   
   return new Uint8Array(bytes);
@@ -100,19 +113,22 @@ export async function cacheImageFromUrl(
 }
 
 /**
- * Processa e salva uma imagem (redimensiona se necessário)
+ * Processa e salva uma imagem: aplica a rotação do EXIF, reduz só se passar
+ * das dimensões máximas e mantém PNG quando há transparência
  * 
  * @param imageData - Dados binários da imagem
  * @param maxWidth - Largura máxima (opcional)
  * @param maxHeight - Altura máxima (opcional)
- * @param quality - Qualidade JPEG (0-100, opcional, padrão: 85)
+ * @param quality - Qualidade JPEG (1-100, opcional, padrão: 85)
+ * @param stripMetadata - Remove EXIF/GPS mesmo quando a imagem não precisa mudar
  * @returns Metadados da imagem processada
  */
 export async function processAndSaveImage(
   imageData: Uint8Array,
   maxWidth?: number,
   maxHeight?: number,
-  quality?: number
+  quality?: number,
+  stripMetadata = false
 ): Promise<LocalImageMetadata> {
   const bytes = Array.from(imageData);
   
@@ -121,9 +137,53 @@ export async function processAndSaveImage(
     maxWidth: maxWidth || null,
     maxHeight: maxHeight || null,
     quality: quality || null,
+    stripMetadata,
   });
 }
 
+/** Valor de `accept` dos campos de arte: imagens comuns e formatos de impressão */
+export const ARTWORK_ACCEPT = 'image/*,.tif,.tiff,.bmp,.pdf,application/pdf';
+
+/** Formatos de impressão: guardados sem conversão, só ganham prévia */
+const PRINT_MIME_TYPES = ['image/tiff', 'image/bmp', 'application/pdf'];
+const PRINT_EXTENSIONS = ['tif', 'tiff', 'bmp', 'pdf'];
+
+function extensionOf(file: File): string {
+  return file.name.split('.').pop()?.toLowerCase() ?? '';
+}
+
+/** Indica se o arquivo pode ser usado como arte (imagem, TIFF, BMP ou PDF) */
+export function isArtworkFile(file: File): boolean {
+  return (
+    file.type.startsWith('image/') ||
+    file.type === 'application/pdf' ||
+    PRINT_EXTENSIONS.includes(extensionOf(file))
+  );
+}
+
+/**
+ * Salva a arte de um item de produção. TIFF, BMP e PDF vão para o
+ * armazenamento como vieram; PNG, JPEG, WebP e GIF passam por
+ * processAndSaveImage, que mantém PNG quando há transparência.
+ *
+ * @param file - Arquivo escolhido ou arrastado pelo usuário
+ * @param maxWidth - Largura máxima para os formatos processados
+ * @param maxHeight - Altura máxima para os formatos processados
+ * @param quality - Qualidade JPEG para os formatos processados
+ */
+export async function saveArtworkLocally(
+  file: File,
+  maxWidth?: number,
+  maxHeight?: number,
+  quality?: number
+): Promise<LocalImageMetadata> {
+  if (PRINT_MIME_TYPES.includes(file.type) || PRINT_EXTENSIONS.includes(extensionOf(file))) {
+    return await saveImageLocally(file);
+  }
+  const imageData = new Uint8Array(await file.arrayBuffer());
+  return await processAndSaveImage(imageData, maxWidth, maxHeight, quality);
+}
+
 /**
  * Verifica se uma imagem existe localmente
  * 
@@ -137,3 +197,153 @@ export async function imageExistsLocally(
   return path !== null;
 }
 
+/**
+ * Registra que um item de pedido usa a imagem
+ *
+ * @param reference - `sha256:<hash>`, referência do servidor ligada por linkServerReference ou caminho local
+ * @param orderItem - Identificador do item (`pedido:<id>:item:<id>`)
+ * @returns Total de itens que usam a imagem
+ */
+export async function addImageReference(
+  reference: string,
+  orderItem: string
+): Promise<number> {
+  return await invoke<number>('image_add_reference', { reference, orderItem });
+}
+
+/**
+ * Solta a referência do item; a imagem sem itens sai na limpeza de órfãs, após o prazo
+ *
+ * @returns Quantos itens ainda usam a imagem
+ */
+export async function releaseImageReference(
+  reference: string,
+  orderItem: string
+): Promise<number> {
+  return await invoke<number>('image_release_reference', { reference, orderItem });
+}
+
+/**
+ * Liga a referência devolvida pela API no upload à imagem local,
+ * para que getLocalImagePath também a resolva
+ */
+export async function linkServerReference(
+  reference: string,
+  serverReference: string
+): Promise<void> {
+  await invoke('image_link_server_reference', { reference, serverReference });
+}
+
+export interface ImageQuota {
+  max_cache_bytes: number;
+  max_cache_age_days: number;
+  orphan_grace_hours: number;
+}
+
+export interface ImageUsageBucket {
+  count: number;
+  bytes: number;
+}
+
+export interface ImageStorageUsage {
+  objects: ImageUsageBucket;
+  cached: ImageUsageBucket;
+  orphans: ImageUsageBucket;
+  quota: ImageQuota;
+}
+
+export interface OrphanImage {
+  path: string;
+  kind: 'unreferenced' | 'untracked';
+  size: number;
+}
+
+export interface ImageCleanupReport {
+  evicted_cached: number;
+  pruned_entries: number;
+  removed_orphans: number;
+  freed_bytes: number;
+  orphans: OrphanImage[];
+}
+
+/**
+ * Espaço ocupado pelas imagens locais
+ */
+export async function getImageStorageUsage(): Promise<ImageStorageUsage> {
+  return await invoke<ImageStorageUsage>('image_storage_usage');
+}
+
+/**
+ * Aplica a cota do cache e lista as imagens órfãs
+ *
+ * @param removeOrphans - Apaga as órfãs em vez de só listá-las
+ */
+export async function cleanupImageStorage(
+  removeOrphans = false
+): Promise<ImageCleanupReport> {
+  return await invoke<ImageCleanupReport>('image_storage_cleanup', { removeOrphans });
+}
+
+export async function getImageQuota(): Promise<ImageQuota> {
+  return await invoke<ImageQuota>('image_quota_get');
+}
+
+export async function setImageQuota(quota: ImageQuota): Promise<void> {
+  await invoke('image_quota_set', { quota });
+}
+
+export interface PickedImageFile {
+  path: string;
+  file_name: string;
+  file_size: number;
+  /** Libera a leitura deste arquivo em readImageFile/loadLocalImageAsBase64 */
+  grant_token: string;
+}
+
+/**
+ * Abre o seletor de arquivos nativo. Arquivos fora do diretório de imagens
+ * só podem ser lidos com o token devolvido aqui.
+ *
+ * @returns Arquivo escolhido ou null se o usuário cancelar
+ */
+export async function pickImageFile(): Promise<PickedImageFile | null> {
+  return await invoke<PickedImageFile | null>('pick_image_file');
+}
+
+export interface ImageVariant {
+  path: string;
+  width: number;
+  height: number;
+  mime_type: string;
+  /** Lado da miniatura (128, 512) ou null para o original */
+  variant: number | null;
+  data_url?: string;
+}
+
+/**
+ * Melhor variante para exibir a imagem com o maior lado em `size` px.
+ * Use em listas e prévias no lugar de loadLocalImageAsBase64.
+ *
+ * @param reference - `sha256:<hash>`, URL baixada, referência do servidor ou caminho local
+ * @param size - Maior lado desejado, em px
+ * @param asDataUrl - Devolve a imagem embutida em `data_url`
+ */
+export async function getImageVariant(
+  reference: string,
+  size: number,
+  asDataUrl = false
+): Promise<ImageVariant> {
+  return await invoke<ImageVariant>('image_best_variant', { reference, size, asDataUrl });
+}
+
+/**
+ * URL `sgpimg://` para usar direto em <img src>, sem base64 pelo IPC.
+ * Para TIFF e PDF a URL aponta para a prévia (no PDF, a primeira página)
+ *
+ * @param reference - `sha256:<hash>` (cacheado como imutável), URL baixada ou referência do servidor
+ * @param width - Maior lado desejado; devolve a miniatura mais adequada
+ */
+export function localImageSrc(reference: string, width?: number): string {
+  const src = convertFileSrc(reference, 'sgpimg');
+  return width ? `${src}?w=${Math.round(width)}` : src;
+}